@@ -0,0 +1,472 @@
+//! What a search result does when executed. Every result kind is its own type implementing
+//! [`Actionable`]; `run_query` stores them boxed in `AppState::pending_actions` under the result
+//! id, and `execute_action` only runs the shared confirmation and window handling around
+//! [`Actionable::execute`].
+
+use std::path::Path;
+
+use tauri::AppHandle;
+use url::Url;
+use windows::Win32::Foundation::RECT;
+
+use crate::{
+    bookmarks::BookmarkEntry,
+    commands::{
+        bookmark_folder_url, bookmark_manager_url, launch_custom_launcher, launch_script,
+        launch_uwp_app, launch_win32_app, launch_win32_app_on_monitor, open_folder,
+        open_in_bookmark_browser, open_terminal, open_url, open_url_with_mode, root_url,
+        shell_execute_path, shell_execute_raw, uninstall_app,
+    },
+    config::{AppConfig, CustomLauncher},
+    indexer,
+    models::{AppType, ApplicationInfo, ResultDetails},
+    state::AppState,
+    usage::URL_USAGE_PREFIX,
+    windows_utils,
+};
+
+/// A result's action as stored in `AppState::pending_actions`.
+pub type PendingAction = Box<dyn Actionable + Send>;
+
+/// Everything an action may need while executing, resolved by `execute_action` beforehand.
+pub struct ActionContext<'a> {
+    pub app_handle: &'a AppHandle,
+    pub state: &'a AppState,
+    pub config: &'a AppConfig,
+    pub run_as_admin: bool,
+    /// Open URLs in a private browser window.
+    pub private: bool,
+    /// Open the site's home page instead of the URL itself.
+    pub site_root: bool,
+    /// Open URLs without taking focus; see [`Actionable::opens_web_page`].
+    pub background: bool,
+    /// Work area of the monitor the launcher was on, when `launch_on_active_monitor` is set.
+    pub target_work_area: Option<RECT>,
+    /// Window to paste copied text into, as an `HWND`.
+    pub paste_target: Option<isize>,
+}
+
+/// One kind of result action. Only [`execute`](Actionable::execute) is required; the other
+/// methods describe how `execute_action` and the result list treat the action and default to
+/// the plain behavior.
+pub trait Actionable: CloneAction {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String>;
+
+    /// Whether the user has to confirm before the action runs. An error means the action is
+    /// not allowed with the current config at all.
+    fn requires_confirmation(
+        &self,
+        _config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    /// Opens a web page, so it follows `open_urls_in_background`.
+    fn opens_web_page(&self) -> bool {
+        false
+    }
+
+    /// Only copies to the clipboard, so the launcher stays open unless the text is pasted.
+    fn copies_text(&self) -> bool {
+        false
+    }
+
+    /// Usage-statistics key for the target, independent of the result id and of the mode the
+    /// result came from. `None` for actions that are not counted.
+    fn usage_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Full details for `get_result_details`.
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        None
+    }
+
+    /// Suffixes that can tell the result apart from same-named ones, from most to least
+    /// concise.
+    fn title_suffix_candidates(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Lets boxed actions be cloned out of `pending_actions`; implemented for every `Clone` action.
+pub trait CloneAction {
+    fn clone_action(&self) -> PendingAction;
+}
+
+impl<T: Actionable + Clone + Send + 'static> CloneAction for T {
+    fn clone_action(&self) -> PendingAction {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for PendingAction {
+    fn clone(&self) -> Self {
+        self.clone_action()
+    }
+}
+
+/// Launches an indexed app, folder or script.
+#[derive(Clone)]
+pub struct LaunchApp(pub ApplicationInfo);
+
+impl Actionable for LaunchApp {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        let app = &self.0;
+        match app.app_type {
+            // 非提权进程无法移动提权程序的窗口，提权启动时保持原样
+            AppType::Win32 => match ctx.target_work_area {
+                Some(work_area) if !ctx.run_as_admin => launch_win32_app_on_monitor(app, work_area),
+                _ => launch_win32_app(app, ctx.run_as_admin),
+            },
+            AppType::Uwp => launch_uwp_app(&app.path),
+            AppType::Folder => open_folder(&app.path),
+            AppType::Script => launch_script(app, ctx.run_as_admin),
+        }
+    }
+
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        run_as_admin: bool,
+    ) -> Result<bool, String> {
+        Ok(run_as_admin && config.confirm_admin_launch)
+    }
+
+    fn usage_key(&self) -> Option<String> {
+        Some(self.0.id.clone())
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        let mut app = self.0.clone();
+        app.icon_b64.clear();
+        Some(ResultDetails::Application(app))
+    }
+
+    fn title_suffix_candidates(&self) -> Vec<String> {
+        let app = &self.0;
+        let target = Path::new(app.source_path.as_deref().unwrap_or(&app.path));
+        let parent = target.parent();
+        [
+            app.description.clone(),
+            parent
+                .and_then(|dir| dir.file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            parent.map(|dir| dir.to_string_lossy().into_owned()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|value| !value.trim().is_empty())
+        .collect()
+    }
+}
+
+/// Opens a bookmark's URL.
+#[derive(Clone)]
+pub struct OpenBookmark(pub BookmarkEntry);
+
+impl Actionable for OpenBookmark {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        let target = if ctx.site_root {
+            root_url(&self.0.url)
+        } else {
+            self.0.url.clone()
+        };
+        open_url_with_mode(
+            ctx.app_handle,
+            ctx.state,
+            &target,
+            ctx.private,
+            ctx.background,
+        )
+    }
+
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        Ok(config.confirm_url_open)
+    }
+
+    fn opens_web_page(&self) -> bool {
+        true
+    }
+
+    fn usage_key(&self) -> Option<String> {
+        Some(self.0.id.clone())
+    }
+
+    fn details(&self, folder_separator: &str) -> Option<ResultDetails> {
+        Some(bookmark_details(&self.0, folder_separator))
+    }
+
+    fn title_suffix_candidates(&self) -> Vec<String> {
+        [
+            self.0.folder_segments.last().cloned(),
+            Some(self.0.url.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|value| !value.trim().is_empty())
+        .collect()
+    }
+}
+
+/// Opens the browser's bookmark manager at the bookmark instead of its URL.
+#[derive(Clone)]
+pub struct EditBookmark(pub BookmarkEntry);
+
+impl Actionable for EditBookmark {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        open_url(ctx.app_handle, &bookmark_manager_url(&self.0))
+    }
+
+    fn details(&self, folder_separator: &str) -> Option<ResultDetails> {
+        Some(bookmark_details(&self.0, folder_separator))
+    }
+}
+
+/// Opens the browser's bookmark manager at the folder containing the bookmark.
+#[derive(Clone)]
+pub struct OpenBookmarkFolder(pub BookmarkEntry);
+
+impl Actionable for OpenBookmarkFolder {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        open_in_bookmark_browser(ctx.app_handle, ctx.config, &bookmark_folder_url(&self.0))
+    }
+
+    fn details(&self, folder_separator: &str) -> Option<ResultDetails> {
+        Some(bookmark_details(&self.0, folder_separator))
+    }
+}
+
+fn bookmark_details(entry: &BookmarkEntry, folder_separator: &str) -> ResultDetails {
+    ResultDetails::Bookmark {
+        title: entry.title.clone(),
+        url: entry.url.clone(),
+        folder_path: entry.folder_segments.join(folder_separator),
+        keywords: entry.keywords.clone(),
+    }
+}
+
+/// Opens the configured terminal in a folder result's directory.
+#[derive(Clone)]
+pub struct OpenTerminal(pub String);
+
+impl Actionable for OpenTerminal {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        open_terminal(&self.0, ctx.config.terminal, ctx.run_as_admin)
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::Path {
+            path: self.0.clone(),
+        })
+    }
+}
+
+/// Opens a URL typed into the launcher.
+#[derive(Clone)]
+pub struct OpenUrl(pub String);
+
+impl Actionable for OpenUrl {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        let target = if ctx.site_root {
+            root_url(&self.0)
+        } else {
+            self.0.clone()
+        };
+        open_url_with_mode(
+            ctx.app_handle,
+            ctx.state,
+            &target,
+            ctx.private,
+            ctx.background,
+        )
+    }
+
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        if config.offline_mode {
+            return Err("离线模式下不会打开网址".into());
+        }
+        Ok(config.confirm_url_open)
+    }
+
+    fn opens_web_page(&self) -> bool {
+        true
+    }
+
+    fn usage_key(&self) -> Option<String> {
+        // 统一大小写与尾部斜杠等写法，忽略片段
+        let normalized = match Url::parse(&self.0) {
+            Ok(mut parsed) => {
+                parsed.set_fragment(None);
+                parsed.to_string()
+            }
+            Err(_) => self.0.trim().to_lowercase(),
+        };
+        Some(format!("{URL_USAGE_PREFIX}{normalized}"))
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::Url {
+            url: self.0.clone(),
+        })
+    }
+}
+
+/// Opens an existing local file or folder; offered instead of [`OpenUrl`] in offline mode.
+#[derive(Clone)]
+pub struct OpenPath(pub String);
+
+impl Actionable for OpenPath {
+    fn execute(&self, _ctx: &ActionContext) -> Result<(), String> {
+        shell_execute_path(Path::new(&self.0), false)
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::Path {
+            path: self.0.clone(),
+        })
+    }
+}
+
+/// Opens a web search for the query, holding the expanded search URL.
+#[derive(Clone)]
+pub struct WebSearch(pub String);
+
+impl Actionable for WebSearch {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        open_url_with_mode(
+            ctx.app_handle,
+            ctx.state,
+            &self.0,
+            ctx.private,
+            ctx.background,
+        )
+    }
+
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        if config.offline_mode {
+            return Err("离线模式下不会打开网址".into());
+        }
+        Ok(config.confirm_url_open)
+    }
+
+    fn opens_web_page(&self) -> bool {
+        true
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::Url {
+            url: self.0.clone(),
+        })
+    }
+}
+
+/// Runs a user-defined custom launcher.
+#[derive(Clone)]
+pub struct LaunchCustom(pub CustomLauncher);
+
+impl Actionable for LaunchCustom {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        launch_custom_launcher(ctx.app_handle, &self.0, ctx.run_as_admin)
+    }
+
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        run_as_admin: bool,
+    ) -> Result<bool, String> {
+        Ok(run_as_admin && config.confirm_admin_launch)
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::CustomLauncher(self.0.clone()))
+    }
+}
+
+/// Runs an app's registry `UninstallString`, or opens Apps & features when it is unknown.
+#[derive(Clone)]
+pub struct Uninstall(pub Option<String>);
+
+impl Actionable for Uninstall {
+    fn execute(&self, _ctx: &ActionContext) -> Result<(), String> {
+        uninstall_app(self.0.as_deref())
+    }
+
+    // 卸载无法撤销，始终要求前端显式确认
+    fn requires_confirmation(
+        &self,
+        _config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        Ok(true)
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        let (program, arguments) = indexer::split_command_line(self.0.as_deref()?);
+        Some(ResultDetails::Command { program, arguments })
+    }
+}
+
+/// Runs a shell command typed in run mode.
+#[derive(Clone)]
+pub struct RunCommand {
+    pub program: String,
+    pub arguments: String,
+}
+
+impl Actionable for RunCommand {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        shell_execute_raw(&self.program, Some(&self.arguments), None, ctx.run_as_admin)
+    }
+
+    // 执行 shell 命令风险较高，始终要求前端显式确认
+    fn requires_confirmation(
+        &self,
+        config: &AppConfig,
+        _run_as_admin: bool,
+    ) -> Result<bool, String> {
+        if !config.enable_run_mode {
+            return Err("运行模式未启用".into());
+        }
+        Ok(true)
+    }
+
+    fn details(&self, _folder_separator: &str) -> Option<ResultDetails> {
+        Some(ResultDetails::Command {
+            program: self.program.clone(),
+            arguments: self.arguments.clone(),
+        })
+    }
+}
+
+/// Copies the text to the clipboard and keeps the launcher open, or pastes it into
+/// [`ActionContext::paste_target`].
+#[derive(Clone)]
+pub struct CopyText(pub String);
+
+impl Actionable for CopyText {
+    fn execute(&self, ctx: &ActionContext) -> Result<(), String> {
+        windows_utils::set_clipboard_text(&self.0)?;
+        if let Some(hwnd) = ctx.paste_target {
+            windows_utils::paste_into_window(hwnd)?;
+        }
+        Ok(())
+    }
+
+    fn copies_text(&self) -> bool {
+        true
+    }
+}
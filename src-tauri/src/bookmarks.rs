@@ -1,28 +1,236 @@
 use std::{
+    collections::{HashMap, HashSet},
     env, fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 use log::{debug, warn};
+use serde::Serialize;
 use serde_json::Value;
 use sha1::{Digest, Sha1};
 
-use crate::text_utils::extend_keywords_with_pinyin;
+use crate::{
+    config::CustomBookmarkFile,
+    text_utils::{extend_keywords_with_pinyin, fold_diacritics},
+};
 
-#[derive(Debug, Clone)]
+const CHROME_BOOKMARK_MANAGER_URL: &str = "chrome://bookmarks/";
+const OPERA_BOOKMARK_MANAGER_URL: &str = "opera://bookmarks/";
+
+#[derive(Debug, Clone, Serialize)]
 pub struct BookmarkEntry {
     pub id: String,
     pub title: String,
     pub url: String,
-    pub folder_path: Option<String>,
+    /// Folder names from the profile root down to the bookmark, kept separate so names
+    /// containing `/` are never mis-split.
+    pub folder_segments: Vec<String>,
+    /// `folder_segments` with diacritics stripped, used only for matching.
+    pub folded_folder_segments: Vec<String>,
     pub keywords: Vec<String>,
+    /// Chrome's numeric node id, used to open the bookmark manager at this entry.
+    pub node_id: Option<String>,
+    /// Numeric node id of the folder containing this entry, used to open that folder in the
+    /// bookmark manager.
+    pub folder_node_id: Option<String>,
+    /// Bookmark manager page of the browser the entry was read from.
+    pub manager_url: &'static str,
+}
+
+/// Which browsers and Chrome profiles to read bookmarks from.
+#[derive(Debug, Clone)]
+pub struct BookmarkSources {
+    /// Chrome profile directory names to include; `None` includes every profile.
+    pub chrome_profiles: Option<Vec<String>>,
+    pub opera: bool,
+    pub opera_gx: bool,
+    /// Explicit bookmark files; files already covered by a discovered profile are skipped.
+    pub custom_files: Vec<CustomBookmarkFile>,
 }
 
-/// Loads Chrome bookmark entries from all detected profiles under LOCALAPPDATA.
-pub fn load_chrome_bookmarks() -> Vec<BookmarkEntry> {
+/// Parsed entries of each bookmarks file seen by a previous load, keyed by file identity.
+/// A file is reparsed only when its modification time or display label changed.
+#[derive(Debug, Default)]
+pub struct BookmarkCache {
+    files: HashMap<PathBuf, CachedBookmarkFile>,
+}
+
+#[derive(Debug)]
+struct CachedBookmarkFile {
+    modified: SystemTime,
+    label: String,
+    entries: Vec<BookmarkEntry>,
+}
+
+impl BookmarkCache {
+    /// Returns the entries of `path`, reusing the cached ones unless `force` is set or the file
+    /// changed since it was last parsed.
+    fn entries(
+        &mut self,
+        path: &Path,
+        label: &str,
+        force: bool,
+    ) -> Result<Vec<BookmarkEntry>, String> {
+        let key = file_identity(path);
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        if !force {
+            if let (Some(cached), Some(modified)) = (self.files.get(&key), modified) {
+                if cached.modified == modified && cached.label == label {
+                    debug!("reusing cached bookmarks from {:?}", path);
+                    return Ok(cached.entries.clone());
+                }
+            }
+        }
+
+        let json = read_bookmark_file(path)?;
+        let mut entries = Vec::new();
+        collect_entries_from_file(&json, label, &mut entries);
+        match modified {
+            Some(modified) => {
+                self.files.insert(
+                    key,
+                    CachedBookmarkFile {
+                        modified,
+                        label: label.to_string(),
+                        entries: entries.clone(),
+                    },
+                );
+            }
+            None => {
+                self.files.remove(&key);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Loads bookmarks from Chrome, every enabled Chromium-based browser and the custom files.
+/// Files unchanged since the previous load come from `cache` unless `force` is set; files no
+/// longer among the sources are dropped from it.
+pub fn load_bookmarks(
+    sources: BookmarkSources,
+    cache: &mut BookmarkCache,
+    force: bool,
+) -> Vec<BookmarkEntry> {
+    let chrome_dirs = allowed_chrome_profile_dirs(sources.chrome_profiles.as_deref());
+    let opera_dirs = if sources.opera {
+        opera_profile_dirs("Opera Stable")
+    } else {
+        Vec::new()
+    };
+    let opera_gx_dirs = if sources.opera_gx {
+        opera_profile_dirs("Opera GX Stable")
+    } else {
+        Vec::new()
+    };
+    let mut loaded_files: HashSet<PathBuf> = chrome_dirs
+        .iter()
+        .chain(&opera_dirs)
+        .chain(&opera_gx_dirs)
+        .map(|dir| file_identity(&dir.join("Bookmarks")))
+        .collect();
+
+    let mut entries =
+        load_chromium_bookmarks(None, CHROME_BOOKMARK_MANAGER_URL, chrome_dirs, cache, force);
+    entries.extend(load_chromium_bookmarks(
+        Some("Opera"),
+        OPERA_BOOKMARK_MANAGER_URL,
+        opera_dirs,
+        cache,
+        force,
+    ));
+    entries.extend(load_chromium_bookmarks(
+        Some("Opera GX"),
+        OPERA_BOOKMARK_MANAGER_URL,
+        opera_gx_dirs,
+        cache,
+        force,
+    ));
+
+    for file in &sources.custom_files {
+        let path = Path::new(&file.path);
+        // 同一文件已通过自动发现的配置读取过时跳过，避免重复结果
+        if !loaded_files.insert(file_identity(path)) {
+            debug!("skipping duplicate bookmarks file {:?}", path);
+            continue;
+        }
+        match cache.entries(path, &file.label(), force) {
+            Ok(file_entries) => entries.extend(file_entries),
+            Err(err) => warn!("failed to load custom bookmarks {:?}: {err}", path),
+        }
+    }
+    cache.files.retain(|key, _| loaded_files.contains(key));
+    entries
+}
+
+/// Checks that `path` is a readable Chromium bookmarks file.
+pub fn validate_bookmark_file(path: &str) -> Result<(), String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("书签文件路径不能为空".into());
+    }
+    if !Path::new(path).is_file() {
+        return Err(format!("书签文件不存在: {path}"));
+    }
+    let json = read_bookmark_file(Path::new(path))?;
+    if json
+        .get("roots")
+        .and_then(|value| value.as_object())
+        .is_none()
+    {
+        return Err(format!("{path} 不是有效的书签文件"));
+    }
+    Ok(())
+}
+
+fn read_bookmark_file(path: &Path) -> Result<Value, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("无法读取书签文件 {:?}: {err}", path))?;
+    serde_json::from_str(&content).map_err(|err| format!("无法解析书签文件 {:?}: {err}", path))
+}
+
+/// Normalizes a path for duplicate detection, falling back to a lowercase path when it can't
+/// be canonicalized.
+fn file_identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path.to_string_lossy().to_lowercase()))
+}
+
+fn allowed_chrome_profile_dirs(allowlist: Option<&[String]>) -> Vec<PathBuf> {
+    let mut profile_dirs = chrome_profile_dirs();
+    if let Some(allowlist) = allowlist {
+        profile_dirs.retain(|dir| {
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| allowlist.iter().any(|allowed| allowed == name))
+        });
+    }
+    profile_dirs
+}
+
+/// Returns the directory names of Chrome profiles that have a bookmarks file.
+pub fn chrome_profile_names() -> Vec<String> {
+    let mut names: Vec<String> = chrome_profile_dirs()
+        .iter()
+        .filter_map(|dir| dir.file_name()?.to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Loads entries from the `Bookmarks` file of each Chromium profile directory. `browser_label`
+/// prefixes the profile label so entries from different browsers stay distinguishable, and
+/// `manager_url` is the browser's bookmark manager page.
+fn load_chromium_bookmarks(
+    browser_label: Option<&str>,
+    manager_url: &'static str,
+    profile_dirs: Vec<PathBuf>,
+    cache: &mut BookmarkCache,
+    force: bool,
+) -> Vec<BookmarkEntry> {
     let mut all_entries = Vec::new();
 
-    for profile_dir in chrome_profile_dirs() {
+    for profile_dir in profile_dirs {
         let Some(profile_name) = profile_dir
             .file_name()
             .and_then(|os| os.to_str())
@@ -30,30 +238,31 @@ pub fn load_chrome_bookmarks() -> Vec<BookmarkEntry> {
         else {
             continue;
         };
-        let display_name = profile_display_label(&profile_name);
+        let display_name = match browser_label {
+            // Opera 的单配置目录（如 "Opera Stable"）直接以浏览器名称显示
+            Some(browser) if profile_name.ends_with(" Stable") => browser.to_string(),
+            Some(browser) => format!("{browser} · {}", profile_display_label(&profile_name)),
+            None => profile_display_label(&profile_name),
+        };
         let bookmarks_path = profile_dir.join("Bookmarks");
         if !bookmarks_path.is_file() {
             continue;
         }
 
-        match fs::read_to_string(&bookmarks_path) {
-            Ok(content) => match serde_json::from_str::<Value>(&content) {
-                Ok(json) => {
-                    collect_entries_from_file(&json, &display_name, &mut all_entries);
-                }
-                Err(err) => warn!(
-                    "failed to parse Chrome bookmarks {:?}: {err}",
-                    bookmarks_path
-                ),
-            },
-            Err(err) => warn!(
-                "failed to read Chrome bookmarks {:?}: {err}",
-                bookmarks_path
-            ),
+        match cache.entries(&bookmarks_path, &display_name, force) {
+            Ok(entries) => all_entries.extend(entries.into_iter().map(|entry| BookmarkEntry {
+                manager_url,
+                ..entry
+            })),
+            Err(err) => warn!("{err}"),
         }
     }
 
-    debug!("loaded {} Chrome bookmark entries", all_entries.len());
+    debug!(
+        "loaded {} {} bookmark entries",
+        all_entries.len(),
+        browser_label.unwrap_or("Chrome")
+    );
     all_entries
 }
 
@@ -82,30 +291,80 @@ fn chrome_profile_dirs() -> Vec<PathBuf> {
     results
 }
 
+/// Opera keeps a single profile directly in `%APPDATA%\Opera Software\<channel>`; newer builds
+/// may add further profiles as subdirectories.
+fn opera_profile_dirs(channel: &str) -> Vec<PathBuf> {
+    let mut results = Vec::new();
+    let Ok(app_data) = env::var("APPDATA") else {
+        return results;
+    };
+    let base_path = Path::new(&app_data).join("Opera Software").join(channel);
+    if !base_path.is_dir() {
+        return results;
+    }
+
+    if base_path.join("Bookmarks").is_file() {
+        results.push(base_path.clone());
+    }
+    if let Ok(entries) = fs::read_dir(&base_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() && path.join("Bookmarks").is_file() {
+                results.push(path);
+            }
+        }
+    }
+
+    results
+}
+
 fn collect_entries_from_file(json: &Value, profile_label: &str, acc: &mut Vec<BookmarkEntry>) {
     let Some(roots) = json.get("roots").and_then(|value| value.as_object()) else {
         return;
     };
 
     for (key, node) in roots.iter() {
-        let mut path_stack = vec![profile_label.to_string()];
-        if let Some(label) = root_display_label(key) {
-            path_stack.push(label.to_string());
+        // Opera 把快速拨号、回收站等特殊文件夹放在 custom_root 下，回收站中的内容不参与索引
+        if key == "custom_root" {
+            if let Some(custom_roots) = node.as_object() {
+                for (custom_key, custom_node) in custom_roots {
+                    if custom_key != "trash" {
+                        collect_root(custom_key, custom_node, profile_label, acc);
+                    }
+                }
+            }
+            continue;
         }
+        collect_root(key, node, profile_label, acc);
+    }
+}
 
-        if let Some(children) = node.get("children").and_then(|value| value.as_array()) {
-            for child in children {
-                collect_node(child, profile_label, &mut path_stack, acc);
-            }
-        } else {
-            collect_node(node, profile_label, &mut path_stack, acc);
+fn collect_root(key: &str, node: &Value, profile_label: &str, acc: &mut Vec<BookmarkEntry>) {
+    let mut path_stack = vec![profile_label.to_string()];
+    if let Some(label) = root_display_label(key) {
+        path_stack.push(label.to_string());
+    }
+
+    if let Some(children) = node.get("children").and_then(|value| value.as_array()) {
+        let root_id = numeric_node_id(node);
+        for child in children {
+            collect_node(
+                child,
+                profile_label,
+                root_id.as_deref(),
+                &mut path_stack,
+                acc,
+            );
         }
+    } else {
+        collect_node(node, profile_label, None, &mut path_stack, acc);
     }
 }
 
 fn collect_node(
     node: &Value,
     profile_label: &str,
+    parent_id: Option<&str>,
     path_stack: &mut Vec<String>,
     acc: &mut Vec<BookmarkEntry>,
 ) {
@@ -126,8 +385,9 @@ fn collect_node(
             }
 
             if let Some(children) = node.get("children").and_then(|value| value.as_array()) {
+                let folder_id = numeric_node_id(node);
                 for child in children {
-                    collect_node(child, profile_label, path_stack, acc);
+                    collect_node(child, profile_label, folder_id.as_deref(), path_stack, acc);
                 }
             }
 
@@ -152,43 +412,54 @@ fn collect_node(
                 return;
             }
 
-            let folder_path = if path_stack.is_empty() {
-                None
-            } else {
-                Some(path_stack.join(" / "))
-            };
+            let folder_segments = path_stack.clone();
 
             let mut keywords = Vec::new();
             keywords.push(title.to_string());
             keywords.push(url.to_string());
-            if let Some(folder) = &folder_path {
-                keywords.push(folder.clone());
-                keywords.extend(folder.split('/').map(|segment| segment.trim().to_string()));
-            }
+            keywords.extend(folder_segments.iter().cloned());
             keywords.push(profile_label.to_string());
             keywords.retain(|value| !value.trim().is_empty());
             extend_keywords_with_pinyin(&mut keywords);
             keywords.sort();
             keywords.dedup();
 
-            let id = derive_bookmark_id(profile_label, node, url);
+            let folded_folder_segments = folder_segments
+                .iter()
+                .map(|segment| fold_diacritics(segment))
+                .collect();
+            let id = derive_bookmark_id(profile_label, node, url, &folder_segments);
             acc.push(BookmarkEntry {
                 id,
                 title: title.to_string(),
                 url: url.to_string(),
-                folder_path,
+                folder_segments,
+                folded_folder_segments,
                 keywords,
+                node_id: numeric_node_id(node),
+                folder_node_id: parent_id.map(str::to_string),
+                manager_url: CHROME_BOOKMARK_MANAGER_URL,
             });
         }
         _ => {}
     }
 }
 
+/// Returns the node's `id` when it is Chrome's numeric form.
+fn numeric_node_id(node: &Value) -> Option<String> {
+    node.get("id")
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()))
+        .map(str::to_string)
+}
+
 fn root_display_label(key: &str) -> Option<&'static str> {
     match key {
         "bookmark_bar" => Some("书签栏"),
         "other" => Some("其他书签"),
         "synced" => Some("已同步"),
+        "speedDial" => Some("快速拨号"),
+        "unsorted" => Some("未分类"),
         _ => None,
     }
 }
@@ -204,7 +475,12 @@ fn is_supported_url(url: &str) -> bool {
     url.starts_with("http://") || url.starts_with("https://")
 }
 
-fn derive_bookmark_id(profile_label: &str, node: &Value, url: &str) -> String {
+fn derive_bookmark_id(
+    profile_label: &str,
+    node: &Value,
+    url: &str,
+    folder_segments: &[String],
+) -> String {
     if let Some(guid) = node.get("guid").and_then(|value| value.as_str()) {
         return format!("{profile_label}:{guid}");
     }
@@ -215,6 +491,11 @@ fn derive_bookmark_id(profile_label: &str, node: &Value, url: &str) -> String {
     let mut hasher = Sha1::new();
     hasher.update(profile_label.as_bytes());
     hasher.update(url.as_bytes());
+    // 同一网址收藏在不同文件夹时也需要得到不同的 id
+    for segment in folder_segments {
+        hasher.update([0u8]);
+        hasher.update(segment.as_bytes());
+    }
     format!("{profile_label}:{}", hex::encode(hasher.finalize()))
 }
 
@@ -227,3 +508,51 @@ mod hex {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_url_in_two_folders_gets_distinct_ids() {
+        let root = serde_json::json!({
+            "type": "folder",
+            "name": "Bookmarks bar",
+            "children": [
+                {
+                    "type": "folder",
+                    "name": "Work",
+                    "children": [
+                        { "type": "url", "name": "Docs", "url": "https://docs.example.com/" }
+                    ]
+                },
+                {
+                    "type": "folder",
+                    "name": "Personal",
+                    "children": [
+                        { "type": "url", "name": "Docs", "url": "https://docs.example.com/" }
+                    ]
+                }
+            ]
+        });
+        let mut entries = Vec::new();
+        collect_node(&root, "Chrome", None, &mut Vec::new(), &mut entries);
+
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[0].id, entries[1].id);
+        assert_eq!(entries[0].folder_segments, ["Bookmarks bar", "Work"]);
+        assert_eq!(entries[1].folder_segments, ["Bookmarks bar", "Personal"]);
+    }
+
+    #[test]
+    fn chinese_title_gets_pinyin_initials_keyword() {
+        let node = serde_json::json!({
+            "type": "url",
+            "name": "百度一下",
+            "url": "https://www.baidu.com/"
+        });
+        let mut entries = Vec::new();
+        collect_node(&node, "Chrome", None, &mut Vec::new(), &mut entries);
+        assert!(entries[0].keywords.iter().any(|keyword| keyword == "bdyx"));
+    }
+}
@@ -1,50 +1,101 @@
 use std::{
-    collections::HashMap,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
     ffi::{OsStr, OsString},
     path::Path,
     ptr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use serde::Deserialize;
+use sha1::{Digest, Sha1};
 use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_opener::OpenerExt;
+use url::Url;
 use windows::{
     core::{HSTRING, PCWSTR},
     Win32::{
-        Foundation::HWND,
-        System::Com::{CoCreateInstance, CLSCTX_LOCAL_SERVER},
+        Foundation::{CloseHandle, HWND, RECT},
+        System::{
+            Com::{CoCreateInstance, CLSCTX_LOCAL_SERVER},
+            Threading::GetProcessId,
+        },
         UI::{
             Shell::{
-                ApplicationActivationManager, IApplicationActivationManager, ShellExecuteW,
-                ACTIVATEOPTIONS,
+                ApplicationActivationManager, IApplicationActivationManager, ShellExecuteExW,
+                ShellExecuteW, ACTIVATEOPTIONS, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
             },
-            WindowsAndMessaging::SW_SHOWNORMAL,
+            WindowsAndMessaging::{SHOW_WINDOW_CMD, SW_SHOWMINNOACTIVE, SW_SHOWNORMAL},
         },
     },
 };
 
-use crate::windows_utils::{os_str_to_wide, ComGuard};
+use crate::windows_utils::{self, extract_icon_from_path, os_str_to_wide, ComGuard};
 
 use crate::{
+    actions::{
+        ActionContext, CopyText, EditBookmark, LaunchApp, LaunchCustom, OpenBookmark,
+        OpenBookmarkFolder, OpenPath, OpenTerminal, OpenUrl, PendingAction, RunCommand, Uninstall,
+        WebSearch,
+    },
     bookmarks::{self, BookmarkEntry},
-    config::AppConfig,
-    hotkey::bind_hotkey,
+    config::{
+        self, AppConfig, CustomBookmarkFile, CustomLauncher, CustomTargetKind, LaunchOrder,
+        ProfileOverrides, RunShell, ScoringWeights, Terminal, GOOGLE_SEARCH_TEMPLATE,
+        SCRIPT_EXTENSIONS,
+    },
+    crash, elevation,
+    hotkey::{self, bind_hotkey},
     hotkey_capture, indexer,
-    models::{AppType, ApplicationInfo, SearchResult},
-    state::{AppState, PendingAction},
+    models::{
+        result_group, AppType, ApplicationInfo, BenchmarkResult, ExecuteOutcome, ModeInfo,
+        QueryDiagnostics, ResultDetails, ScoreBreakdown, SearchResult,
+    },
+    state::{AppState, MAX_RECENT_LAUNCHES},
+    text_utils::{collation_key, fold_diacritics, quote_argument, quote_path},
+    usage::{UsageRecord, UsageStats, URL_USAGE_PREFIX},
 };
 
 const MIN_QUERY_DELAY_MS: u64 = 50;
 const MAX_QUERY_DELAY_MS: u64 = 2000;
 const MIN_RESULT_LIMIT: u32 = 10;
 const MAX_RESULT_LIMIT: u32 = 60;
+const MIN_MATCH_QUERY_CHARS: usize = 16;
+const MAX_LAUNCH_FOCUS_DELAY_MS: u64 = 2000;
+/// Command-line switches that open a URL in a private window, keyed by browser executable.
+const PRIVATE_BROWSING_FLAGS: &[(&str, &str)] = &[
+    ("chrome.exe", "--incognito"),
+    ("brave.exe", "--incognito"),
+    ("msedge.exe", "--inprivate"),
+    ("firefox.exe", "-private-window"),
+];
+/// Placeholder in search templates that is replaced by the encoded query.
+const SEARCH_QUERY_PLACEHOLDER: &str = "{query}";
+const MAX_BENCHMARK_ITERATIONS: u32 = 1000;
+/// Id of the placeholder result shown while the first index is still being built.
+const INDEXING_RESULT_ID: &str = "indexing";
+/// Id of the info result reporting matches dropped by the result limit; never executed.
+const TRUNCATION_HINT_ID: &str = "truncated";
+/// Settings page listing installed apps, opened when an app has no known uninstaller.
+const APPS_FEATURES_URI: &str = "ms-settings:appsfeatures";
+/// How many index entries are matched between checks for a superseded query.
+const CANCEL_CHECK_INTERVAL: usize = 256;
+/// Queries longer than this skip fuzzy matching entirely and only offer URL/web search.
+const MAX_MATCHABLE_QUERY_CHARS: usize = 2048;
+/// How long to wait for a launched app's window before giving up on moving it.
+const LAUNCH_WINDOW_TIMEOUT: Duration = Duration::from_secs(10);
 pub const HIDE_WINDOW_EVENT: &str = "hide_window";
 pub const OPEN_SETTINGS_EVENT: &str = "open_settings";
 pub const SETTINGS_UPDATED_EVENT: &str = "settings_updated";
 pub const FOCUS_INPUT_EVENT: &str = "focus_input";
+/// Emitted once, when the initial index load completes. Later reindexes do not emit it.
+pub const INDEX_READY_EVENT: &str = "index_ready";
 
 #[derive(Debug, Default, Deserialize)]
 pub struct SettingsUpdatePayload {
@@ -61,6 +112,56 @@ pub struct SettingsUpdatePayload {
     pub force_english_input: Option<bool>,
     pub debug_mode: Option<bool>,
     pub system_tool_exclusions: Option<Vec<String>>,
+    pub scoring_weights: Option<ScoringWeights>,
+    pub confirm_admin_launch: Option<bool>,
+    pub skip_dead_shortcuts: Option<bool>,
+    pub custom_launchers: Option<Vec<CustomLauncher>>,
+    pub max_match_query_chars: Option<usize>,
+    pub bookmark_browser: Option<String>,
+    pub open_urls_in_background: Option<bool>,
+    pub max_indexed_apps: Option<usize>,
+    pub max_indexed_bookmarks: Option<usize>,
+    pub max_inline_icons: Option<usize>,
+    pub hidden_ids: Option<Vec<String>>,
+    pub smart_case: Option<bool>,
+    pub enable_run_mode: Option<bool>,
+    pub run_shell: Option<RunShell>,
+    pub launch_order: Option<LaunchOrder>,
+    pub launch_focus_delay_ms: Option<u64>,
+    pub strip_tracking_params: Option<bool>,
+    pub tracking_param_denylist: Option<Vec<String>>,
+    pub show_match_source: Option<bool>,
+    pub folder_separator: Option<String>,
+    pub extra_app_folders: Option<Vec<String>>,
+    pub index_script_extensions: Option<Vec<String>>,
+    pub min_match_score: Option<i64>,
+    pub terminal: Option<Terminal>,
+    pub enable_opera_bookmarks: Option<bool>,
+    pub enable_opera_gx_bookmarks: Option<bool>,
+    pub use_elevation_broker: Option<bool>,
+    /// An empty list clears the allowlist so every profile is read again.
+    pub chrome_profile_allowlist: Option<Vec<String>>,
+    pub app_dir_allowlist: Option<Vec<String>>,
+    pub max_icon_bytes: Option<usize>,
+    pub offline_mode: Option<bool>,
+    pub custom_bookmark_files: Option<Vec<CustomBookmarkFile>>,
+    pub mode_cycle_hotkey: Option<String>,
+    pub launch_on_active_monitor: Option<bool>,
+    pub score_overrides: Option<HashMap<String, i64>>,
+    pub extra_app_dir_excludes: Option<Vec<String>>,
+    pub quick_open_hotkey: Option<String>,
+    pub capture_crashes: Option<bool>,
+    pub enable_cjk_transliteration: Option<bool>,
+    pub hide_after_launch: Option<bool>,
+    pub start_menu_max_depth: Option<usize>,
+    pub show_last_used: Option<bool>,
+    pub index_apps_folder: Option<bool>,
+    pub confirm_url_open: Option<bool>,
+    pub locale: Option<String>,
+    pub profiles: Option<HashMap<String, ProfileOverrides>>,
+    pub show_truncation_hint: Option<bool>,
+    pub enable_paste_action: Option<bool>,
+    pub search_url_template: Option<String>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -69,9 +170,65 @@ enum QueryMode {
     Bookmark,
     Application,
     Search,
+    RecentlyInstalled,
+    Run,
+    /// Searches every UWP app, including system components normally left out of the index.
+    /// Some of these entries have no launchable UI and may fail to start.
+    SystemApps,
 }
 
 impl QueryMode {
+    /// Modes offered to the frontend, in the order the mode-cycling key walks through them.
+    const CYCLE_ORDER: [Self; 6] = [
+        Self::All,
+        Self::Application,
+        Self::Bookmark,
+        Self::Search,
+        Self::Run,
+        Self::SystemApps,
+    ];
+
+    /// Identifier the frontend sends back as `mode`.
+    fn id(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Bookmark => "bookmark",
+            Self::Application => "app",
+            Self::Search => "search",
+            Self::RecentlyInstalled => "recent",
+            Self::Run => "run",
+            Self::SystemApps => "sys",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::All => "智能模式",
+            Self::Bookmark => "书签模式",
+            Self::Application => "应用模式",
+            Self::Search => "搜索模式",
+            Self::RecentlyInstalled => "最近安装",
+            Self::Run => "运行模式",
+            Self::SystemApps => "系统应用模式",
+        }
+    }
+
+    /// Input prefix and enabled state of the mode under `config`.
+    fn prefix_and_enabled(self, config: &AppConfig) -> (Option<String>, bool) {
+        match self {
+            Self::All => (None, true),
+            Self::Bookmark => (
+                Some(config.prefix_bookmark.clone()),
+                config.enable_bookmark_results,
+            ),
+            Self::Application => (Some(config.prefix_app.clone()), config.enable_app_results),
+            Self::Search => (Some(config.prefix_search.clone()), !config.offline_mode),
+            Self::RecentlyInstalled => (None, config.enable_app_results),
+            Self::Run => (Some(">".to_string()), config.enable_run_mode),
+            Self::SystemApps => (Some("sys".to_string()), config.enable_app_results),
+        }
+    }
+
     fn from_option(mode: Option<String>) -> Self {
         match mode
             .as_deref()
@@ -81,6 +238,9 @@ impl QueryMode {
             Some("bookmark") | Some("bookmarks") | Some("b") => Self::Bookmark,
             Some("app") | Some("apps") | Some("application") | Some("r") => Self::Application,
             Some("search") | Some("s") => Self::Search,
+            Some("recent") | Some("new") => Self::RecentlyInstalled,
+            Some("run") | Some(">") => Self::Run,
+            Some("sys") | Some("system") => Self::SystemApps,
             _ => Self::All,
         }
     }
@@ -98,6 +258,24 @@ impl QueryMode {
     }
 }
 
+/// Lists the query modes in cycling order with their configured prefixes.
+#[tauri::command]
+pub fn get_modes(state: State<'_, AppState>) -> Vec<ModeInfo> {
+    let config = state.config_snapshot();
+    QueryMode::CYCLE_ORDER
+        .iter()
+        .map(|mode| {
+            let (prefix, enabled) = mode.prefix_and_enabled(&config);
+            ModeInfo {
+                id: mode.id().to_string(),
+                name: mode.label().to_string(),
+                prefix,
+                enabled,
+            }
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub async fn submit_query(
     query: String,
@@ -105,162 +283,695 @@ pub async fn submit_query(
     state: State<'_, AppState>,
 ) -> Result<Vec<SearchResult>, String> {
     let trimmed = query.trim();
-    if trimmed.is_empty() {
+    let query_mode = QueryMode::from_option(mode);
+    // 新查询使上一次结果的二次筛选缓存失效
+    if let Ok(mut guard) = state.last_results.lock() {
+        guard.clear();
+    }
+    // 最近安装模式允许空查询，直接列出最新安装的应用
+    if trimmed.is_empty() && query_mode != QueryMode::RecentlyInstalled {
         return Ok(Vec::new());
     }
+    if let Ok(mut guard) = state.last_query.lock() {
+        *guard = Some((trimmed.to_string(), query_mode.id().to_string()));
+    }
 
-    let query_mode = QueryMode::from_option(mode);
-    let config_snapshot = state
-        .config
-        .lock()
-        .map(|cfg| cfg.clone())
-        .unwrap_or_default();
-    let include_apps = config_snapshot.enable_app_results;
-    let include_bookmarks = config_snapshot.enable_bookmark_results;
-    let mut result_limit = config_snapshot
-        .max_results
-        .clamp(MIN_RESULT_LIMIT, MAX_RESULT_LIMIT) as usize;
-    if result_limit == 0 {
-        result_limit = MIN_RESULT_LIMIT as usize;
+    // 新查询到来时取消仍在执行的旧查询
+    let cancelled = state.begin_query();
+    let context = QueryContext::new(&state, trimmed, query_mode, Arc::clone(&cancelled));
+    let (results, pending_actions) = tauri::async_runtime::spawn_blocking(move || {
+        run_query(&context, &mut QueryDiagnostics::default())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // 已被新查询取代时不覆盖待执行队列，前端也会丢弃该结果
+    if cancelled.load(Ordering::Relaxed) {
+        return Ok(Vec::new());
     }
 
-    let app_index = state.app_index.clone();
-    let bookmark_index = state.bookmark_index.clone();
-    let query_str = trimmed.to_string();
+    if let Ok(mut guard) = state.pending_actions.lock() {
+        guard.clear();
+        guard.extend(pending_actions);
+    } else {
+        log::warn!("无法记录搜索结果缓存，可能导致执行失败");
+    }
+    if let Ok(mut guard) = state.result_order.lock() {
+        *guard = results.iter().map(|result| result.id.clone()).collect();
+    }
+    if let Ok(mut guard) = state.last_results.lock() {
+        guard.clone_from(&results);
+    }
 
-    let (results, pending_actions) = tauri::async_runtime::spawn_blocking(move || {
-        let mut results = Vec::new();
-        let mut counter = 0usize;
-        let mut pending_actions: HashMap<String, PendingAction> = HashMap::new();
+    Ok(results)
+}
+
+/// Re-runs the remembered query and executes its top result without showing the window. Does
+/// nothing when no query is remembered, nothing matches, or the result needs confirmation.
+/// Runs the query on its own, so the results shown in an open launcher window stay valid.
+pub(crate) async fn quick_open_last_query(app_handle: AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let Some((query, mode)) = state.last_query.lock().ok().and_then(|guard| guard.clone()) else {
+        return;
+    };
+    // 不经过 submit_query，避免取消窗口中进行中的查询或覆盖其待执行队列
+    let context = QueryContext::new(
+        &state,
+        &query,
+        QueryMode::from_option(Some(mode)),
+        Arc::new(AtomicBool::new(false)),
+    );
+    let (results, mut pending_actions) = match tauri::async_runtime::spawn_blocking(move || {
+        run_query(&context, &mut QueryDiagnostics::default())
+    })
+    .await
+    {
+        Ok(output) => output,
+        Err(err) => {
+            log::warn!("快速打开查询失败: {err}");
+            return;
+        }
+    };
+    let Some(action) = results
+        .iter()
+        .find(|result| result.id != INDEXING_RESULT_ID && result.id != TRUNCATION_HINT_ID)
+        .and_then(|result| pending_actions.remove(&result.id))
+    else {
+        return;
+    };
+    let options = ExecuteOptions::default();
+    match run_action(action, false, options, app_handle.clone(), state).await {
+        Ok(ExecuteOutcome::NeedsConfirmation) => log::info!("快速打开的结果需要确认，已跳过"),
+        Ok(_) => {}
+        Err(err) => log::warn!("快速打开失败: {err}"),
+    }
+}
+
+/// Everything a query needs from the config and the shared state, captured up front so the
+/// matching can run on a blocking thread. Shared by `submit_query`, `diagnose_query` and
+/// quick open.
+struct QueryContext {
+    query_mode: QueryMode,
+    query_str: String,
+    match_query: String,
+    /// Application type requested with a `type:` token; other types are left out.
+    app_type_filter: Option<AppType>,
+    skip_matching: bool,
+    include_apps: bool,
+    include_bookmarks: bool,
+    custom_launchers: Vec<CustomLauncher>,
+    enable_run_mode: bool,
+    show_match_source: bool,
+    show_last_used: bool,
+    show_truncation_hint: bool,
+    folder_separator: String,
+    locale: String,
+    debug_mode: bool,
+    run_shell: RunShell,
+    scoring_weights: ScoringWeights,
+    min_match_score: i64,
+    recent_launches: Vec<String>,
+    score_overrides: HashMap<String, i64>,
+    offline_mode: bool,
+    search_url_template: String,
+    result_limit: usize,
+    indexing: bool,
+    cancel_flag: Arc<AtomicBool>,
+    matcher: Arc<SkimMatcherV2>,
+    app_index: Arc<Mutex<Vec<ApplicationInfo>>>,
+    bookmark_index: Arc<Mutex<Vec<BookmarkEntry>>>,
+    system_uwp_index: Arc<Mutex<Vec<ApplicationInfo>>>,
+    usage_stats: Arc<Mutex<UsageStats>>,
+    custom_launcher_icons: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl QueryContext {
+    fn new(
+        state: &AppState,
+        trimmed: &str,
+        query_mode: QueryMode,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Self {
+        let config_snapshot = state.effective_config();
+        let mut result_limit = config_snapshot
+            .max_results
+            .clamp(MIN_RESULT_LIMIT, MAX_RESULT_LIMIT) as usize;
+        if result_limit == 0 {
+            result_limit = MIN_RESULT_LIMIT as usize;
+        }
+        let (filtered_query, app_type_filter) = extract_app_type_filter(trimmed);
+        let app_index = state.app_index.clone();
+        // 首次索引尚未完成时提示用户稍候，避免只看到网络搜索结果
+        let indexing = !state.index_ready.load(Ordering::SeqCst);
+
+        Self {
+            query_mode,
+            query_str: trimmed.to_string(),
+            // 超长查询（例如误粘贴的大段文本）只截取前若干字符参与模糊匹配，
+            // 超过硬上限则跳过匹配，仅保留网址/网络搜索结果
+            match_query: truncate_chars(
+                &filtered_query,
+                config_snapshot.max_match_query_chars.max(1),
+            )
+            .to_string(),
+            app_type_filter,
+            skip_matching: trimmed.chars().count() > MAX_MATCHABLE_QUERY_CHARS,
+            include_apps: config_snapshot.enable_app_results,
+            include_bookmarks: config_snapshot.enable_bookmark_results,
+            custom_launchers: config_snapshot.custom_launchers.clone(),
+            enable_run_mode: config_snapshot.enable_run_mode,
+            show_match_source: config_snapshot.show_match_source,
+            show_last_used: config_snapshot.show_last_used,
+            show_truncation_hint: config_snapshot.show_truncation_hint,
+            folder_separator: config_snapshot.folder_separator.clone(),
+            locale: config_snapshot.locale.clone(),
+            debug_mode: config_snapshot.debug_mode,
+            run_shell: config_snapshot.run_shell,
+            scoring_weights: config_snapshot.scoring_weights.clone(),
+            min_match_score: config_snapshot.min_match_score,
+            recent_launches: state.recent_launches_snapshot(),
+            score_overrides: config_snapshot.score_overrides.clone(),
+            offline_mode: config_snapshot.offline_mode,
+            search_url_template: config_snapshot.search_url_template.clone(),
+            result_limit,
+            indexing,
+            cancel_flag,
+            matcher: state.matcher_snapshot(),
+            app_index,
+            bookmark_index: state.bookmark_index.clone(),
+            system_uwp_index: state.system_uwp_index.clone(),
+            usage_stats: Arc::clone(&state.usage_stats),
+            custom_launcher_icons: Arc::clone(&state.custom_launcher_icons),
+        }
+    }
+}
+
+/// Builds the results and their pending actions for a query. Counts per source are written to
+/// `diagnostics`; timing is left to the caller.
+fn run_query(
+    context: &QueryContext,
+    diagnostics: &mut QueryDiagnostics,
+) -> (Vec<SearchResult>, HashMap<String, PendingAction>) {
+    let QueryContext {
+        query_mode,
+        ref query_str,
+        ref match_query,
+        ref app_type_filter,
+        skip_matching,
+        include_apps,
+        include_bookmarks,
+        ref custom_launchers,
+        enable_run_mode,
+        show_match_source,
+        show_last_used,
+        show_truncation_hint,
+        ref folder_separator,
+        ref locale,
+        debug_mode,
+        run_shell,
+        ref scoring_weights,
+        min_match_score,
+        ref recent_launches,
+        ref score_overrides,
+        offline_mode,
+        ref search_url_template,
+        result_limit,
+        indexing,
+        ref cancel_flag,
+        ref matcher,
+        ref app_index,
+        ref bookmark_index,
+        ref system_uwp_index,
+        ref usage_stats,
+        ref custom_launcher_icons,
+    } = *context;
+    diagnostics.mode = query_mode.id().to_string();
+    diagnostics.matching_skipped = skip_matching;
+    let mut results = Vec::new();
+    let mut pending_actions: HashMap<String, PendingAction> = HashMap::new();
+
+    if query_mode == QueryMode::RecentlyInstalled {
+        let mut apps = if include_apps && !skip_matching {
+            app_index.lock().expect("failed to lock app index").clone()
+        } else {
+            Vec::new()
+        };
+        if let Some(app_type) = app_type_filter {
+            apps.retain(|app| app.app_type == *app_type);
+        }
+        diagnostics.apps.considered = apps.len();
+        for app in recently_installed_applications(
+            matcher,
+            &apps,
+            match_query,
+            scoring_weights,
+            min_match_score,
+            result_limit,
+        ) {
+            diagnostics.apps.record(0);
+            let result_id = format!("app-{}", app.id);
+            results.push(application_result(&result_id, app, 0));
+            insert_uninstall_action(&mut pending_actions, &result_id, app);
+            pending_actions.insert(result_id, Box::new(LaunchApp(app.clone())));
+        }
+        fill_launch_counts(&mut results, &pending_actions, usage_stats, show_last_used);
+        return (results, pending_actions);
+    }
+
+    // 系统应用模式绕过隐藏与排除规则，仅在完整的 UWP 列表中匹配
+    if query_mode == QueryMode::SystemApps {
+        if skip_matching {
+            return (results, pending_actions);
+        }
+        let apps = system_uwp_index
+            .lock()
+            .expect("failed to lock system app index")
+            .clone();
+        diagnostics.apps.considered = apps.len();
+        for app in apps.iter() {
+            if let Some(outcome) = match_application(
+                matcher,
+                app,
+                match_query,
+                scoring_weights,
+                min_match_score,
+                recent_launches,
+                score_overrides,
+            ) {
+                diagnostics.apps.record(outcome.score);
+                let result_id = format!("app-{}", app.id);
+                pending_actions.insert(result_id.clone(), Box::new(LaunchApp(app.clone())));
+                let mut result = application_result(&result_id, app, outcome.score);
+                if debug_mode {
+                    result.score_breakdown = Some(outcome.breakdown);
+                }
+                results.push(result);
+            }
+        }
+        sort_results(&mut results, locale);
+        let matched = results.len();
+        results.truncate(result_limit);
+        fill_launch_counts(&mut results, &pending_actions, usage_stats, show_last_used);
+        if show_truncation_hint && matched > results.len() {
+            results.push(truncation_hint(matched - results.len()));
+        }
+        return (results, pending_actions);
+    }
+
+    // 运行模式只生成一条命令结果，副标题展示将要执行的完整命令
+    if query_mode == QueryMode::Run {
+        if enable_run_mode {
+            let (program, arguments) = run_shell.command_line(query_str);
+            let result_id = content_result_id("run", query_str);
+            results.push(SearchResult {
+                id: result_id.clone(),
+                title: format!("运行命令: {query_str}"),
+                subtitle: format!("{program} {arguments}"),
+                icon: String::new(),
+                score: 0,
+                action_id: "run".to_string(),
+                group: result_group("run").to_string(),
+                score_breakdown: None,
+                launch_count: None,
+            });
+            pending_actions.insert(
+                result_id,
+                Box::new(RunCommand {
+                    program: program.to_string(),
+                    arguments,
+                }),
+            );
+        }
+        return (results, pending_actions);
+    }
 
-        if is_url_like(&query_str) {
-            let result_id = format!("url-{counter}");
-            pending_actions.insert(result_id.clone(), PendingAction::Url(query_str.clone()));
+    diagnostics.url_detected = is_url_like(query_str);
+    // 离线模式下网址类查询只在本地路径存在时提供打开文件的结果
+    if offline_mode && diagnostics.url_detected {
+        if let Some(path) = existing_local_path(query_str) {
+            let result_id = content_result_id("path", &path);
+            pending_actions.insert(result_id.clone(), Box::new(OpenPath(path.clone())));
             results.push(SearchResult {
                 id: result_id,
-                title: format!("打开网址: {query_str}"),
-                subtitle: query_str.clone(),
+                title: format!("打开文件: {query_str}"),
+                subtitle: path,
                 icon: String::new(),
                 score: 200,
-                action_id: "url".to_string(),
+                action_id: "path".to_string(),
+                group: result_group("path").to_string(),
+                score_breakdown: None,
+                launch_count: None,
             });
-            counter += 1;
         }
+    } else if diagnostics.url_detected {
+        let result_id = content_result_id("url", query_str);
+        pending_actions.insert(result_id.clone(), Box::new(OpenUrl(query_str.clone())));
+        // 次要操作：复制为 Markdown 链接，裸网址以主机名作为链接文字
+        pending_actions.insert(
+            format!("{result_id}:markdown"),
+            Box::new(CopyText(markdown_link(None, query_str))),
+        );
+        results.push(SearchResult {
+            id: result_id,
+            title: format!("打开网址: {query_str}"),
+            subtitle: query_str.clone(),
+            icon: String::new(),
+            score: 200,
+            action_id: "url".to_string(),
+            group: result_group("url").to_string(),
+            score_breakdown: None,
+            launch_count: None,
+        });
+    }
 
-        let matcher = SkimMatcherV2::default();
-        let apps = if query_mode.allows_applications() && include_apps {
-            Some(app_index.lock().expect("failed to lock app index").clone())
-        } else {
-            None
-        };
-        let bookmarks = if query_mode.allows_bookmarks() && include_bookmarks {
-            Some(
-                bookmark_index
-                    .lock()
-                    .expect("failed to lock bookmark index")
-                    .clone(),
-            )
-        } else {
-            None
-        };
+    let apps = if query_mode.allows_applications() && include_apps && !skip_matching {
+        Some(app_index.lock().expect("failed to lock app index").clone())
+    } else {
+        None
+    };
+    let bookmarks = if query_mode.allows_bookmarks() && include_bookmarks && !skip_matching {
+        Some(
+            bookmark_index
+                .lock()
+                .expect("failed to lock bookmark index")
+                .clone(),
+        )
+    } else {
+        None
+    };
 
-        if let Some(apps) = apps.as_ref() {
-            for app in apps.iter() {
-                if let Some(score) = match_application(&matcher, app, &query_str) {
-                    counter += 1;
-                    let result_id = format!("app-{}", app.id);
-                    pending_actions
-                        .insert(result_id.clone(), PendingAction::Application(app.clone()));
-                    let subtitle = app
-                        .description
-                        .clone()
-                        .filter(|d| !d.is_empty())
-                        .or_else(|| app.source_path.clone())
-                        .unwrap_or_else(|| app.path.clone());
-                    results.push(SearchResult {
-                        id: result_id,
-                        title: app.name.clone(),
-                        subtitle,
-                        icon: app.icon_b64.clone(),
-                        score,
-                        action_id: match app.app_type {
-                            AppType::Win32 => "app".to_string(),
-                            AppType::Uwp => "uwp".to_string(),
-                        },
-                    });
+    if let Some(apps) = apps.as_ref() {
+        diagnostics.apps.considered = apps.len();
+        for (index, app) in apps.iter().enumerate() {
+            if index % CANCEL_CHECK_INTERVAL == 0 && cancel_flag.load(Ordering::Relaxed) {
+                return (Vec::new(), HashMap::new());
+            }
+            if app_type_filter
+                .as_ref()
+                .is_some_and(|app_type| app.app_type != *app_type)
+            {
+                continue;
+            }
+            if let Some(outcome) = match_application(
+                matcher,
+                app,
+                match_query,
+                scoring_weights,
+                min_match_score,
+                recent_launches,
+                score_overrides,
+            ) {
+                diagnostics.apps.record(outcome.score);
+                let result_id = format!("app-{}", app.id);
+                pending_actions.insert(result_id.clone(), Box::new(LaunchApp(app.clone())));
+                // 次要操作：在文件夹中打开终端
+                if app.app_type == AppType::Folder {
+                    pending_actions.insert(
+                        format!("{result_id}:terminal"),
+                        Box::new(OpenTerminal(app.path.clone())),
+                    );
+                }
+                insert_uninstall_action(&mut pending_actions, &result_id, app);
+                let mut result = application_result(&result_id, app, outcome.score);
+                if show_match_source {
+                    result.subtitle = with_match_hint(result.subtitle, outcome.matched);
+                }
+                if debug_mode {
+                    result.score_breakdown = Some(outcome.breakdown);
                 }
+                results.push(result);
             }
         }
+    }
 
-        if let Some(bookmarks) = bookmarks.as_ref() {
-            for bookmark in bookmarks.iter() {
-                if let Some(score) = match_bookmark(&matcher, bookmark, &query_str) {
-                    counter += 1;
-                    let subtitle = match &bookmark.folder_path {
-                        Some(path) => format!("收藏夹 · {path} · {}", bookmark.url),
-                        None => format!("收藏夹 · {}", bookmark.url),
-                    };
-                    let result_id = format!("bookmark-{}", bookmark.id);
-                    pending_actions
-                        .insert(result_id.clone(), PendingAction::Bookmark(bookmark.clone()));
-                    results.push(SearchResult {
-                        id: result_id,
-                        title: bookmark.title.clone(),
-                        subtitle,
-                        icon: String::new(),
-                        score,
-                        action_id: "bookmark".to_string(),
-                    });
-                }
+    // 指定了应用类型时自定义启动项不属于任何类型，一并略过
+    if query_mode.allows_applications() && !skip_matching && app_type_filter.is_none() {
+        diagnostics.custom_launchers.considered = custom_launchers.len();
+        for launcher in custom_launchers.iter() {
+            if let Some(outcome) = match_custom_launcher(
+                matcher,
+                launcher,
+                match_query,
+                scoring_weights,
+                min_match_score,
+            ) {
+                diagnostics.custom_launchers.record(outcome.score);
+                let result_id = format!("custom-{}", launcher.id);
+                pending_actions.insert(result_id.clone(), Box::new(LaunchCustom(launcher.clone())));
+                results.push(SearchResult {
+                    id: result_id,
+                    title: launcher.name.clone(),
+                    subtitle: launcher.target.clone(),
+                    icon: custom_launcher_icon(launcher, custom_launcher_icons),
+                    score: outcome.score,
+                    action_id: "custom".to_string(),
+                    group: result_group("custom").to_string(),
+                    score_breakdown: debug_mode.then_some(outcome.breakdown),
+                    launch_count: None,
+                });
             }
         }
+    }
 
-        results.sort_by(|a, b| b.score.cmp(&a.score));
-        if result_limit > 1 && results.len() >= result_limit {
-            results.truncate(result_limit - 1);
-        } else {
-            results.truncate(result_limit);
+    if let Some(bookmarks) = bookmarks.as_ref() {
+        diagnostics.bookmarks.considered = bookmarks.len();
+        for (index, bookmark) in bookmarks.iter().enumerate() {
+            if index % CANCEL_CHECK_INTERVAL == 0 && cancel_flag.load(Ordering::Relaxed) {
+                return (Vec::new(), HashMap::new());
+            }
+            if let Some(outcome) = match_bookmark(
+                matcher,
+                bookmark,
+                match_query,
+                scoring_weights,
+                min_match_score,
+                recent_launches,
+                score_overrides,
+            ) {
+                diagnostics.bookmarks.record(outcome.score);
+                let mut subtitle = if bookmark.folder_segments.is_empty() {
+                    format!("收藏夹 · {}", bookmark.url)
+                } else {
+                    let path = bookmark.folder_segments.join(folder_separator.as_str());
+                    format!("收藏夹 · {path} · {}", bookmark.url)
+                };
+                if show_match_source {
+                    subtitle = with_match_hint(subtitle, outcome.matched);
+                }
+                let result_id = format!("bookmark-{}", bookmark.id);
+                pending_actions.insert(result_id.clone(), Box::new(OpenBookmark(bookmark.clone())));
+                // 次要操作：在书签管理器中定位该书签
+                pending_actions.insert(
+                    format!("{result_id}:edit"),
+                    Box::new(EditBookmark(bookmark.clone())),
+                );
+                pending_actions.insert(
+                    format!("{result_id}:folder"),
+                    Box::new(OpenBookmarkFolder(bookmark.clone())),
+                );
+                pending_actions.insert(
+                    format!("{result_id}:markdown"),
+                    Box::new(CopyText(markdown_link(
+                        Some(&bookmark.title),
+                        &bookmark.url,
+                    ))),
+                );
+                results.push(SearchResult {
+                    id: result_id,
+                    title: bookmark.title.clone(),
+                    subtitle,
+                    icon: String::new(),
+                    score: outcome.score,
+                    action_id: "bookmark".to_string(),
+                    group: result_group("bookmark").to_string(),
+                    score_breakdown: debug_mode.then_some(outcome.breakdown),
+                    launch_count: None,
+                });
+            }
         }
+    }
 
-        if query_mode.allows_web_search() {
-            let search_id = format!("search-{counter}");
-            let search_url = format!(
-                "https://google.com/search?q={}",
-                urlencoding::encode(&query_str)
-            );
-            pending_actions.insert(search_id.clone(), PendingAction::Search(search_url.clone()));
-            results.push(SearchResult {
-                id: search_id,
-                title: format!("在 Google 上搜索: {query_str}"),
-                subtitle: String::from("Google 搜索"),
+    sort_results(&mut results, locale);
+    let matched = results.len();
+    if result_limit > 1 && results.len() >= result_limit {
+        results.truncate(result_limit - 1);
+    } else {
+        results.truncate(result_limit);
+    }
+    fill_launch_counts(&mut results, &pending_actions, usage_stats, show_last_used);
+    disambiguate_titles(&mut results, &pending_actions);
+    // 放在真实结果之后、网络搜索之前
+    if show_truncation_hint && matched > results.len() {
+        results.push(truncation_hint(matched - results.len()));
+    }
+
+    if indexing {
+        results.insert(
+            0,
+            SearchResult {
+                id: INDEXING_RESULT_ID.to_string(),
+                title: "正在建立索引…".to_string(),
+                subtitle: "应用列表尚未就绪，请稍候再试".to_string(),
                 icon: String::new(),
-                score: i64::MIN,
-                action_id: "search".to_string(),
-            });
-        }
+                score: i64::MAX,
+                action_id: "indexing".to_string(),
+                group: result_group("indexing").to_string(),
+                score_breakdown: None,
+                launch_count: None,
+            },
+        );
+    }
+
+    // 明确的网址已有“打开网址”结果，不再追加重复的网络搜索。
+    // 网络搜索不参与排序与截断，始终追加在末尾，其分数仅作展示
+    if !offline_mode && query_mode.allows_web_search() && !has_url_scheme(query_str) {
+        diagnostics.web_search_offered = true;
+        let search_url = build_search_url(search_url_template, query_str);
+        let search_id = content_result_id("search", &search_url);
+        pending_actions.insert(search_id.clone(), Box::new(WebSearch(search_url.clone())));
+        // 只有默认模板才确定是 Google，自定义搜索引擎使用通用标题
+        let (title, subtitle) = if search_url_template == GOOGLE_SEARCH_TEMPLATE {
+            (format!("在 Google 上搜索: {query_str}"), "Google 搜索")
+        } else {
+            (format!("在网络上搜索: {query_str}"), "网页搜索")
+        };
+        results.push(SearchResult {
+            id: search_id,
+            title,
+            subtitle: subtitle.to_string(),
+            icon: String::new(),
+            score: i64::MIN,
+            action_id: "search".to_string(),
+            group: result_group("search").to_string(),
+            score_breakdown: None,
+            launch_count: None,
+        });
+    }
+
+    (results, pending_actions)
+}
+
+/// Builds the non-actionable result telling the user that `hidden` matches were cut off.
+fn truncation_hint(hidden: usize) -> SearchResult {
+    SearchResult {
+        id: TRUNCATION_HINT_ID.to_string(),
+        title: format!("还有 {hidden} 个结果，请细化搜索"),
+        subtitle: "结果数量已达上限".to_string(),
+        icon: String::new(),
+        score: 0,
+        action_id: "truncated".to_string(),
+        group: result_group("truncated").to_string(),
+        score_breakdown: None,
+        launch_count: None,
+    }
+}
+
+/// Runs `query` through the same code path as `submit_query` and reports how many entries each
+/// source considered and matched, plus the elapsed time. Pending actions and the cached results
+/// of the last query are left untouched, and an in-flight query is not cancelled.
+#[tauri::command]
+pub async fn diagnose_query(
+    query: String,
+    mode: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<QueryDiagnostics, String> {
+    let trimmed = query.trim();
+    let query_mode = QueryMode::from_option(mode);
+    if trimmed.is_empty() && query_mode != QueryMode::RecentlyInstalled {
+        return Err("查询内容不能为空".into());
+    }
 
-        (results, pending_actions)
+    let context = QueryContext::new(
+        &state,
+        trimmed,
+        query_mode,
+        Arc::new(AtomicBool::new(false)),
+    );
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut diagnostics = QueryDiagnostics::default();
+        let started = Instant::now();
+        let (results, _) = run_query(&context, &mut diagnostics);
+        diagnostics.elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        diagnostics.result_count = results.len();
+        diagnostics
     })
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|err| err.to_string())
+}
 
-    if let Ok(mut guard) = state.pending_actions.lock() {
-        guard.clear();
-        guard.extend(pending_actions);
-    } else {
-        log::warn!("无法记录搜索结果缓存，可能导致执行失败");
+/// Looks up the full details of a result from the last query without re-running it. Returns
+/// `None` once the id has expired.
+#[tauri::command]
+pub fn get_result_details(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<ResultDetails>, String> {
+    let action = state
+        .pending_actions
+        .lock()
+        .map_err(|_| "无法访问待执行队列".to_string())?
+        .get(&id)
+        .cloned();
+    let folder_separator = state.config_snapshot().folder_separator.clone();
+    Ok(action.and_then(|action| action.details(&folder_separator)))
+}
+
+/// Narrows the last `submit_query` results to titles matching `sub_query`, keeping their
+/// original order. The pending actions of the surviving results stay valid.
+#[tauri::command]
+pub fn filter_results(
+    sub_query: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchResult>, String> {
+    let results = state
+        .last_results
+        .lock()
+        .map_err(|_| "无法读取上一次搜索结果".to_string())?
+        .clone();
+    let sub_query = sub_query.trim();
+    if sub_query.is_empty() {
+        return Ok(results);
     }
 
-    Ok(results)
+    let matcher = state.matcher_snapshot();
+    let lowered = sub_query.to_lowercase();
+    Ok(results
+        .into_iter()
+        .filter(|result| {
+            result.title.to_lowercase().contains(&lowered)
+                || matcher.fuzzy_match(&result.title, sub_query).is_some()
+        })
+        .collect())
+}
+
+/// Optional switches for [`execute_action`], passed as a single `options` object in the invoke
+/// payload. Omitted fields default to `false`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExecuteOptions {
+    /// The user already confirmed an action that returned `NeedsConfirmation`.
+    pub confirmed: bool,
+    /// Open URLs in a private browser window.
+    pub private: bool,
+    /// Open the site's home page instead of the URL itself.
+    pub site_root: bool,
+    /// Paste copied text into the window that was focused before the launcher opened.
+    pub paste: bool,
 }
 
 #[tauri::command]
 pub async fn execute_action(
     id: String,
     run_as_admin: bool,
+    options: Option<ExecuteOptions>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<ExecuteOutcome, String> {
+    // 索引提示项与截断提示项不可执行
+    if id == INDEXING_RESULT_ID || id == TRUNCATION_HINT_ID {
+        return Ok(ExecuteOutcome::Executed);
+    }
+
     let action = {
         let guard = state
             .pending_actions
@@ -271,176 +982,971 @@ pub async fn execute_action(
             .cloned()
             .ok_or_else(|| "结果已失效，请重新搜索".to_string())?
     };
+    run_action(
+        action,
+        run_as_admin,
+        options.unwrap_or_default(),
+        app_handle,
+        state,
+    )
+    .await
+}
 
-    match action {
-        PendingAction::Application(app) => match app.app_type {
-            AppType::Win32 => launch_win32_app(&app, run_as_admin)?,
-            AppType::Uwp => launch_uwp_app(&app.path)?,
-        },
-        PendingAction::Bookmark(entry) => open_url(&app_handle, &entry.url)?,
-        PendingAction::Url(url) | PendingAction::Search(url) => {
-            open_url(&app_handle, &url)?;
-        }
+/// Executes `action` after the confirmation and mode checks shared by `execute_action` and
+/// quick open, then hides or keeps the launcher as configured.
+async fn run_action(
+    action: PendingAction,
+    run_as_admin: bool,
+    options: ExecuteOptions,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecuteOutcome, String> {
+    let ExecuteOptions {
+        confirmed,
+        private,
+        site_root,
+        paste,
+    } = options;
+
+    // 需要确认时保留待执行项，以便前端确认后再次调用
+    let config = state.config_snapshot();
+    if action.requires_confirmation(&config, run_as_admin)? && !confirmed {
+        return Ok(ExecuteOutcome::NeedsConfirmation);
     }
 
-    // 恢复之前保存的输入法
-    if let Some(state) = app_handle.try_state::<AppState>() {
-        if let Ok(mut guard) = state.saved_ime.lock() {
-            if let Some(layout_id) = *guard {
-                crate::windows_utils::restore_input_method(layout_id);
-                *guard = None;
-            }
+    // 后台打开网址时保留启动器窗口，方便连续打开多个页面
+    let background = config.open_urls_in_background && action.opens_web_page();
+
+    // 部分系统上先启动再隐藏会导致新窗口被压在启动器之后，可改为先隐藏并稍作等待
+    // 需在隐藏启动器之前记下它所在的显示器
+    let target_work_area = config
+        .launch_on_active_monitor
+        .then(|| launcher_work_area(&app_handle))
+        .flatten();
+    // 粘贴到原窗口需要先隐藏启动器再还原焦点；原窗口已关闭时退回为仅复制
+    let paste_target = if paste && action.copies_text() {
+        if !config.enable_paste_action {
+            return Err("粘贴操作未启用".into());
+        }
+        let target = state
+            .previous_foreground
+            .lock()
+            .ok()
+            .and_then(|guard| *guard);
+        let alive = target.filter(|hwnd| windows_utils::is_window_alive(*hwnd));
+        if alive.is_none() {
+            log::warn!("原窗口已不存在，仅复制到剪贴板");
+        }
+        alive
+    } else {
+        None
+    };
+    let copy_only = action.copies_text() && paste_target.is_none();
+    let hide_after =
+        !background && !copy_only && (config.hide_after_launch || paste_target.is_some());
+    let hide_first = hide_after
+        && (config.launch_order == LaunchOrder::HideThenLaunch || paste_target.is_some());
+    if hide_first {
+        crate::restore_saved_input_method(&app_handle);
+        crate::hide_main_window(&app_handle);
+        let delay = Duration::from_millis(config.launch_focus_delay_ms);
+        if !delay.is_zero() {
+            tauri::async_runtime::spawn_blocking(move || std::thread::sleep(delay))
+                .await
+                .map_err(|err| err.to_string())?;
         }
     }
 
-    if let Some(window) = app_handle.get_webview_window("main") {
-        let _ = window.hide();
+    action.execute(&ActionContext {
+        app_handle: &app_handle,
+        state: &state,
+        config: &config,
+        run_as_admin,
+        private,
+        site_root,
+        background,
+        target_work_area,
+        paste_target,
+    })?;
+    if let Some(key) = action.usage_key() {
+        record_usage(&app_handle, &state, &key);
     }
 
-    let _ = app_handle.emit(HIDE_WINDOW_EVENT, ());
+    // 复制操作不隐藏也不清空窗口，便于连续复制多条结果
+    if copy_only {
+        return Ok(ExecuteOutcome::ExecutedKeepOpen);
+    }
 
-    Ok(())
+    if background {
+        return Ok(ExecuteOutcome::ExecutedInBackground);
+    }
+
+    if !hide_after {
+        // 保持窗口打开：不恢复输入法，仅清空输入框并重新聚焦
+        let _ = app_handle.emit(FOCUS_INPUT_EVENT, true);
+        return Ok(ExecuteOutcome::ExecutedKeepOpen);
+    }
+
+    if !hide_first {
+        // 恢复之前保存的输入法
+        crate::restore_saved_input_method(&app_handle);
+        crate::hide_main_window(&app_handle);
+    }
+
+    Ok(ExecuteOutcome::Executed)
+}
+
+/// Executes the `n`th result (1-based) of the most recent `submit_query`, in the order it was
+/// returned, so keyboard shortcuts like Alt+1..9 need no result ids.
+#[tauri::command]
+pub async fn execute_nth(
+    n: usize,
+    run_as_admin: bool,
+    confirmed: Option<bool>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecuteOutcome, String> {
+    let id = {
+        let guard = state
+            .result_order
+            .lock()
+            .map_err(|_| "无法访问待执行队列".to_string())?;
+        n.checked_sub(1)
+            .and_then(|index| guard.get(index))
+            .cloned()
+            .ok_or_else(|| "结果已失效，请重新搜索".to_string())?
+    };
+    let options = ExecuteOptions {
+        confirmed: confirmed.unwrap_or(false),
+        ..ExecuteOptions::default()
+    };
+    execute_action(id, run_as_admin, Some(options), app_handle, state).await
+}
+
+/// Whether the initial index load has completed; see [`INDEX_READY_EVENT`].
+#[tauri::command]
+pub fn is_index_ready(state: State<'_, AppState>) -> bool {
+    state.index_ready.load(Ordering::SeqCst)
 }
 
 #[tauri::command]
-pub async fn trigger_reindex(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn trigger_reindex(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    force: Option<bool>,
+) -> Result<(), String> {
     let app_index = Arc::clone(&state.app_index);
     let bookmark_index = Arc::clone(&state.bookmark_index);
-    let config_arc = Arc::clone(&state.config);
-
-    tauri::async_runtime::spawn(async move {
-        let exclusion_paths = {
-            let config = config_arc.lock().unwrap();
-            config.system_tool_exclusions.clone()
+    let bookmark_cache = Arc::clone(&state.bookmark_cache);
+    // force 时忽略收藏夹缓存，重新解析所有书签文件
+    let force = force.unwrap_or(false);
+    let system_uwp_index = Arc::clone(&state.system_uwp_index);
+    let usage_stats = Arc::clone(&state.usage_stats);
+    let bookmark_usage_stats = Arc::clone(&state.usage_stats);
+    let config = state.config_snapshot();
+    let options = indexer::IndexOptions::from(config.as_ref());
+    let max_indexed_apps = config.max_indexed_apps;
+    let max_indexed_bookmarks = config.max_indexed_bookmarks;
+    let max_inline_icons = config.max_inline_icons;
+    // 重建索引后 id 可能变化，本次会话的启动加成一并清空
+    if let Ok(mut guard) = state.recent_launches.lock() {
+        guard.clear();
+    }
+    let bookmark_sources = bookmarks::BookmarkSources {
+        chrome_profiles: config.chrome_profile_allowlist.clone(),
+        opera: config.enable_opera_bookmarks,
+        opera_gx: config.enable_opera_gx_bookmarks,
+        custom_files: config.custom_bookmark_files.clone(),
+    };
+    // 新一轮重建使仍在进行的旧一轮失效，旧结果不会覆盖新索引
+    let generation = indexer::IndexGeneration::next(&state.reindex_generation);
+    state.reindex_in_progress.store(true, Ordering::SeqCst);
+
+    let app_generation = generation.clone();
+    let app_task = tauri::async_runtime::spawn(async move {
+        let Some(output) = indexer::build_index(options, app_generation.clone()).await else {
+            log::info!("应用索引刷新已被新的重建取代");
+            return;
         };
-        
-        let apps = indexer::build_index(exclusion_paths).await;
+        let mut apps = output.applications;
+        apply_app_index_limits(&mut apps, &usage_stats, max_indexed_apps, max_inline_icons);
+        // 持有锁时再次检查，确保不会在新一轮写入之后覆盖
         if let Ok(mut guard) = app_index.lock() {
+            if app_generation.is_superseded() {
+                return;
+            }
             *guard = apps;
         }
+        if let Ok(mut guard) = system_uwp_index.lock() {
+            if app_generation.is_superseded() {
+                return;
+            }
+            *guard = output.system_uwp_apps;
+        }
         log::info!("应用索引刷新完成");
     });
 
-    tauri::async_runtime::spawn_blocking(move || {
-        let bookmarks = bookmarks::load_chrome_bookmarks();
+    let bookmark_generation = generation.clone();
+    let bookmark_task = tauri::async_runtime::spawn_blocking(move || {
+        let mut bookmarks = match bookmark_cache.lock() {
+            Ok(mut cache) => bookmarks::load_bookmarks(bookmark_sources, &mut cache, force),
+            Err(_) => bookmarks::load_bookmarks(
+                bookmark_sources,
+                &mut bookmarks::BookmarkCache::default(),
+                force,
+            ),
+        };
+        apply_bookmark_index_limit(&mut bookmarks, &bookmark_usage_stats, max_indexed_bookmarks);
         if let Ok(mut guard) = bookmark_index.lock() {
+            if bookmark_generation.is_superseded() {
+                log::info!("收藏夹索引刷新已被新的重建取代");
+                return;
+            }
             *guard = bookmarks;
         }
-        log::info!("Chrome 收藏夹索引刷新完成");
+        log::info!("收藏夹索引刷新完成");
     });
 
-    Ok(())
-}
+    // 两个索引都刷新完成后，清理已卸载应用/已删除收藏的使用统计
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = app_task.await;
+        let _ = bookmark_task.await;
+        // 较新的重建负责收尾
+        if generation.is_superseded() {
+            return;
+        }
+        state.reindex_in_progress.store(false, Ordering::SeqCst);
+        if !state.index_ready.swap(true, Ordering::SeqCst) {
+            log::info!("首次索引加载完成");
+            let _ = app_handle.emit(INDEX_READY_EVENT, ());
+        }
+        match prune_usage(&app_handle, &state) {
+            Ok(removed) if removed > 0 => log::info!("已清理 {removed} 条失效的使用统计"),
+            Ok(_) => {}
+            Err(err) => log::warn!("清理使用统计失败: {err}"),
+        }
+    });
 
-#[tauri::command]
-pub fn get_settings(state: State<'_, AppState>) -> AppConfig {
-    state
-        .config
-        .lock()
-        .map(|cfg| cfg.clone())
-        .unwrap_or_default()
+    Ok(())
 }
 
-#[tauri::command]
-pub fn update_settings(
-    updates: SettingsUpdatePayload,
-    app_handle: AppHandle,
-    state: State<'_, AppState>,
-) -> Result<AppConfig, String> {
-    let mut guard = state
-        .config
-        .lock()
-        .map_err(|_| "无法获取配置".to_string())?;
-
-    if let Some(hotkey) = updates.global_hotkey {
-        let normalized = hotkey.trim();
-        if normalized.is_empty() {
-            return Err("快捷键不能为空".into());
-        }
-        if normalized != guard.global_hotkey {
-            bind_hotkey(&app_handle, &state, normalized, "main")?;
-            guard.global_hotkey = normalized.to_string();
-        }
+/// Bounds the resident size of the app index on low-end machines.
+///
+/// Entries are ranked by launch count, then name, and only the first `max_apps` are kept.
+/// Beyond `max_inline_icons` the base64 icon is dropped from memory; `refresh_icon` reloads
+/// it from the icon cache when the frontend needs it. This trades a short delay before such
+/// icons appear (and the loss of rarely used apps past the cap) for a bounded footprint.
+fn apply_app_index_limits(
+    apps: &mut Vec<ApplicationInfo>,
+    usage_stats: &Mutex<UsageStats>,
+    max_apps: usize,
+    max_inline_icons: usize,
+) {
+    if apps.len() <= max_inline_icons && apps.len() <= max_apps {
+        return;
     }
 
-    if updates.query_delay_ms.is_some() {
-        guard.query_delay_ms = normalize_query_delay(updates.query_delay_ms, guard.query_delay_ms);
+    if let Ok(stats) = usage_stats.lock() {
+        apps.sort_by_cached_key(|app| {
+            (
+                Reverse(stats.launch_count(&app.id)),
+                app.name.to_lowercase(),
+            )
+        });
     }
 
-    if updates.max_results.is_some() {
-        guard.max_results = normalize_max_results(updates.max_results, guard.max_results);
+    if apps.len() > max_apps {
+        log::info!(
+            "应用数量 {} 超出上限 {max_apps}，多余部分不参与搜索",
+            apps.len()
+        );
+        apps.truncate(max_apps);
     }
-
-    if let Some(value) = updates.enable_app_results {
-        guard.enable_app_results = value;
+    for app in apps.iter_mut().skip(max_inline_icons) {
+        app.icon_b64.clear();
     }
+}
 
-    if let Some(value) = updates.enable_bookmark_results {
-        guard.enable_bookmark_results = value;
+/// Keeps the `max_bookmarks` most launched bookmarks, in file order among equal counts.
+fn apply_bookmark_index_limit(
+    bookmarks: &mut Vec<BookmarkEntry>,
+    usage_stats: &Mutex<UsageStats>,
+    max_bookmarks: usize,
+) {
+    if bookmarks.len() <= max_bookmarks {
+        return;
     }
 
-    if let Some(value) = updates.launch_on_startup {
-        crate::windows_utils::configure_launch_on_startup(value)?;
-        guard.launch_on_startup = value;
+    if let Ok(stats) = usage_stats.lock() {
+        bookmarks.sort_by_cached_key(|bookmark| Reverse(stats.launch_count(&bookmark.id)));
     }
+    log::info!(
+        "收藏夹数量 {} 超出上限 {max_bookmarks}，多余部分不参与搜索",
+        bookmarks.len()
+    );
+    bookmarks.truncate(max_bookmarks);
+}
 
-    if let Some(value) = updates.force_english_input {
-        guard.force_english_input = value;
+/// Runs the matching loop of `submit_query` `iterations` times over the current index and
+/// reports timings. Nothing is recorded in `pending_actions` and nothing is launched.
+#[tauri::command]
+pub async fn benchmark_query(
+    query: String,
+    iterations: u32,
+    state: State<'_, AppState>,
+) -> Result<BenchmarkResult, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err("查询内容不能为空".into());
+    }
+    let iterations = iterations.clamp(1, MAX_BENCHMARK_ITERATIONS);
+
+    let config = state.config_snapshot();
+    let matcher = state.matcher_snapshot();
+    let apps = state
+        .app_index
+        .lock()
+        .map_err(|_| "无法读取应用索引".to_string())?
+        .clone();
+    let bookmarks = state
+        .bookmark_index
+        .lock()
+        .map_err(|_| "无法读取收藏夹索引".to_string())?
+        .clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let match_query = truncate_chars(&query, config.max_match_query_chars.max(1));
+        let weights = &config.scoring_weights;
+        let min_score = config.min_match_score;
+        let overrides = &config.score_overrides;
+        let mut durations = Vec::with_capacity(iterations as usize);
+        let mut result_count = 0;
+
+        for _ in 0..iterations {
+            let started = Instant::now();
+            result_count = apps
+                .iter()
+                .filter(|app| {
+                    match_application(
+                        &matcher,
+                        app,
+                        match_query,
+                        weights,
+                        min_score,
+                        &[],
+                        overrides,
+                    )
+                    .is_some()
+                })
+                .count()
+                + config
+                    .custom_launchers
+                    .iter()
+                    .filter(|launcher| {
+                        match_custom_launcher(&matcher, launcher, match_query, weights, min_score)
+                            .is_some()
+                    })
+                    .count()
+                + bookmarks
+                    .iter()
+                    .filter(|bookmark| {
+                        match_bookmark(
+                            &matcher,
+                            bookmark,
+                            match_query,
+                            weights,
+                            min_score,
+                            &[],
+                            overrides,
+                        )
+                        .is_some()
+                    })
+                    .count();
+            durations.push(started.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        durations.sort_by(f64::total_cmp);
+        BenchmarkResult {
+            iterations,
+            entry_count: apps.len() + config.custom_launchers.len() + bookmarks.len(),
+            result_count,
+            min_ms: durations[0],
+            median_ms: durations[durations.len() / 2],
+            max_ms: durations[durations.len() - 1],
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Lists Chrome profile directory names, for choosing `chrome_profile_allowlist`.
+#[tauri::command]
+pub async fn list_chrome_profiles() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(bookmarks::chrome_profile_names)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn get_usage_stats(state: State<'_, AppState>) -> Result<Vec<UsageRecord>, String> {
+    let guard = state
+        .usage_stats
+        .lock()
+        .map_err(|_| "无法访问使用统计".to_string())?;
+    Ok(guard.records())
+}
+
+#[tauri::command]
+pub fn prune_usage_stats(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    prune_usage(&app_handle, &state)
+}
+
+/// Removes usage entries for ids missing from the current app and bookmark indexes, and URLs
+/// not opened for a long time, and persists the result. Returns the number of removed entries.
+fn prune_usage(app_handle: &AppHandle, state: &AppState) -> Result<usize, String> {
+    let indexed = indexed_ids(state)?;
+    let valid_ids: HashSet<&str> = indexed.iter().map(String::as_str).collect();
+
+    let mut guard = state
+        .usage_stats
+        .lock()
+        .map_err(|_| "无法访问使用统计".to_string())?;
+    let removed = guard.prune(&valid_ids);
+    if removed > 0 {
+        guard.save(app_handle)?;
+    }
+    Ok(removed)
+}
+
+/// Ids of every indexed entry whose usage records `prune_usage` keeps, including UWP apps that
+/// are only reachable through the `sys` mode.
+fn indexed_ids(state: &AppState) -> Result<HashSet<String>, String> {
+    let mut ids = HashSet::new();
+    for index in [&state.app_index, &state.system_uwp_index] {
+        let apps = index.lock().map_err(|_| "无法访问应用索引".to_string())?;
+        ids.extend(apps.iter().map(|app| app.id.clone()));
+    }
+    let bookmarks = state
+        .bookmark_index
+        .lock()
+        .map_err(|_| "无法访问收藏夹索引".to_string())?;
+    ids.extend(bookmarks.iter().map(|bookmark| bookmark.id.clone()));
+    Ok(ids)
+}
+
+fn record_usage(app_handle: &AppHandle, state: &AppState, id: &str) {
+    let Ok(mut guard) = state.usage_stats.lock() else {
+        return;
+    };
+    guard.record(id);
+    if let Err(err) = guard.save(app_handle) {
+        log::warn!("保存使用统计失败: {err}");
+    }
+    drop(guard);
+    state.note_launch(id);
+}
+
+#[tauri::command]
+pub async fn refresh_icon(id: String, state: State<'_, AppState>) -> Result<String, String> {
+    // 同时接受搜索结果 id（app-前缀）与应用 id
+    let app_id = id.strip_prefix("app-").unwrap_or(&id).to_string();
+    let app = {
+        let guard = state
+            .app_index
+            .lock()
+            .map_err(|_| "无法访问应用索引".to_string())?;
+        guard
+            .iter()
+            .find(|app| app.id == app_id)
+            .cloned()
+            .ok_or_else(|| "未找到对应的应用".to_string())?
+    };
+
+    // 因内存上限被丢弃的图标优先从缓存恢复，否则强制重新提取
+    let lazy_load = app.icon_b64.is_empty();
+    let max_icon_bytes = state.config_snapshot().max_icon_bytes;
+    let icon = tauri::async_runtime::spawn_blocking(move || {
+        let icon = if lazy_load {
+            indexer::load_icon(&app)
+        } else {
+            indexer::reload_icon(&app)
+        };
+        icon.map(|icon| indexer::cap_icon_size(&app, icon, max_icon_bytes))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .ok_or_else(|| "无法提取应用图标".to_string())?;
+
+    // 按需加载的图标只返回给前端，不写回索引，以免突破内联图标上限
+    if !lazy_load {
+        if let Ok(mut guard) = state.app_index.lock() {
+            if let Some(entry) = guard.iter_mut().find(|app| app.id == app_id) {
+                entry.icon_b64 = icon.clone();
+            }
+        }
+    }
+
+    Ok(icon)
+}
+
+/// Returns the icon a custom launcher or icon override pointing at `path` would show, so the
+/// settings UI can preview it before saving. Read-only apart from filling the icon cache.
+#[tauri::command]
+pub async fn preview_icon(path: String, icon_index: i32) -> Result<Option<String>, String> {
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Ok(None);
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        windows_utils::preview_icon_from_path(&path, icon_index)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, AppState>) -> AppConfig {
+    AppConfig::clone(&state.config_snapshot())
+}
+
+#[tauri::command]
+pub fn update_settings(
+    updates: SettingsUpdatePayload,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    // 持有锁直到新配置替换完成，避免并发更新互相覆盖
+    let mut guard = state
+        .config
+        .lock()
+        .map_err(|_| "无法获取配置".to_string())?;
+    let mut updated = AppConfig::clone(&guard);
+
+    if let Some(hotkey) = updates.global_hotkey {
+        let normalized = hotkey.trim();
+        if normalized.is_empty() {
+            return Err("快捷键不能为空".into());
+        }
+        if normalized != updated.global_hotkey {
+            hotkey::validate_hotkey(normalized)?;
+            updated.global_hotkey = normalized.to_string();
+        }
+    }
+
+    if updates.query_delay_ms.is_some() {
+        updated.query_delay_ms =
+            normalize_query_delay(updates.query_delay_ms, updated.query_delay_ms);
+    }
+
+    if updates.max_results.is_some() {
+        updated.max_results = normalize_max_results(updates.max_results, updated.max_results);
+    }
+
+    if let Some(value) = updates.enable_app_results {
+        updated.enable_app_results = value;
+    }
+
+    if let Some(value) = updates.enable_bookmark_results {
+        updated.enable_bookmark_results = value;
+    }
+
+    if let Some(value) = updates.launch_on_startup {
+        crate::windows_utils::configure_launch_on_startup(value)?;
+        updated.launch_on_startup = value;
+    }
+
+    if let Some(value) = updates.force_english_input {
+        updated.force_english_input = value;
     }
 
     if let Some(value) = updates.debug_mode {
-        guard.debug_mode = value;
+        updated.debug_mode = value;
     }
 
 
     // 同步模式前缀设置（如果前端传入了非空值）
     if let Some(prefix) = updates.prefix_app {
-        guard.prefix_app = normalize_prefix(&prefix)
+        updated.prefix_app = normalize_prefix(&prefix)
             .ok_or_else(|| "应用模式前缀需为单个字母，可选跟随空格或冒号".to_string())?;
     }
 
     if let Some(prefix) = updates.prefix_bookmark {
-        guard.prefix_bookmark = normalize_prefix(&prefix)
+        updated.prefix_bookmark = normalize_prefix(&prefix)
             .ok_or_else(|| "书签模式前缀需为单个字母，可选跟随空格或冒号".to_string())?;
     }
 
     if let Some(prefix) = updates.prefix_search {
-        guard.prefix_search = normalize_prefix(&prefix)
+        updated.prefix_search = normalize_prefix(&prefix)
             .ok_or_else(|| "搜索模式前缀需为单个字母，可选跟随空格或冒号".to_string())?;
     }
 
     if let Some(paths) = updates.system_tool_exclusions {
-        guard.system_tool_exclusions = paths;
+        updated.system_tool_exclusions = paths;
+    }
+
+    if let Some(weights) = updates.scoring_weights {
+        updated.scoring_weights = weights;
+    }
+
+    if let Some(value) = updates.confirm_admin_launch {
+        updated.confirm_admin_launch = value;
+    }
+
+    if let Some(value) = updates.skip_dead_shortcuts {
+        updated.skip_dead_shortcuts = value;
+    }
+
+    if let Some(mut value) = updates.custom_launchers {
+        for launcher in value.iter_mut() {
+            if launcher.id.trim().is_empty() {
+                launcher.id = custom_launcher_id(launcher);
+            }
+            launcher.validate()?;
+        }
+        updated.custom_launchers = value;
+    }
+
+    if let Some(value) = updates.max_match_query_chars {
+        updated.max_match_query_chars =
+            value.clamp(MIN_MATCH_QUERY_CHARS, MAX_MATCHABLE_QUERY_CHARS);
+    }
+
+    if let Some(value) = updates.bookmark_browser {
+        updated.bookmark_browser = value.trim().to_string();
+    }
+
+    if let Some(value) = updates.open_urls_in_background {
+        updated.open_urls_in_background = value;
+    }
+
+    if let Some(value) = updates.max_indexed_apps {
+        updated.max_indexed_apps = value.max(1);
+    }
+
+    if let Some(value) = updates.max_indexed_bookmarks {
+        updated.max_indexed_bookmarks = value.max(1);
+    }
+
+    if let Some(value) = updates.max_inline_icons {
+        updated.max_inline_icons = value;
+    }
+
+    if let Some(value) = updates.hidden_ids {
+        updated.hidden_ids = value;
+    }
+
+    if let Some(value) = updates.smart_case {
+        updated.smart_case = value;
+    }
+
+    if let Some(value) = updates.enable_run_mode {
+        updated.enable_run_mode = value;
+    }
+
+    if let Some(value) = updates.run_shell {
+        updated.run_shell = value;
+    }
+
+    if let Some(value) = updates.launch_order {
+        updated.launch_order = value;
+    }
+
+    if let Some(value) = updates.launch_focus_delay_ms {
+        updated.launch_focus_delay_ms = value.min(MAX_LAUNCH_FOCUS_DELAY_MS);
+    }
+
+    if let Some(value) = updates.strip_tracking_params {
+        updated.strip_tracking_params = value;
+    }
+
+    if let Some(value) = updates.tracking_param_denylist {
+        updated.tracking_param_denylist = value;
+    }
+
+    if let Some(value) = updates.show_match_source {
+        updated.show_match_source = value;
+    }
+
+    if let Some(value) = updates.folder_separator {
+        // 空分隔符会让路径挤在一起，忽略
+        if !value.is_empty() {
+            updated.folder_separator = value;
+        }
+    }
+
+    if let Some(value) = updates.extra_app_folders {
+        updated.extra_app_folders = value
+            .into_iter()
+            .map(|folder| folder.trim().to_string())
+            .filter(|folder| !folder.is_empty())
+            .collect();
+    }
+
+    if let Some(value) = updates.index_script_extensions {
+        updated.index_script_extensions = value
+            .into_iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|ext| SCRIPT_EXTENSIONS.contains(&ext.as_str()))
+            .collect();
+    }
+
+    if let Some(value) = updates.min_match_score {
+        updated.min_match_score = value;
+    }
+
+    if let Some(value) = updates.terminal {
+        updated.terminal = value;
+    }
+
+    if let Some(value) = updates.enable_opera_bookmarks {
+        updated.enable_opera_bookmarks = value;
+    }
+
+    if let Some(value) = updates.enable_opera_gx_bookmarks {
+        updated.enable_opera_gx_bookmarks = value;
+    }
+
+    if let Some(value) = updates.use_elevation_broker {
+        updated.use_elevation_broker = value;
+    }
+
+    if let Some(value) = updates.chrome_profile_allowlist {
+        // 空列表视为不限制，避免误把所有配置文件都排除
+        let profiles: Vec<String> = value
+            .into_iter()
+            .map(|profile| profile.trim().to_string())
+            .filter(|profile| !profile.is_empty())
+            .collect();
+        updated.chrome_profile_allowlist = (!profiles.is_empty()).then_some(profiles);
+    }
+
+    if let Some(value) = updates.app_dir_allowlist {
+        // 与收藏夹配置白名单一致：空列表视为不限制
+        let folders: Vec<String> = value
+            .into_iter()
+            .map(|folder| folder.trim().to_string())
+            .filter(|folder| !folder.is_empty())
+            .collect();
+        updated.app_dir_allowlist = (!folders.is_empty()).then_some(folders);
+    }
+
+    if let Some(value) = updates.max_icon_bytes {
+        updated.max_icon_bytes = value;
+    }
+
+    if let Some(value) = updates.offline_mode {
+        updated.offline_mode = value;
+    }
+
+    if let Some(value) = updates.custom_bookmark_files {
+        for file in value.iter() {
+            bookmarks::validate_bookmark_file(&file.path)?;
+        }
+        updated.custom_bookmark_files = value;
+    }
+
+    if let Some(value) = updates.mode_cycle_hotkey {
+        updated.mode_cycle_hotkey = value.trim().to_string();
+    }
+
+    if let Some(value) = updates.launch_on_active_monitor {
+        updated.launch_on_active_monitor = value;
+    }
+
+    if let Some(value) = updates.score_overrides {
+        // 0 与未设置等价，不写入配置
+        updated.score_overrides = value.into_iter().filter(|(_, delta)| *delta != 0).collect();
+    }
+
+    if let Some(value) = updates.extra_app_dir_excludes {
+        updated.extra_app_dir_excludes = value
+            .into_iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+    }
+
+    if let Some(value) = updates.quick_open_hotkey {
+        let normalized = value.trim();
+        if !normalized.is_empty() && normalized.eq_ignore_ascii_case(&updated.global_hotkey) {
+            return Err("快速打开快捷键不能与唤起快捷键相同".into());
+        }
+        if normalized != updated.quick_open_hotkey {
+            if !normalized.is_empty() {
+                hotkey::validate_hotkey(normalized)?;
+            }
+            updated.quick_open_hotkey = normalized.to_string();
+        }
+    }
+
+    if let Some(value) = updates.capture_crashes {
+        updated.capture_crashes = value;
+    }
+
+    if let Some(value) = updates.enable_cjk_transliteration {
+        updated.enable_cjk_transliteration = value;
     }
 
-    guard.save(&app_handle)?;
-    let snapshot = guard.clone();
-    let _ = app_handle.emit(SETTINGS_UPDATED_EVENT, snapshot.clone());
-    Ok(snapshot)
+    if let Some(value) = updates.hide_after_launch {
+        updated.hide_after_launch = value;
+    }
+
+    if let Some(value) = updates.start_menu_max_depth {
+        updated.start_menu_max_depth = value;
+    }
+
+    if let Some(value) = updates.show_last_used {
+        updated.show_last_used = value;
+    }
+
+    if let Some(value) = updates.index_apps_folder {
+        updated.index_apps_folder = value;
+    }
+
+    if let Some(value) = updates.confirm_url_open {
+        updated.confirm_url_open = value;
+    }
+
+    if let Some(value) = updates.locale {
+        let value = value.trim();
+        // 空值无法确定排序规则，忽略
+        if !value.is_empty() {
+            updated.locale = value.to_string();
+        }
+    }
+
+    if let Some(value) = updates.profiles {
+        let mut profiles = HashMap::new();
+        for (name, mut overrides) in value {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            if let Some(template) = overrides.search_url_template.take() {
+                overrides.search_url_template = Some(normalize_search_template(&template)?);
+            }
+            profiles.insert(name.to_string(), overrides);
+        }
+        updated.profiles = profiles;
+        // 当前方案被删除时回到基础配置
+        if !updated.profiles.contains_key(&updated.active_profile) {
+            updated.active_profile.clear();
+        }
+    }
+
+    if let Some(value) = updates.show_truncation_hint {
+        updated.show_truncation_hint = value;
+    }
+
+    if let Some(value) = updates.enable_paste_action {
+        updated.enable_paste_action = value;
+    }
+
+    if let Some(value) = updates.search_url_template {
+        updated.search_url_template = normalize_search_template(&value)?;
+    }
+
+    // 被当前方案覆盖的字段改了也不会生效，直接拒绝以免用户误以为已保存
+    if let Some(field) = updated.shadowed_change(&guard) {
+        return Err(format!(
+            "{field} 已被配置方案 {} 覆盖，请在该方案中修改",
+            updated.active_profile
+        ));
+    }
+
+    // 所有校验通过后才注册快捷键；注册或保存失败时恢复原快捷键，保持与已保存的配置一致
+    if let Err(err) = bind_changed_hotkeys(&app_handle, &state, &updated, &guard)
+        .and_then(|()| updated.save(&app_handle))
+    {
+        if let Err(restore_err) = bind_changed_hotkeys(&app_handle, &state, &guard, &updated) {
+            log::warn!("恢复原快捷键失败: {restore_err}");
+        }
+        return Err(err);
+    }
+    state.configure_matcher(updated.smart_case);
+    elevation::configure(updated.use_elevation_broker);
+    crash::configure(updated.capture_crashes || updated.debug_mode);
+    *guard = Arc::new(updated.clone());
+    let _ = app_handle.emit(SETTINGS_UPDATED_EVENT, updated.clone());
+    Ok(updated)
+}
+
+/// Registers the launcher and quick-open hotkeys of `config` that differ from `previous`.
+fn bind_changed_hotkeys(
+    app_handle: &AppHandle,
+    state: &AppState,
+    config: &AppConfig,
+    previous: &AppConfig,
+) -> Result<(), String> {
+    if config.global_hotkey != previous.global_hotkey {
+        bind_hotkey(app_handle, state, &config.global_hotkey)?;
+    }
+    if config.quick_open_hotkey != previous.quick_open_hotkey {
+        hotkey::bind_quick_open_hotkey(app_handle, state, &config.quick_open_hotkey)?;
+    }
+    Ok(())
+}
+
+/// Activates the named config profile (empty for the base config), then rebinds the hotkeys
+/// and reindexes so the profile's overrides take effect.
+#[tauri::command]
+pub async fn switch_profile(
+    name: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    let name = name.trim().to_string();
+    let updated = {
+        let mut guard = state
+            .config
+            .lock()
+            .map_err(|_| "无法获取配置".to_string())?;
+        if !name.is_empty() && !guard.profiles.contains_key(&name) {
+            return Err(format!("未找到配置方案 {name}"));
+        }
+        let mut updated = AppConfig::clone(&guard);
+        updated.active_profile = name;
+        updated.save(&app_handle)?;
+        *guard = Arc::new(updated.clone());
+        updated
+    };
+    log::info!("已切换到配置方案 {:?}", updated.active_profile);
+
+    if let Err(err) = hotkey::rebind_hotkeys(&app_handle, &state) {
+        log::warn!("切换配置方案后重新注册快捷键失败: {err}");
+    }
+    trigger_reindex(app_handle.clone(), state, None).await?;
+    let _ = app_handle.emit(SETTINGS_UPDATED_EVENT, updated.clone());
+    Ok(updated)
 }
 
 
 #[tauri::command]
-pub fn update_hotkey(
-    hotkey: String,
-    query_delay_ms: Option<u64>,
+pub fn add_custom_launcher(
+    launcher: CustomLauncher,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<AppConfig, String> {
+    let mut launcher = launcher;
+    launcher.name = launcher.name.trim().to_string();
+    launcher.target = launcher.target.trim().to_string();
+    if launcher.id.trim().is_empty() {
+        launcher.id = custom_launcher_id(&launcher);
+    }
+    launcher.validate()?;
+
+    let mut launchers = state.config_snapshot().custom_launchers.clone();
+    launchers.retain(|existing| existing.id != launcher.id);
+    launchers.push(launcher);
+
     update_settings(
         SettingsUpdatePayload {
-            global_hotkey: Some(hotkey),
-            query_delay_ms,
-            max_results: None,
-            enable_app_results: None,
-            enable_bookmark_results: None,
-            prefix_app: None,
-            prefix_bookmark: None,
-            prefix_search: None,
-            launch_on_startup: None,
-            force_english_input: None,
-            debug_mode: None,
-            system_tool_exclusions: None,
+            custom_launchers: Some(launchers),
+            ..Default::default()
         },
         app_handle,
         state,
@@ -448,142 +1954,1205 @@ pub fn update_hotkey(
 }
 
 #[tauri::command]
-pub fn begin_hotkey_capture(
+pub fn remove_custom_launcher(
+    id: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    hotkey_capture::start(app_handle, state.inner().clone())
-}
+) -> Result<AppConfig, String> {
+    let mut launchers = state.config_snapshot().custom_launchers.clone();
+    let before = launchers.len();
+    launchers.retain(|launcher| launcher.id != id);
+    if launchers.len() == before {
+        return Err("未找到对应的自定义启动项".into());
+    }
 
-#[tauri::command]
-pub fn end_hotkey_capture() -> Result<(), String> {
+    update_settings(
+        SettingsUpdatePayload {
+            custom_launchers: Some(launchers),
+            ..Default::default()
+        },
+        app_handle,
+        state,
+    )
+}
+
+/// Builds a result id from `prefix` and a short hash of `content`, so re-running a query yields
+/// the same id for the same URL, path or command.
+fn content_result_id(prefix: &str, content: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(content.as_bytes());
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{prefix}-{hash}")
+}
+
+fn custom_launcher_id(launcher: &CustomLauncher) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(launcher.name.to_lowercase().as_bytes());
+    hasher.update(launcher.target.to_lowercase().as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[tauri::command]
+pub fn hide_app(
+    id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    // 同时接受搜索结果 id（app-前缀）与应用 id
+    let app_id = id.strip_prefix("app-").unwrap_or(&id).to_string();
+    let mut hidden_ids = state.config_snapshot().hidden_ids.clone();
+    if !hidden_ids.contains(&app_id) {
+        hidden_ids.push(app_id.clone());
+    }
+
+    let config = update_settings(
+        SettingsUpdatePayload {
+            hidden_ids: Some(hidden_ids),
+            ..Default::default()
+        },
+        app_handle,
+        state.clone(),
+    )?;
+
+    if let Ok(mut guard) = state.app_index.lock() {
+        guard.retain(|app| app.id != app_id);
+    }
+
+    Ok(config)
+}
+
+/// Removes an id from the hidden list. The app shows up again after the next reindex.
+#[tauri::command]
+pub fn unhide_app(
+    id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    let app_id = id.strip_prefix("app-").unwrap_or(&id);
+    let mut hidden_ids = state.config_snapshot().hidden_ids.clone();
+    let before = hidden_ids.len();
+    hidden_ids.retain(|hidden| hidden != app_id);
+    if hidden_ids.len() == before {
+        return Err("该应用未被隐藏".into());
+    }
+
+    update_settings(
+        SettingsUpdatePayload {
+            hidden_ids: Some(hidden_ids),
+            ..Default::default()
+        },
+        app_handle,
+        state,
+    )
+}
+
+/// Adds `delta` to the score of the result `id` (`app-<id>` or `bookmark-<id>`) in every
+/// future query. A delta of 0 removes the override.
+#[tauri::command]
+pub fn set_score_override(
+    id: String,
+    delta: i64,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    let id = id.trim();
+    if !id.starts_with("app-") && !id.starts_with("bookmark-") {
+        return Err("仅支持应用或收藏夹结果".into());
+    }
+    let mut overrides = state.config_snapshot().score_overrides.clone();
+    overrides.insert(id.to_string(), delta);
+
+    update_settings(
+        SettingsUpdatePayload {
+            score_overrides: Some(overrides),
+            ..Default::default()
+        },
+        app_handle,
+        state,
+    )
+}
+
+#[tauri::command]
+pub fn clear_score_override(
+    id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    let mut overrides = state.config_snapshot().score_overrides.clone();
+    if overrides.remove(id.trim()).is_none() {
+        return Err("该结果没有设置分数调整".into());
+    }
+
+    update_settings(
+        SettingsUpdatePayload {
+            score_overrides: Some(overrides),
+            ..Default::default()
+        },
+        app_handle,
+        state,
+    )
+}
+
+#[tauri::command]
+pub fn list_hidden(state: State<'_, AppState>) -> Vec<String> {
+    state.config_snapshot().hidden_ids.clone()
+}
+
+#[tauri::command]
+pub fn update_hotkey(
+    hotkey: String,
+    query_delay_ms: Option<u64>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppConfig, String> {
+    update_settings(
+        SettingsUpdatePayload {
+            global_hotkey: Some(hotkey),
+            query_delay_ms,
+            ..Default::default()
+        },
+        app_handle,
+        state,
+    )
+}
+
+/// Re-registers the configured hotkeys for when the global hotkey stopped responding.
+#[tauri::command]
+pub fn rebind_hotkey(app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    hotkey::rebind_hotkeys(&app_handle, &state)
+}
+
+#[tauri::command]
+pub fn restart_app(app_handle: AppHandle) {
+    log::info!("restarting application");
+    crate::prepare_for_exit(&app_handle);
+    app_handle.restart();
+}
+
+#[tauri::command]
+pub fn get_config_path(app_handle: AppHandle) -> Result<String, String> {
+    config::config_path(&app_handle)
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| "无法确定配置目录".to_string())
+}
+
+/// Dumps the application and bookmark indexes as pretty JSON for bug reports. Icon data is
+/// replaced by a length marker so the output stays readable.
+#[tauri::command]
+pub fn export_index(state: State<'_, AppState>) -> Result<String, String> {
+    let mut applications = state
+        .app_index
+        .lock()
+        .map_err(|_| "无法访问应用索引".to_string())?
+        .clone();
+    for app in applications.iter_mut() {
+        if !app.icon_b64.is_empty() {
+            app.icon_b64 = format!("<{} bytes>", app.icon_b64.len());
+        }
+    }
+    let bookmarks = state
+        .bookmark_index
+        .lock()
+        .map_err(|_| "无法访问书签索引".to_string())?
+        .clone();
+
+    serde_json::to_string_pretty(&serde_json::json!({
+        "applications": applications,
+        "bookmarks": bookmarks,
+    }))
+    .map_err(|err| err.to_string())
+}
+
+/// Panic message and backtrace from the last captured crash, if any.
+#[tauri::command]
+pub fn get_last_crash() -> Option<String> {
+    crash::last_crash()
+}
+
+#[tauri::command]
+pub fn get_data_dir() -> Result<String, String> {
+    windows_utils::data_dir()
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| "无法确定数据目录".to_string())
+}
+
+/// Saves the current keyboard layout and switches to English (US), e.g. when the input gains
+/// focus. Runs on the main thread because keyboard layouts are per-thread.
+#[tauri::command]
+pub fn ensure_english_input(state: State<'_, AppState>) -> Result<(), String> {
+    crate::force_english_input(&state)
+}
+
+/// Restores the keyboard layout saved by `ensure_english_input` or when the window was shown.
+#[tauri::command]
+pub fn restore_input(app_handle: AppHandle) -> Result<(), String> {
+    crate::restore_saved_input_method(&app_handle);
+    Ok(())
+}
+
+/// Checks a hotkey string with the same rules as binding, without registering it.
+#[tauri::command]
+pub fn validate_hotkey(hotkey: String) -> Result<(), String> {
+    hotkey::validate_hotkey(&hotkey).map(|_| ())
+}
+
+#[tauri::command]
+pub fn begin_hotkey_capture(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    hotkey_capture::start(app_handle, state.inner().clone())
+}
+
+#[tauri::command]
+pub fn end_hotkey_capture() -> Result<(), String> {
     hotkey_capture::stop()
 }
 
-fn normalize_query_delay(candidate: Option<u64>, current: u64) -> u64 {
-    let value = candidate.unwrap_or(current);
-    value.clamp(MIN_QUERY_DELAY_MS, MAX_QUERY_DELAY_MS)
+/// Registers the `:uninstall` secondary action of a Win32 or UWP app result.
+fn insert_uninstall_action(
+    pending_actions: &mut HashMap<String, PendingAction>,
+    result_id: &str,
+    app: &ApplicationInfo,
+) {
+    if matches!(app.app_type, AppType::Win32 | AppType::Uwp) {
+        pending_actions.insert(
+            format!("{result_id}:uninstall"),
+            Box::new(Uninstall(app.uninstall_string.clone())),
+        );
+    }
+}
+
+fn application_result(result_id: &str, app: &ApplicationInfo, score: i64) -> SearchResult {
+    let subtitle = app
+        .description
+        .clone()
+        .filter(|d| !d.is_empty())
+        .or_else(|| app.source_path.clone())
+        .unwrap_or_else(|| app.path.clone());
+    let action_id = match app.app_type {
+        AppType::Win32 => "app",
+        AppType::Uwp => "uwp",
+        AppType::Folder => "folder",
+        AppType::Script => "script",
+    };
+    SearchResult {
+        id: result_id.to_string(),
+        title: app.name.clone(),
+        subtitle,
+        icon: app.icon_b64.clone(),
+        score,
+        action_id: action_id.to_string(),
+        group: result_group(action_id).to_string(),
+        score_breakdown: None,
+        launch_count: None,
+    }
+}
+
+/// Lists apps with a known install time, newest first, optionally narrowed by the query.
+fn recently_installed_applications<'a>(
+    matcher: &SkimMatcherV2,
+    apps: &'a [ApplicationInfo],
+    query: &str,
+    weights: &ScoringWeights,
+    min_score: i64,
+    limit: usize,
+) -> Vec<&'a ApplicationInfo> {
+    let mut recent: Vec<&ApplicationInfo> = apps
+        .iter()
+        .filter(|app| app.installed_at.is_some())
+        .filter(|app| {
+            query.is_empty()
+                || match_application(
+                    matcher,
+                    app,
+                    query,
+                    weights,
+                    min_score,
+                    &[],
+                    &HashMap::new(),
+                )
+                .is_some()
+        })
+        .collect();
+    recent.sort_by_key(|app| std::cmp::Reverse(app.installed_at));
+    recent.truncate(limit);
+    recent
+}
+
+fn normalize_query_delay(candidate: Option<u64>, current: u64) -> u64 {
+    let value = candidate.unwrap_or(current);
+    value.clamp(MIN_QUERY_DELAY_MS, MAX_QUERY_DELAY_MS)
+}
+
+fn normalize_max_results(candidate: Option<u32>, current: u32) -> u32 {
+    let value = candidate.unwrap_or(current);
+    value.clamp(MIN_RESULT_LIMIT, MAX_RESULT_LIMIT)
+}
+
+
+fn normalize_prefix(value: &str) -> Option<String> {
+    let trimmed_start = value.trim_start();
+    if trimmed_start.is_empty() {
+        return None;
+    }
+
+    let mut chars = trimmed_start.chars();
+    let Some(first) = chars.next() else {
+        return None;
+    };
+
+    if !first.is_ascii_alphabetic() {
+        return None;
+    }
+
+    let mut normalized = String::new();
+    normalized.push(first.to_ascii_uppercase());
+
+    let remainder: String = chars.collect();
+    let mut remainder_chars = remainder.chars().filter(|c| !c.is_control());
+    if let Some(next) = remainder_chars.next() {
+        match next {
+            ' ' | ':' => normalized.push(next),
+            _ => return None,
+        }
+
+        if remainder_chars.any(|c| !c.is_whitespace()) {
+            return None;
+        }
+    }
+
+    Some(normalized)
+}
+
+/// Formats `[title](url)`. Without a title the URL's host becomes the link text. Backslashes
+/// and brackets in the text are escaped, and characters that would end the target early are
+/// percent-encoded.
+fn markdown_link(title: Option<&str>, url: &str) -> String {
+    let url = url.trim();
+    let target = if has_url_scheme(url) {
+        url.to_string()
+    } else {
+        format!("https://{url}")
+    };
+    let text = match title.map(str::trim).filter(|title| !title.is_empty()) {
+        Some(title) => title.to_string(),
+        None => Url::parse(&target)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string()),
+    };
+
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    let target = target
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29");
+    format!("[{escaped}]({target})")
+}
+
+pub(crate) fn open_url(app_handle: &AppHandle, target: &str) -> Result<(), String> {
+    app_handle
+        .opener()
+        .open_url(target.to_string(), Option::<&str>::None)
+        .map_err(|err| err.to_string())
+}
+
+/// Reduces a URL to its origin, e.g. `https://docs.rs/foo` → `https://docs.rs`. IP hosts and
+/// non-default ports are kept; unparsable URLs are returned unchanged.
+pub(crate) fn root_url(target: &str) -> String {
+    match Url::parse(target) {
+        Ok(parsed) if parsed.origin().is_tuple() => parsed.origin().ascii_serialization(),
+        _ => target.to_string(),
+    }
+}
+
+/// Builds the bookmark manager URL for `entry`, falling back to the manager root when the
+/// node id is unknown.
+pub(crate) fn bookmark_manager_url(entry: &BookmarkEntry) -> String {
+    match entry.node_id.as_deref() {
+        Some(node_id) => format!("{}?id={node_id}", entry.manager_url),
+        None => entry.manager_url.to_string(),
+    }
+}
+
+/// Builds the bookmark manager URL for the folder containing `entry`, falling back to the
+/// manager root when the folder id is unknown.
+pub(crate) fn bookmark_folder_url(entry: &BookmarkEntry) -> String {
+    match entry.folder_node_id.as_deref() {
+        Some(folder_id) => format!("{}?id={folder_id}", entry.manager_url),
+        None => entry.manager_url.to_string(),
+    }
+}
+
+/// Opens `target` in the configured bookmark browser, or with the system handler when none is
+/// configured.
+pub(crate) fn open_in_bookmark_browser(
+    app_handle: &AppHandle,
+    config: &AppConfig,
+    target: &str,
+) -> Result<(), String> {
+    if config.bookmark_browser.is_empty() {
+        return open_url(app_handle, target);
+    }
+    shell_execute_raw(
+        &config.bookmark_browser,
+        Some(&format!("\"{target}\"")),
+        None,
+        false,
+    )
+}
+
+/// Opens `target` normally, or in a private window when requested and the browser supports it.
+pub(crate) fn open_url_with_mode(
+    app_handle: &AppHandle,
+    state: &AppState,
+    target: &str,
+    private: bool,
+    background: bool,
+) -> Result<(), String> {
+    let config = state.config_snapshot();
+    let cleaned;
+    let target = if config.strip_tracking_params {
+        cleaned = clean_url(target, &config.tracking_param_denylist);
+        cleaned.as_str()
+    } else {
+        target
+    };
+
+    if !private {
+        return if background {
+            open_url_in_background(target)
+        } else {
+            open_url(app_handle, target)
+        };
+    }
+
+    let configured = config.bookmark_browser.clone();
+    let browser = if configured.is_empty() {
+        windows_utils::default_browser_executable()
+    } else {
+        Some(configured)
+    };
+
+    // 无法识别浏览器或其隐私模式参数时，退回普通方式打开
+    let Some((browser, flag)) =
+        browser.and_then(|browser| private_browsing_flag(&browser).map(|flag| (browser, flag)))
+    else {
+        log::warn!("未识别浏览器的隐私模式参数，使用普通方式打开");
+        return open_url(app_handle, target);
+    };
+
+    let arguments = format!("{flag} {}", quote_argument(target));
+    shell_execute_raw(&browser, Some(&arguments), None, false)
+}
+
+/// Removes query parameters matching `denylist` (case-insensitive, trailing `*` = prefix)
+/// while keeping every other parameter and the fragment untouched.
+fn clean_url(url: &str, denylist: &[String]) -> String {
+    let (without_fragment, fragment) = match url.split_once('#') {
+        Some((head, fragment)) => (head, Some(fragment)),
+        None => (url, None),
+    };
+    let Some((base, query)) = without_fragment.split_once('?') else {
+        return url.to_string();
+    };
+
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map_or(*pair, |(key, _)| key);
+            !is_denied_param(key, denylist)
+        })
+        .collect();
+
+    let mut cleaned = base.to_string();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    cleaned
+}
+
+fn is_denied_param(key: &str, denylist: &[String]) -> bool {
+    let key = key.to_ascii_lowercase();
+    denylist.iter().any(|pattern| {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => !pattern.is_empty() && key == pattern,
+        }
+    })
+}
+
+/// Hands the URL to the default handler without activating it. Browsers that
+/// already have a window open usually add the page as a background tab.
+fn open_url_in_background(target: &str) -> Result<(), String> {
+    shell_execute_internal(OsStr::new(target), None, None, None, SW_SHOWMINNOACTIVE)
+}
+
+fn private_browsing_flag(browser: &str) -> Option<&'static str> {
+    let file_name = Path::new(browser)
+        .file_name()?
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    PRIVATE_BROWSING_FLAGS
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, flag)| *flag)
+}
+
+pub(crate) fn launch_win32_app(app: &ApplicationInfo, run_as_admin: bool) -> Result<(), String> {
+    // shell:AppsFolder 中的条目没有文件路径，交由外壳按 AppUserModelId 启动
+    if app.path.starts_with(windows_utils::APPS_FOLDER_NAMESPACE) {
+        return shell_execute_raw(&app.path, None, None, run_as_admin);
+    }
+    let primary = Path::new(&app.path);
+    match shell_execute_path(primary, run_as_admin) {
+        Ok(_) => Ok(()),
+        Err(primary_err) => {
+            if let Some(source) = &app.source_path {
+                launch_from_source(
+                    source,
+                    app.arguments.as_deref(),
+                    app.working_directory.as_deref(),
+                    run_as_admin,
+                )
+                .or(Err(primary_err))
+            } else {
+                Err(primary_err)
+            }
+        }
+    }
+}
+
+/// Launches a Win32 app and moves its main window into `work_area` once it appears. Falls back
+/// to [`launch_win32_app`] when the direct launch fails.
+pub(crate) fn launch_win32_app_on_monitor(
+    app: &ApplicationInfo,
+    work_area: RECT,
+) -> Result<(), String> {
+    let path = Path::new(&app.path);
+    if !path.exists() {
+        return launch_win32_app(app, false);
+    }
+    match shell_execute_for_process(path.as_os_str(), None, None) {
+        Ok(Some(process_id)) => {
+            std::thread::spawn(move || {
+                windows_utils::move_process_window_to(process_id, work_area, LAUNCH_WINDOW_TIMEOUT)
+            });
+            Ok(())
+        }
+        // 交由已运行的实例处理时拿不到新进程，窗口保持原位
+        Ok(None) => Ok(()),
+        Err(_) => launch_win32_app(app, false),
+    }
+}
+
+/// Work area of the monitor the launcher window is on.
+fn launcher_work_area(app_handle: &AppHandle) -> Option<RECT> {
+    let window = crate::main_window(app_handle)?;
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    windows_utils::monitor_work_area_at(
+        position.x + size.width as i32 / 2,
+        position.y + size.height as i32 / 2,
+    )
+}
+
+/// Runs an indexed script through its interpreter: `.ps1` via PowerShell, `.bat`/`.cmd` via cmd.
+pub(crate) fn launch_script(app: &ApplicationInfo, run_as_admin: bool) -> Result<(), String> {
+    let path = Path::new(&app.path);
+    if !path.is_file() {
+        return Err("脚本不存在或已被移动".into());
+    }
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())
+        .unwrap_or_default();
+    let (program, arguments) = match extension.as_str() {
+        "ps1" => ("powershell.exe", format!("-File {}", quote_path(&app.path))),
+        "bat" | "cmd" => ("cmd.exe", format!("/c {}", quote_path(&app.path))),
+        _ => return Err(format!("不支持的脚本类型: {extension}")),
+    };
+    shell_execute_raw(
+        program,
+        Some(&arguments),
+        app.working_directory.as_deref(),
+        run_as_admin,
+    )
+}
+
+pub(crate) fn launch_custom_launcher(
+    app_handle: &AppHandle,
+    launcher: &CustomLauncher,
+    run_as_admin: bool,
+) -> Result<(), String> {
+    let target = launcher.target.trim();
+    match launcher.target_kind() {
+        CustomTargetKind::Url => open_url(app_handle, target),
+        CustomTargetKind::Uri => shell_execute_uri(target),
+        CustomTargetKind::File => {
+            if !Path::new(target).exists() {
+                return Err("目标程序不存在或已被移动".into());
+            }
+            shell_execute_raw(
+                target,
+                launcher.arguments.as_deref(),
+                launcher.working_directory.as_deref(),
+                run_as_admin,
+            )
+        }
+    }
+}
+
+/// Icon for a custom launcher result. Extraction is cached per source path, since it would
+/// otherwise run for every matching launcher on every keystroke.
+fn custom_launcher_icon(
+    launcher: &CustomLauncher,
+    cache: &Mutex<HashMap<String, String>>,
+) -> String {
+    let Some(source) = launcher
+        .icon_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .or_else(|| {
+            (launcher.target_kind() == CustomTargetKind::File).then_some(launcher.target.trim())
+        })
+    else {
+        return String::new();
+    };
+    if let Some(icon) = cache
+        .lock()
+        .ok()
+        .and_then(|guard| guard.get(source).cloned())
+    {
+        return icon;
+    }
+    let icon = extract_icon_from_path(source, 0).unwrap_or_default();
+    if let Ok(mut guard) = cache.lock() {
+        guard.insert(source.to_string(), icon.clone());
+    }
+    icon
+}
+
+pub(crate) fn shell_execute_path(path: &Path, run_as_admin: bool) -> Result<(), String> {
+    if !path.exists() {
+        return Err("目标程序不存在或已被移动".into());
+    }
+    if run_as_admin && launch_via_broker(&path.to_string_lossy(), None, None) {
+        return Ok(());
+    }
+
+    let verb = if run_as_admin {
+        Some(OsStr::new("runas"))
+    } else {
+        None
+    };
+    shell_execute_internal(path.as_os_str(), None, None, verb, SW_SHOWNORMAL)
+}
+
+/// Runs the uninstaller without elevation; installers request it themselves when needed.
+pub(crate) fn uninstall_app(command: Option<&str>) -> Result<(), String> {
+    match command {
+        Some(command) => {
+            let (program, arguments) = indexer::split_command_line(command);
+            shell_execute_raw(&program, Some(&arguments), None, false)
+        }
+        None => shell_execute_raw(APPS_FEATURES_URI, None, None, false),
+    }
+}
+
+pub(crate) fn open_folder(path: &str) -> Result<(), String> {
+    if !Path::new(path).is_dir() {
+        return Err("文件夹不存在或已被移动".into());
+    }
+    shell_execute_raw("explorer.exe", Some(&quote_path(path)), None, false)
+}
+
+pub(crate) fn open_terminal(
+    directory: &str,
+    terminal: Terminal,
+    run_as_admin: bool,
+) -> Result<(), String> {
+    if !Path::new(directory).is_dir() {
+        return Err("文件夹不存在或已被移动".into());
+    }
+    let terminal = if terminal == Terminal::WindowsTerminal && !windows_terminal_installed() {
+        Terminal::Cmd
+    } else {
+        terminal
+    };
+    let (program, arguments) = terminal.command_line(directory);
+    shell_execute_raw(program, Some(&arguments), None, run_as_admin)
+}
+
+/// Looks for `wt.exe` on `PATH` and at its app execution alias location.
+fn windows_terminal_installed() -> bool {
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join("wt.exe").is_file()))
+        .unwrap_or(false);
+    on_path
+        || std::env::var_os("LOCALAPPDATA")
+            .map(|dir| {
+                Path::new(&dir)
+                    .join(r"Microsoft\WindowsApps\wt.exe")
+                    .exists()
+            })
+            .unwrap_or(false)
+}
+
+/// `ActivateApplication` HRESULTs with a user-facing message, and whether launching through
+/// `shell:AppsFolder` is worth trying afterwards. Unlisted codes always get the fallback.
+const UWP_ACTIVATION_ERRORS: &[(u32, &str, bool)] = &[
+    (0x8007_0002, "找不到应用文件，可能需要修复或重新安装", true),
+    (0x8007_0005, "访问被拒绝", true),
+    (0x8007_0490, "找不到该应用，可能已被卸载", false),
+    (0x8027_0254, "应用未注册，可能已被卸载", false),
+    (0x8027_025B, "应用启动超时", true),
+    (0x8027_025C, "应用启动失败，可能需要在设置中修复", true),
+];
+
+pub(crate) fn launch_uwp_app(app_id: &str) -> Result<(), String> {
+    let Err(err) = activate_uwp_app(app_id) else {
+        return Ok(());
+    };
+    let code = err.code().0 as u32;
+    log::warn!("ActivateApplication failed for {app_id}: 0x{code:08X} {err}");
+
+    let known = UWP_ACTIVATION_ERRORS
+        .iter()
+        .find(|(value, _, _)| *value == code);
+    if known.is_none_or(|(_, _, fallback)| *fallback) {
+        // 激活接口失败时改由外壳启动，部分需要修复的应用仍可打开
+        match shell_execute_raw(&format!("shell:AppsFolder\\{app_id}"), None, None, false) {
+            Ok(()) => return Ok(()),
+            Err(fallback_err) => log::warn!("shell:AppsFolder fallback failed: {fallback_err}"),
+        }
+    }
+
+    let message = known
+        .map(|(_, message, _)| *message)
+        .unwrap_or("无法启动应用");
+    Err(format!("{message} (0x{code:08X})"))
+}
+
+fn activate_uwp_app(app_id: &str) -> windows::core::Result<()> {
+    unsafe {
+        let _guard = ComGuard::new()?;
+
+        let manager: IApplicationActivationManager =
+            CoCreateInstance(&ApplicationActivationManager, None, CLSCTX_LOCAL_SERVER)?;
+
+        let app_id = HSTRING::from(app_id);
+        let _process_id =
+            manager.ActivateApplication(&app_id, PCWSTR::null(), ACTIVATEOPTIONS::default())?;
+        Ok(())
+    }
+}
+
+/// Expands a search-engine template. Only the query is percent-encoded, so the template's own
+/// parameters and fragment (e.g. `https://x/?a=1&q={query}#frag`) are kept verbatim.
+fn build_search_url(template: &str, query: &str) -> String {
+    let encoded = urlencoding::encode(query);
+    if template.contains(SEARCH_QUERY_PLACEHOLDER) {
+        template.replace(SEARCH_QUERY_PLACEHOLDER, &encoded)
+    } else {
+        // 模板缺少占位符时把查询追加在末尾
+        format!("{template}{encoded}")
+    }
+}
+
+/// Trims a search-engine template, falling back to Google when empty. The template must expand
+/// to an http(s) URL.
+fn normalize_search_template(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(GOOGLE_SEARCH_TEMPLATE.to_string());
+    }
+    match Url::parse(&build_search_url(value, "egg")) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(value.to_string()),
+        _ => Err("搜索引擎地址需为 http(s) 网址，并用 {query} 表示搜索词".into()),
+    }
+}
+
+/// Splits `type:<kind>` tokens (`win32`, `uwp`, `folder`, `script`) off the query. Unknown kinds
+/// are dropped from the query without restricting anything.
+fn extract_app_type_filter(query: &str) -> (String, Option<AppType>) {
+    let mut app_type = None;
+    let mut found = false;
+    let mut rest = Vec::new();
+    for token in query.split_whitespace() {
+        let kind = token
+            .get(..5)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("type:"))
+            .map(|_| token[5..].to_ascii_lowercase());
+        match kind.as_deref() {
+            Some(kind) => {
+                found = true;
+                app_type = match kind {
+                    "win32" => Some(AppType::Win32),
+                    "uwp" => Some(AppType::Uwp),
+                    "folder" => Some(AppType::Folder),
+                    "script" => Some(AppType::Script),
+                    _ => app_type,
+                };
+            }
+            None => rest.push(token),
+        }
+    }
+    if !found {
+        return (query.to_string(), None);
+    }
+    (rest.join(" "), app_type)
+}
+
+fn truncate_chars(value: &str, max_chars: usize) -> &str {
+    match value.char_indices().nth(max_chars) {
+        Some((index, _)) => &value[..index],
+        None => value,
+    }
 }
 
-fn normalize_max_results(candidate: Option<u32>, current: u32) -> u32 {
-    let value = candidate.unwrap_or(current);
-    value.clamp(MIN_RESULT_LIMIT, MAX_RESULT_LIMIT)
+fn is_url_like(input: &str) -> bool {
+    has_url_scheme(input) || input.contains('.') && input.split_whitespace().count() == 1
 }
 
-
-fn normalize_prefix(value: &str) -> Option<String> {
-    let trimmed_start = value.trim_start();
-    if trimmed_start.is_empty() {
+/// Resolves a query to an existing local file or folder, expanding environment variables.
+fn existing_local_path(input: &str) -> Option<String> {
+    if has_url_scheme(input) {
         return None;
     }
+    let expanded = windows_utils::expand_env_vars(input)?;
+    Path::new(&expanded).exists().then_some(expanded)
+}
 
-    let mut chars = trimmed_start.chars();
-    let Some(first) = chars.next() else {
-        return None;
-    };
+/// True when the input is unambiguously a URL rather than a dotted search term.
+fn has_url_scheme(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
 
-    if !first.is_ascii_alphabetic() {
-        return None;
-    }
+/// Result of matching one entry against the query.
+struct MatchOutcome<'a> {
+    score: i64,
+    /// Keyword or field that produced the best match, `None` when the title itself won.
+    matched: Option<&'a str>,
+    breakdown: ScoreBreakdown,
+}
 
-    let mut normalized = String::new();
-    normalized.push(first.to_ascii_uppercase());
+/// Keeps the best-scoring field seen while matching a single entry.
+#[derive(Default)]
+struct BestMatch<'a> {
+    best: Option<(i64, i64, Option<&'a str>)>,
+}
 
-    let remainder: String = chars.collect();
-    let mut remainder_chars = remainder.chars().filter(|c| !c.is_control());
-    if let Some(next) = remainder_chars.next() {
-        match next {
-            ' ' | ':' => normalized.push(next),
-            _ => return None,
+impl<'a> BestMatch<'a> {
+    /// Considers a fuzzy score for one field; later fields only win with a strictly higher score.
+    fn offer(&mut self, fuzzy: Option<i64>, penalty: i64, matched: Option<&'a str>) {
+        let Some(fuzzy) = fuzzy else {
+            return;
+        };
+        let score = fuzzy - penalty;
+        if self
+            .best
+            .is_none_or(|(current, current_penalty, _)| score > current - current_penalty)
+        {
+            self.best = Some((fuzzy, penalty, matched));
         }
+    }
 
-        if remainder_chars.any(|c| !c.is_whitespace()) {
+    /// Builds the outcome, dropping weak fuzzy matches below `min_score`. Entries whose name
+    /// literally contains the query (non-zero `prefix_boost`) always survive.
+    fn finish(self, prefix_boost: i64, min_score: i64) -> Option<MatchOutcome<'a>> {
+        let (fuzzy, penalty, matched) = self.best?;
+        if prefix_boost == 0 && fuzzy < min_score {
             return None;
         }
+        let breakdown = ScoreBreakdown {
+            base_fuzzy: fuzzy,
+            keyword_penalty: penalty,
+            frecency_bonus: 0,
+            prefix_boost,
+            session_boost: 0,
+            score_override: 0,
+            deprioritize_penalty: 0,
+        };
+        Some(MatchOutcome {
+            score: breakdown.total(),
+            matched,
+            breakdown,
+        })
     }
+}
 
-    Some(normalized)
+impl MatchOutcome<'_> {
+    fn with_session_boost(mut self, boost: i64) -> Self {
+        self.breakdown.session_boost = boost;
+        self.score = self.breakdown.total();
+        self
+    }
+
+    fn with_score_override(mut self, delta: i64) -> Self {
+        self.breakdown.score_override = delta;
+        self.score = self.breakdown.total();
+        self
+    }
+
+    fn with_deprioritize_penalty(mut self, penalty: i64) -> Self {
+        self.breakdown.deprioritize_penalty = penalty;
+        self.score = self.breakdown.total();
+        self
+    }
 }
 
-fn open_url(app_handle: &AppHandle, target: &str) -> Result<(), String> {
-    app_handle
-        .opener()
-        .open_url(target.to_string(), Option::<&str>::None)
-        .map_err(|err| err.to_string())
+/// Bonus for an id launched earlier in this session: the full `recent_launch_boost` for the
+/// latest launch, decreasing linearly for older ones, 0 when not in the list.
+fn recent_launch_boost(recent: &[String], id: &str, weights: &ScoringWeights) -> i64 {
+    recent
+        .iter()
+        .position(|launched| launched == id)
+        .map(|rank| {
+            weights.recent_launch_boost * (MAX_RECENT_LAUNCHES - rank) as i64
+                / MAX_RECENT_LAUNCHES as i64
+        })
+        .unwrap_or(0)
 }
 
-fn launch_win32_app(app: &ApplicationInfo, run_as_admin: bool) -> Result<(), String> {
-    let primary = Path::new(&app.path);
-    match shell_execute_path(primary, run_as_admin) {
-        Ok(_) => Ok(()),
-        Err(primary_err) => {
-            if let Some(source) = &app.source_path {
-                launch_from_source(
-                    source,
-                    app.arguments.as_deref(),
-                    app.working_directory.as_deref(),
-                    run_as_admin,
-                )
-                .or(Err(primary_err))
-            } else {
-                Err(primary_err)
-            }
-        }
+/// User-configured score delta for the result `{prefix}-{id}`, 0 when none is set.
+fn score_override(overrides: &HashMap<String, i64>, prefix: &str, id: &str) -> i64 {
+    if overrides.is_empty() {
+        return 0;
     }
+    overrides
+        .get(&format!("{prefix}-{id}"))
+        .copied()
+        .unwrap_or(0)
 }
 
-fn shell_execute_path(path: &Path, run_as_admin: bool) -> Result<(), String> {
-    if !path.exists() {
-        return Err("目标程序不存在或已被移动".into());
+/// Scores an application against its name, keywords and description.
+fn match_application<'a>(
+    matcher: &SkimMatcherV2,
+    app: &'a ApplicationInfo,
+    query: &str,
+    weights: &ScoringWeights,
+    min_score: i64,
+    recent: &[String],
+    overrides: &HashMap<String, i64>,
+) -> Option<MatchOutcome<'a>> {
+    let mut best = BestMatch::default();
+    best.offer(matcher.fuzzy_match(&app.name, query), 0, None);
+
+    for keyword in &app.keywords {
+        if keyword.is_empty() {
+            continue;
+        }
+        // prefer primary name by adding small penalty to keyword matches
+        best.offer(
+            matcher.fuzzy_match(keyword, query),
+            weights.keyword_penalty,
+            Some(keyword.as_str()),
+        );
     }
 
-    let verb = if run_as_admin {
-        Some(OsStr::new("runas"))
+    // 描述/发行商匹配单独计分，使按厂商搜索（如 "jetbrains"）得到中等分数
+    if let Some(description) = app.description.as_deref().filter(|value| !value.is_empty()) {
+        best.offer(
+            matcher.fuzzy_match(description, query),
+            weights.description_penalty,
+            Some(description),
+        );
+    }
+
+    best.finish(literal_match_boost(&app.name, query, weights), min_score)
+        .map(|outcome| {
+            outcome
+                .with_session_boost(recent_launch_boost(recent, &app.id, weights))
+                .with_score_override(score_override(overrides, "app", &app.id))
+                .with_deprioritize_penalty(deprioritize_penalty(app, weights))
+        })
+}
+
+/// Returns the configured penalty when the app's name or path contains one of the
+/// `deprioritize_patterns`, 0 otherwise.
+fn deprioritize_penalty(app: &ApplicationInfo, weights: &ScoringWeights) -> i64 {
+    if weights.deprioritize_penalty == 0 {
+        return 0;
+    }
+    let name = app.name.to_lowercase();
+    let path = app.path.to_lowercase();
+    let matches = weights
+        .deprioritize_patterns
+        .iter()
+        .map(|pattern| pattern.trim().to_lowercase())
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| name.contains(&pattern) || path.contains(&pattern));
+    if matches {
+        weights.deprioritize_penalty
     } else {
-        None
-    };
-    shell_execute_internal(path.as_os_str(), None, None, verb)
+        0
+    }
 }
 
-fn launch_uwp_app(app_id: &str) -> Result<(), String> {
-    unsafe {
-        let _guard = ComGuard::new().map_err(|err| err.to_string())?;
+/// Orders results by score, then title and id, so equal-score results keep the same order
+/// between keystrokes. Titles are compared by their [`collation_key`] for `locale`, falling
+/// back to the lowercase title. Web-search entries sort after every other result whatever
+/// their score, so heavily penalized matches never fall below them even if the full list is
+/// re-sorted.
+fn sort_results(results: &mut [SearchResult], locale: &str) {
+    results.sort_by_cached_key(|result| {
+        (
+            is_web_search(result),
+            std::cmp::Reverse(result.score),
+            collation_key(&result.title, locale),
+            result.title.to_lowercase(),
+            result.id.clone(),
+        )
+    });
+}
 
-        let manager: IApplicationActivationManager =
-            CoCreateInstance(&ApplicationActivationManager, None, CLSCTX_LOCAL_SERVER)
-                .map_err(|err| err.to_string())?;
+fn is_web_search(result: &SearchResult) -> bool {
+    result.action_id == "search"
+}
 
-        let app_id = HSTRING::from(app_id);
-        let _process_id = manager
-            .ActivateApplication(&app_id, PCWSTR::null(), ACTIVATEOPTIONS::default())
-            .map_err(|err| err.to_string())?;
-        Ok(())
+/// Copies launch counts from the usage stats onto application and bookmark results.
+fn fill_launch_counts(
+    results: &mut [SearchResult],
+    pending: &HashMap<String, PendingAction>,
+    usage_stats: &Mutex<UsageStats>,
+    show_last_used: bool,
+) {
+    let Ok(stats) = usage_stats.lock() else {
+        return;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    for result in results.iter_mut() {
+        let Some(action) = pending.get(&result.id) else {
+            continue;
+        };
+        let Some(key) = action.usage_key() else {
+            continue;
+        };
+        let Some(entry) = stats.get(&key) else {
+            continue;
+        };
+        result.launch_count = Some(entry.launch_count);
+        // 仅应用与书签附加上次使用时间，网址结果的副标题本身就是网址
+        if show_last_used && entry.last_used > 0 && !key.starts_with(URL_USAGE_PREFIX) {
+            result.subtitle = format!(
+                "{} · 上次使用 {}",
+                result.subtitle,
+                format_relative_time(now - entry.last_used)
+            );
+        }
     }
 }
 
-fn is_url_like(input: &str) -> bool {
-    input.starts_with("http://")
-        || input.starts_with("https://")
-        || input.contains('.') && input.split_whitespace().count() == 1
+/// Formats an elapsed duration in seconds as a short Chinese relative time, e.g. "3 天前".
+fn format_relative_time(elapsed_secs: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    match elapsed_secs.max(0) {
+        secs if secs < MINUTE => "刚刚".to_string(),
+        secs if secs < HOUR => format!("{} 分钟前", secs / MINUTE),
+        secs if secs < DAY => format!("{} 小时前", secs / HOUR),
+        secs => format!("{} 天前", secs / DAY),
+    }
 }
 
-fn match_application(matcher: &SkimMatcherV2, app: &ApplicationInfo, query: &str) -> Option<i64> {
-    let mut best = matcher.fuzzy_match(&app.name, query);
+/// Appends a suffix to results whose titles collide within the returned list, e.g. two "Setup"
+/// entries become "Setup (7-Zip)" and "Setup (WinRAR)". Titles that are already unique are
+/// left untouched.
+fn disambiguate_titles(results: &mut [SearchResult], pending: &HashMap<String, PendingAction>) {
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, result) in results.iter().enumerate() {
+        groups
+            .entry(result.title.to_lowercase())
+            .or_default()
+            .push(index);
+    }
 
-    for keyword in &app.keywords {
-        if keyword.is_empty() {
+    for indices in groups.values().filter(|indices| indices.len() > 1) {
+        let candidates: Vec<Vec<String>> = indices
+            .iter()
+            .map(|&index| {
+                pending
+                    .get(&results[index].id)
+                    .map(|action| action.title_suffix_candidates())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        // 依次尝试发行商、所在文件夹、完整路径，取第一个能区分全部结果的层级
+        let levels = candidates.iter().map(Vec::len).max().unwrap_or(0);
+        let chosen = (0..levels)
+            .find(|&level| {
+                let mut seen = HashSet::new();
+                candidates.iter().all(|candidate| {
+                    candidate
+                        .get(level)
+                        .is_some_and(|suffix| seen.insert(suffix.to_lowercase()))
+                })
+            })
+            .or(levels.checked_sub(1));
+        let Some(level) = chosen else {
             continue;
-        }
+        };
 
-        if let Some(score) = matcher.fuzzy_match(keyword, query) {
-            let score = score - 5; // prefer primary name by adding small penalty to keyword matches
-            if best.is_none_or(|current| score > current) {
-                best = Some(score);
+        for (&index, candidate) in indices.iter().zip(&candidates) {
+            if let Some(suffix) = candidate.get(level).or_else(|| candidate.last()) {
+                results[index].title = format!("{} ({suffix})", results[index].title);
             }
         }
     }
+}
+
+/// Appends a "匹配: <keyword>" hint so users can see why a result appeared.
+fn with_match_hint(subtitle: String, matched: Option<&str>) -> String {
+    match matched {
+        Some(matched) => format!("{subtitle} · 匹配: {matched}"),
+        None => subtitle,
+    }
+}
 
-    best
+/// Rewards names that literally start with or contain the query, so scattered
+/// character matches don't outrank an obvious hit.
+fn literal_match_boost(name: &str, query: &str, weights: &ScoringWeights) -> i64 {
+    let name_lower = name.to_lowercase();
+    let query_lower = query.to_lowercase();
+    if name_lower.starts_with(&query_lower) {
+        weights.prefix_boost
+    } else if name_lower.contains(&query_lower) {
+        weights.substring_boost
+    } else {
+        0
+    }
 }
 
 fn launch_from_source(
@@ -604,7 +3173,7 @@ fn launch_from_source(
     shell_execute_raw(normalized, arguments, working_directory, run_as_admin)
 }
 
-fn shell_execute_raw(
+pub(crate) fn shell_execute_raw(
     target: &str,
     arguments: Option<&str>,
     working_directory: Option<&str>,
@@ -620,6 +3189,10 @@ fn shell_execute_raw(
         .filter(|value| !value.is_empty())
         .map(OsString::from);
 
+    if run_as_admin && launch_via_broker(target, arguments, working_directory) {
+        return Ok(());
+    }
+
     let verb = if run_as_admin {
         Some(OsStr::new("runas"))
     } else {
@@ -631,19 +3204,39 @@ fn shell_execute_raw(
         argument_os.as_deref(),
         working_dir_os.as_deref(),
         verb,
+        SW_SHOWNORMAL,
     )
 }
 
 fn shell_execute_uri(uri: &str) -> Result<(), String> {
     let uri_os = OsString::from(uri);
-    shell_execute_internal(uri_os.as_os_str(), None, None, None)
+    shell_execute_internal(uri_os.as_os_str(), None, None, None, SW_SHOWNORMAL)
+}
+
+/// Tries the elevation broker when it is enabled. Returns `false` to fall back to `runas`.
+fn launch_via_broker(
+    target: &str,
+    arguments: Option<&str>,
+    working_directory: Option<&str>,
+) -> bool {
+    if !elevation::is_enabled() {
+        return false;
+    }
+    match elevation::launch(target, arguments, working_directory) {
+        Ok(()) => true,
+        Err(err) => {
+            log::warn!("提权代理不可用，改用 runas 启动: {err}");
+            false
+        }
+    }
 }
 
-fn shell_execute_internal(
+pub(crate) fn shell_execute_internal(
     target: &OsStr,
     arguments: Option<&OsStr>,
     working_directory: Option<&OsStr>,
     verb: Option<&OsStr>,
+    show_cmd: SHOW_WINDOW_CMD,
 ) -> Result<(), String> {
     let file_buffer = os_str_to_wide(target);
     let arg_buffer = arguments.map(os_str_to_wide);
@@ -670,53 +3263,822 @@ fn shell_execute_internal(
             PCWSTR(file_buffer.as_ptr()),
             arg_ptr,
             dir_ptr,
-            SW_SHOWNORMAL,
+            show_cmd,
         )
     };
 
-    if result.0 as isize <= 32 {
+    let code = result.0 as isize;
+    if code <= 32 {
         Err(format!(
-            "无法启动程序 (ShellExecute 错误码 {})",
-            result.0 as isize
+            "无法启动程序：{} (ShellExecute 错误码 {code})",
+            describe_shell_execute_error(code)
         ))
     } else {
         Ok(())
     }
 }
 
-fn match_bookmark(matcher: &SkimMatcherV2, bookmark: &BookmarkEntry, query: &str) -> Option<i64> {
-    let mut best = matcher.fuzzy_match(&bookmark.title, query);
+/// `ShellExecuteExW` counterpart of [`shell_execute_internal`] that returns the id of the started
+/// process, or `None` when the shell did not start a new one.
+pub(crate) fn shell_execute_for_process(
+    target: &OsStr,
+    arguments: Option<&OsStr>,
+    verb: Option<&OsStr>,
+) -> Result<Option<u32>, String> {
+    let file_buffer = os_str_to_wide(target);
+    let arg_buffer = arguments.map(os_str_to_wide);
+    let verb_buffer = verb.map(os_str_to_wide);
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        fMask: SEE_MASK_NOCLOSEPROCESS,
+        lpVerb: verb_buffer
+            .as_ref()
+            .map(|value| PCWSTR(value.as_ptr()))
+            .unwrap_or(PCWSTR::null()),
+        lpFile: PCWSTR(file_buffer.as_ptr()),
+        lpParameters: arg_buffer
+            .as_ref()
+            .map(|value| PCWSTR(value.as_ptr()))
+            .unwrap_or(PCWSTR::null()),
+        nShow: SW_SHOWNORMAL.0,
+        ..Default::default()
+    };
+
+    unsafe {
+        if ShellExecuteExW(&mut info).is_err() {
+            let code = info.hInstApp.0 as isize;
+            return Err(format!(
+                "无法启动程序：{} (ShellExecute 错误码 {code})",
+                describe_shell_execute_error(code)
+            ));
+        }
+        if info.hProcess.is_invalid() {
+            return Ok(None);
+        }
+        let process_id = GetProcessId(info.hProcess);
+        let _ = CloseHandle(info.hProcess);
+        Ok((process_id != 0).then_some(process_id))
+    }
+}
+
+/// Documented `ShellExecute` return codes (values <= 32) and their user-facing meaning.
+const SHELL_EXECUTE_ERRORS: &[(isize, &str)] = &[
+    (0, "系统内存或资源不足"),
+    (2, "找不到文件"),
+    (3, "找不到路径"),
+    (5, "访问被拒绝"),
+    (8, "内存不足"),
+    (11, "可执行文件无效"),
+    (26, "发生共享冲突"),
+    (27, "文件关联不完整或无效"),
+    (28, "DDE 事务超时"),
+    (29, "DDE 事务失败"),
+    (30, "DDE 正忙，无法处理请求"),
+    (31, "没有关联的程序"),
+    (32, "找不到所需的动态链接库"),
+];
+
+fn describe_shell_execute_error(code: isize) -> &'static str {
+    SHELL_EXECUTE_ERRORS
+        .iter()
+        .find(|(value, _)| *value == code)
+        .map(|(_, message)| *message)
+        .unwrap_or("未知错误")
+}
 
-    if let Some(path) = &bookmark.folder_path {
-        if let Some(score) = matcher.fuzzy_match(path, query) {
-            let score = score - 5;
-            if best.is_none_or(|current| score > current) {
-                best = Some(score);
-            }
+fn match_custom_launcher<'a>(
+    matcher: &SkimMatcherV2,
+    launcher: &'a CustomLauncher,
+    query: &str,
+    weights: &ScoringWeights,
+    min_score: i64,
+) -> Option<MatchOutcome<'a>> {
+    let mut best = BestMatch::default();
+    best.offer(matcher.fuzzy_match(&launcher.name, query), 0, None);
+
+    for keyword in &launcher.keywords {
+        if keyword.trim().is_empty() {
+            continue;
         }
+        best.offer(
+            matcher.fuzzy_match(keyword, query),
+            weights.keyword_penalty,
+            Some(keyword.as_str()),
+        );
     }
 
-    if let Some(score) = matcher
-        .fuzzy_match(&bookmark.url, query)
-        .map(|value| value - 8)
+    // 参数与工作目录按关键词对待，"incognito" 也能找到带 --incognito 的启动项
+    for detail in [&launcher.arguments, &launcher.working_directory]
+        .into_iter()
+        .flatten()
     {
-        if best.is_none_or(|current| score > current) {
-            best = Some(score);
+        let detail = detail.trim();
+        if detail.is_empty() {
+            continue;
         }
+        best.offer(
+            matcher.fuzzy_match(detail, query),
+            weights.keyword_penalty,
+            Some(detail),
+        );
+    }
+
+    best.finish(
+        literal_match_boost(&launcher.name, query, weights),
+        min_score,
+    )
+}
+
+/// Scores a bookmark against its title, folder path, URL and keywords.
+fn match_bookmark<'a>(
+    matcher: &SkimMatcherV2,
+    bookmark: &'a BookmarkEntry,
+    query: &str,
+    weights: &ScoringWeights,
+    min_score: i64,
+    recent: &[String],
+    overrides: &HashMap<String, i64>,
+) -> Option<MatchOutcome<'a>> {
+    let mut best = BestMatch::default();
+    best.offer(matcher.fuzzy_match(&bookmark.title, query), 0, None);
+
+    // 逐级匹配文件夹名，并同时匹配去除变音符号的版本，"resume" 也能命中 "Résumé"
+    let folded_query = fold_diacritics(query);
+    for (segment, folded) in bookmark
+        .folder_segments
+        .iter()
+        .zip(&bookmark.folded_folder_segments)
+    {
+        best.offer(
+            matcher
+                .fuzzy_match(segment, query)
+                .max(matcher.fuzzy_match(folded, &folded_query)),
+            weights.bookmark_folder_penalty,
+            Some(segment.as_str()),
+        );
     }
 
+    best.offer(
+        matcher.fuzzy_match(&bookmark.url, query),
+        weights.bookmark_url_penalty,
+        Some(bookmark.url.as_str()),
+    );
+
     for keyword in &bookmark.keywords {
         if keyword.is_empty() {
             continue;
         }
+        best.offer(
+            matcher.fuzzy_match(keyword, query),
+            weights.bookmark_keyword_penalty,
+            Some(keyword.as_str()),
+        );
+    }
 
-        if let Some(score) = matcher.fuzzy_match(keyword, query) {
-            let score = score - 8;
-            if best.is_none_or(|current| score > current) {
-                best = Some(score);
-            }
+    best.finish(
+        literal_match_boost(&bookmark.title, query, weights),
+        min_score,
+    )
+    .map(|outcome| {
+        outcome
+            .with_session_boost(recent_launch_boost(recent, &bookmark.id, weights))
+            .with_score_override(score_override(overrides, "bookmark", &bookmark.id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::Actionable;
+
+    fn app(name: &str) -> ApplicationInfo {
+        ApplicationInfo {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            path: format!(r"C:\Apps\{name}.exe"),
+            source_path: None,
+            app_type: AppType::Win32,
+            icon_b64: String::new(),
+            icon_source: None,
+            icon_index: 0,
+            description: None,
+            keywords: Vec::new(),
+            working_directory: None,
+            arguments: None,
+            installed_at: None,
+            uninstall_string: None,
+        }
+    }
+
+    fn state_with_apps(apps: Vec<ApplicationInfo>) -> AppState {
+        let state = AppState::new();
+        state.index_ready.store(true, Ordering::SeqCst);
+        *state.app_index.lock().unwrap() = apps;
+        state
+    }
+
+    fn query(state: &AppState, text: &str, mode: QueryMode) -> Vec<SearchResult> {
+        let context = QueryContext::new(state, text, mode, Arc::new(AtomicBool::new(false)));
+        run_query(&context, &mut QueryDiagnostics::default()).0
+    }
+
+    fn bookmark(title: &str) -> BookmarkEntry {
+        BookmarkEntry {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            url: format!("https://{}.example.com/", title.to_lowercase()),
+            folder_segments: Vec::new(),
+            folded_folder_segments: Vec::new(),
+            keywords: Vec::new(),
+            node_id: None,
+            folder_node_id: None,
+            manager_url: "chrome://bookmarks/",
+        }
+    }
+
+    fn app_score(app: &ApplicationInfo, query: &str) -> Option<i64> {
+        match_application(
+            &SkimMatcherV2::default(),
+            app,
+            query,
+            &ScoringWeights::default(),
+            0,
+            &[],
+            &HashMap::new(),
+        )
+        .map(|outcome| outcome.score)
+    }
+
+    #[test]
+    fn literal_match_boost_prefers_prefix_over_substring() {
+        let weights = ScoringWeights::default();
+        assert_eq!(
+            literal_match_boost("Chrome", "CHR", &weights),
+            weights.prefix_boost
+        );
+        assert_eq!(
+            literal_match_boost("Google Chrome", "chrome", &weights),
+            weights.substring_boost
+        );
+        assert_eq!(literal_match_boost("Character Map", "chr", &weights), 0);
+    }
+
+    #[test]
+    fn prefix_match_outranks_scattered_match() {
+        let chrome = app_score(&app("Chrome"), "chr").expect("Chrome should match");
+        let character_map =
+            app_score(&app("Character Map"), "chr").expect("Character Map should match");
+        assert!(chrome > character_map);
+    }
+
+    #[test]
+    fn describe_shell_execute_error_maps_known_codes() {
+        assert_eq!(describe_shell_execute_error(2), "找不到文件");
+        assert_eq!(describe_shell_execute_error(31), "没有关联的程序");
+        assert_eq!(describe_shell_execute_error(17), "未知错误");
+    }
+
+    #[test]
+    fn oversized_query_skips_matching() {
+        let state = state_with_apps(
+            (0..2000)
+                .map(|index| app(&format!("App {index}")))
+                .collect(),
+        );
+        let text = "a".repeat(10_000);
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let context = QueryContext::new(&state, &text, QueryMode::All, cancel);
+        let mut diagnostics = QueryDiagnostics::default();
+        let (results, _) = run_query(&context, &mut diagnostics);
+        assert!(diagnostics.matching_skipped);
+        assert_eq!(diagnostics.apps.matched, 0);
+        assert!(results.iter().all(|result| result.action_id == "search"));
+    }
+
+    #[test]
+    fn publisher_query_matches_description_below_name_matches() {
+        let mut idea = app("IntelliJ IDEA");
+        idea.description = Some("JetBrains s.r.o.".into());
+        let toolbox = app("JetBrains Toolbox");
+        let weights = ScoringWeights::default();
+        let matcher = SkimMatcherV2::default();
+
+        let outcome = match_application(
+            &matcher,
+            &idea,
+            "jetbrains",
+            &weights,
+            0,
+            &[],
+            &HashMap::new(),
+        )
+        .expect("publisher should match");
+        assert_eq!(outcome.matched, Some("JetBrains s.r.o."));
+        assert!(app_score(&toolbox, "jetbrains").unwrap() > outcome.score);
+    }
+
+    #[test]
+    fn pruning_keeps_usage_of_system_mode_apps() {
+        let state = AppState::new();
+        *state.system_uwp_index.lock().unwrap() = vec![app("Xbox Game Bar")];
+        let mut stats = UsageStats::default();
+        stats.record("xbox game bar");
+        stats.record("uninstalled");
+
+        let indexed = indexed_ids(&state).unwrap();
+        let valid_ids: HashSet<&str> = indexed.iter().map(String::as_str).collect();
+        assert_eq!(stats.prune(&valid_ids), 1);
+        assert_eq!(stats.launch_count("xbox game bar"), 1);
+    }
+
+    #[test]
+    fn pruning_ages_out_unused_urls() {
+        let mut stats = UsageStats::default();
+        let url = format!("{URL_USAGE_PREFIX}https://example.com/");
+        stats.record(&url);
+        let recorded_at = stats.get(&url).unwrap().last_used;
+
+        let day = 24 * 60 * 60;
+        assert_eq!(stats.prune_at(&HashSet::new(), recorded_at + 30 * day), 0);
+        assert_eq!(stats.launch_count(&url), 1);
+        assert_eq!(stats.prune_at(&HashSet::new(), recorded_at + 120 * day), 1);
+        assert_eq!(stats.launch_count(&url), 0);
+    }
+
+    #[test]
+    fn bookmark_limit_keeps_most_launched_entries() {
+        let mut bookmarks = vec![bookmark("Alpha"), bookmark("Beta"), bookmark("Gamma")];
+        let mut stats = UsageStats::default();
+        stats.record("gamma");
+        let usage_stats = Mutex::new(stats);
+
+        apply_bookmark_index_limit(&mut bookmarks, &usage_stats, 2);
+        let titles: Vec<&str> = bookmarks.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["Gamma", "Alpha"]);
+    }
+
+    #[test]
+    fn search_template_keeps_its_own_params_and_fragment() {
+        assert_eq!(
+            build_search_url("https://x/?a=1&q={query}#frag", "a&b c#d"),
+            "https://x/?a=1&q=a%26b%20c%23d#frag"
+        );
+    }
+
+    #[test]
+    fn cancelled_query_stops_scanning() {
+        let state = state_with_apps(
+            (0..1000)
+                .map(|index| app(&format!("App {index}")))
+                .collect(),
+        );
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let context = QueryContext::new(&state, "app", QueryMode::Application, cancel_flag.clone());
+        let (results, _) = run_query(&context, &mut QueryDiagnostics::default());
+        assert!(!results.is_empty());
+
+        // 扫描循环在检查点读取取消标志，已取代的查询不返回任何结果
+        cancel_flag.store(true, Ordering::Relaxed);
+        let (results, actions) = run_query(&context, &mut QueryDiagnostics::default());
+        assert!(results.is_empty() && actions.is_empty());
+    }
+
+    #[test]
+    fn explicit_url_query_has_no_trailing_web_search() {
+        let state = state_with_apps(Vec::new());
+        let results = query(&state, "https://example.com", QueryMode::All);
+        assert_eq!(
+            results.first().map(|result| result.action_id.as_str()),
+            Some("url")
+        );
+        assert!(results.iter().all(|result| result.action_id != "search"));
+
+        let results = query(&state, "example.com", QueryMode::All);
+        assert_eq!(
+            results.last().map(|result| result.action_id.as_str()),
+            Some("search")
+        );
+    }
+
+    #[test]
+    fn search_url_encodes_the_query() {
+        assert_eq!(
+            build_search_url(GOOGLE_SEARCH_TEMPLATE, "rust & c++"),
+            "https://google.com/search?q=rust%20%26%20c%2B%2B"
+        );
+        // 缺少占位符的模板把查询追加在末尾
+        assert_eq!(
+            build_search_url("https://x/search?q=", "a b"),
+            "https://x/search?q=a%20b"
+        );
+    }
+
+    fn tracking_denylist() -> Vec<String> {
+        vec!["utm_*".to_string(), "fbclid".to_string()]
+    }
+
+    #[test]
+    fn clean_url_strips_tracking_params_and_keeps_the_rest() {
+        let denylist = tracking_denylist();
+        assert_eq!(
+            clean_url(
+                "https://a.com/p?utm_source=x&id=5&FBCLID=y&utm_medium=z&q=rust#section",
+                &denylist
+            ),
+            "https://a.com/p?id=5&q=rust#section"
+        );
+        assert_eq!(
+            clean_url("https://a.com/p?utm_source=x#top", &denylist),
+            "https://a.com/p#top"
+        );
+    }
+
+    #[test]
+    fn clean_url_is_idempotent() {
+        let denylist = tracking_denylist();
+        let once = clean_url("https://a.com/?gclid=1&utm_campaign=2&page=3", &denylist);
+        assert_eq!(once, "https://a.com/?gclid=1&page=3");
+        assert_eq!(clean_url(&once, &denylist), once);
+    }
+
+    #[test]
+    fn root_url_keeps_ip_hosts_and_ports() {
+        assert_eq!(root_url("https://docs.rs/foo/latest"), "https://docs.rs");
+        assert_eq!(
+            root_url("http://192.168.1.1:8080/admin?x=1"),
+            "http://192.168.1.1:8080"
+        );
+        assert_eq!(root_url("http://[::1]:3000/path"), "http://[::1]:3000");
+        assert_eq!(root_url("not a url"), "not a url");
+    }
+
+    #[test]
+    fn score_breakdown_adds_up_to_the_score() {
+        let mut updater = app("Chrome Updater");
+        updater.keywords = vec!["browser".to_string()];
+        let mut docs = bookmark("Chrome Docs");
+        docs.folder_segments = vec!["Dev".to_string()];
+        docs.folded_folder_segments = vec!["dev".to_string()];
+        let recent = vec![updater.id.clone(), docs.id.clone()];
+        let overrides = HashMap::from([
+            (format!("app-{}", updater.id), 30),
+            (format!("bookmark-{}", docs.id), -20),
+        ]);
+        let matcher = SkimMatcherV2::default();
+        let weights = ScoringWeights::default();
+
+        for query in ["chrome", "browser"] {
+            let outcome =
+                match_application(&matcher, &updater, query, &weights, 0, &recent, &overrides)
+                    .expect("updater should match");
+            let breakdown = &outcome.breakdown;
+            assert!(breakdown.session_boost > 0 && breakdown.deprioritize_penalty > 0);
+            assert_eq!(breakdown.score_override, 30);
+            assert_eq!(breakdown.total(), outcome.score);
+        }
+        for query in ["chrome", "dev"] {
+            let outcome = match_bookmark(&matcher, &docs, query, &weights, 0, &recent, &overrides)
+                .expect("bookmark should match");
+            assert_eq!(outcome.breakdown.score_override, -20);
+            assert_eq!(outcome.breakdown.total(), outcome.score);
+        }
+    }
+
+    #[test]
+    fn min_score_drops_weak_scattered_matches_but_keeps_literal_ones() {
+        let matcher = SkimMatcherV2::default();
+        let weights = ScoringWeights::default();
+        let min_score = 60;
+        let scattered = app("Character Map");
+        let fuzzy = matcher.fuzzy_match(&scattered.name, "cmp").unwrap();
+        assert!(fuzzy < min_score);
+        let outcome = match_application(
+            &matcher,
+            &scattered,
+            "cmp",
+            &weights,
+            min_score,
+            &[],
+            &HashMap::new(),
+        );
+        assert!(outcome.is_none());
+
+        // 名称以查询开头时即使模糊分数低于下限也保留
+        let chrome = app("Chrome");
+        let outcome = match_application(
+            &matcher,
+            &chrome,
+            "ch",
+            &weights,
+            i64::MAX,
+            &[],
+            &HashMap::new(),
+        );
+        assert!(outcome.is_some());
+    }
+
+    #[test]
+    fn duplicate_titles_get_distinguishing_suffixes() {
+        let mut seven_zip = app("Setup");
+        seven_zip.id = "7zip-setup".to_string();
+        seven_zip.description = Some("7-Zip".to_string());
+        let mut winrar = app("Setup");
+        winrar.id = "winrar-setup".to_string();
+        winrar.description = Some("WinRAR".to_string());
+        let state = state_with_apps(vec![seven_zip, winrar, app("Setup Wizard")]);
+
+        let results = query(&state, "setup", QueryMode::Application);
+        let mut titles: Vec<&str> = results.iter().map(|result| result.title.as_str()).collect();
+        titles.sort_unstable();
+        assert_eq!(titles, ["Setup (7-Zip)", "Setup (WinRAR)", "Setup Wizard"]);
+    }
+
+    #[test]
+    fn bookmark_manager_urls_follow_the_source_browser() {
+        let mut entry = bookmark("Docs");
+        entry.node_id = Some("12".to_string());
+        entry.folder_node_id = Some("3".to_string());
+        assert_eq!(bookmark_manager_url(&entry), "chrome://bookmarks/?id=12");
+
+        entry.manager_url = "opera://bookmarks/";
+        assert_eq!(bookmark_manager_url(&entry), "opera://bookmarks/?id=12");
+        assert_eq!(bookmark_folder_url(&entry), "opera://bookmarks/?id=3");
+    }
+
+    fn result(id: &str, title: &str, score: i64) -> SearchResult {
+        SearchResult {
+            id: id.to_string(),
+            title: title.to_string(),
+            subtitle: String::new(),
+            icon: String::new(),
+            score,
+            action_id: "app".to_string(),
+            group: result_group("app").to_string(),
+            score_breakdown: None,
+            launch_count: None,
+        }
+    }
+
+    #[test]
+    fn equal_scores_keep_a_fixed_order() {
+        let expected = ["app-b", "app-a", "app-c", "app-d"];
+        let mut forward = vec![
+            result("app-d", "Zed", 50),
+            result("app-c", "notepad", 80),
+            result("app-a", "Notepad", 80),
+            result("app-b", "Calculator", 80),
+        ];
+        let mut backward: Vec<SearchResult> = forward.iter().rev().cloned().collect();
+
+        sort_results(&mut forward, "en");
+        sort_results(&mut backward, "en");
+        let ids = |results: &[SearchResult]| -> Vec<String> {
+            results.iter().map(|result| result.id.clone()).collect()
+        };
+        assert_eq!(ids(&forward), expected);
+        assert_eq!(ids(&backward), expected);
+    }
+
+    #[test]
+    fn score_override_flips_the_order() {
+        let state = state_with_apps(vec![app("Note"), app("Notepad")]);
+        let titles = |state: &AppState| -> Vec<String> {
+            query(state, "note", QueryMode::Application)
+                .into_iter()
+                .map(|result| result.title)
+                .collect()
+        };
+        assert_eq!(titles(&state), ["Note", "Notepad"]);
+
+        let mut config = (*state.config_snapshot()).clone();
+        config
+            .score_overrides
+            .insert("app-notepad".to_string(), 1000);
+        state.replace_config(config);
+        assert_eq!(titles(&state), ["Notepad", "Note"]);
+    }
+
+    #[test]
+    fn launches_in_all_and_application_mode_share_a_counter() {
+        let state = state_with_apps(vec![app("Notepad")]);
+        let key = |mode: QueryMode| {
+            let cancel = Arc::new(AtomicBool::new(false));
+            let context = QueryContext::new(&state, "notepad", mode, cancel);
+            let (results, pending) = run_query(&context, &mut QueryDiagnostics::default());
+            let result = results
+                .iter()
+                .find(|result| result.title == "Notepad")
+                .unwrap();
+            pending[&result.id].usage_key()
+        };
+
+        let mut stats = UsageStats::default();
+        for mode in [QueryMode::All, QueryMode::Application] {
+            stats.record(&key(mode).expect("apps have a usage key"));
         }
+        assert_eq!(stats.launch_count("notepad"), 2);
+    }
+
+    #[test]
+    fn arguments_only_query_finds_custom_launcher() {
+        let state = state_with_apps(vec![app("Chrome")]);
+        let mut config = (*state.config_snapshot()).clone();
+        config.custom_launchers.push(CustomLauncher {
+            id: "private".to_string(),
+            name: "Private Browsing".to_string(),
+            keywords: Vec::new(),
+            target: r"C:\Apps\Chrome.exe".to_string(),
+            arguments: Some("--incognito".to_string()),
+            working_directory: None,
+            icon_path: None,
+        });
+        state.replace_config(config);
+
+        let results = query(&state, "incognito", QueryMode::All);
+        assert_eq!(results[0].id, "custom-private");
+        assert_eq!(results[0].title, "Private Browsing");
+    }
+
+    #[test]
+    fn aumid_package_segment_is_searchable() {
+        let mut calculator = app("Calculator");
+        calculator.app_type = AppType::Uwp;
+        calculator.keywords = vec![
+            "Microsoft.WindowsCalculator".to_string(),
+            "WindowsCalculator".to_string(),
+        ];
+        let state = state_with_apps(vec![calculator, app("Windows Terminal")]);
+
+        let results = query(&state, "windowscalc", QueryMode::Application);
+        assert_eq!(results[0].title, "Calculator");
+    }
+
+    #[test]
+    fn penalized_results_stay_above_web_search() {
+        let state = state_with_apps(vec![app("Foo Updater")]);
+        let mut config = (*state.config_snapshot()).clone();
+        config.scoring_weights.deprioritize_penalty = 100_000;
+        state.replace_config(config);
+
+        let results = query(&state, "foo updater", QueryMode::All);
+        let actions: Vec<&str> = results
+            .iter()
+            .map(|result| result.action_id.as_str())
+            .collect();
+        assert_eq!(actions, ["app", "search"]);
+        assert!(results[0].score < 0);
+
+        // 重新排序时网页搜索即使分数更高也排在最后
+        let mut search = result("search-foo", "Search Google", 0);
+        search.action_id = "search".to_string();
+        let mut resorted = vec![search, result("app-foo", "Foo Updater", -100)];
+        sort_results(&mut resorted, "en");
+        assert_eq!(resorted[0].id, "app-foo");
+    }
+
+    #[test]
+    fn updater_ranks_below_the_real_app() {
+        let state = state_with_apps(vec![app("Zoom Workplace"), app("Zoom Updater")]);
+        let titles = |state: &AppState| -> Vec<String> {
+            query(state, "zoom", QueryMode::Application)
+                .into_iter()
+                .map(|result| result.title)
+                .collect()
+        };
+        assert_eq!(titles(&state), ["Zoom Workplace", "Zoom Updater"]);
+
+        // 不扣分时更新程序反而排在前面
+        let mut config = (*state.config_snapshot()).clone();
+        config.scoring_weights.deprioritize_penalty = 0;
+        state.replace_config(config);
+        assert_eq!(titles(&state), ["Zoom Updater", "Zoom Workplace"]);
+    }
+
+    #[test]
+    fn title_suffix_candidates_go_from_concise_to_full() {
+        let mut foo = app("Foo");
+        foo.path = "C:/Program Files/Foo/foo.exe".to_string();
+        foo.description = Some("Foo Corp".to_string());
+        assert_eq!(
+            LaunchApp(foo).title_suffix_candidates(),
+            ["Foo Corp", "Foo", "C:/Program Files/Foo"]
+        );
+
+        let mut docs = bookmark("Docs");
+        docs.folder_segments = vec!["Work".to_string(), "Reference".to_string()];
+        assert_eq!(
+            OpenBookmark(docs).title_suffix_candidates(),
+            ["Reference", "https://docs.example.com/"]
+        );
+        assert!(OpenPath("C:/Foo".to_string())
+            .title_suffix_candidates()
+            .is_empty());
+    }
+
+    #[test]
+    fn equal_scores_sort_by_locale_collation() {
+        let titles = |locale: &str| -> Vec<String> {
+            let mut results = vec![
+                result("app-zoom", "Zoom", 80),
+                result("app-apple", "苹果", 80),
+                result("app-eclair", "Éclair", 80),
+                result("app-edge", "Edge", 80),
+            ];
+            sort_results(&mut results, locale);
+            results.into_iter().map(|result| result.title).collect()
+        };
+
+        // 重音字母按去掉重音后的字母排序，而不是排在 Z 之后
+        assert_eq!(titles("en"), ["Éclair", "Edge", "Zoom", "苹果"]);
+        // 中文环境下汉字按拼音排在拉丁字母标题之间
+        assert_eq!(titles("zh-CN"), ["Éclair", "Edge", "苹果", "Zoom"]);
+    }
+
+    #[test]
+    fn profile_search_engine_is_used_for_web_search() {
+        let state = state_with_apps(Vec::new());
+        let search = |state: &AppState| {
+            query(state, "rust", QueryMode::All)
+                .into_iter()
+                .find(|result| result.action_id == "search")
+                .unwrap()
+        };
+        assert_eq!(search(&state).title, "在 Google 上搜索: rust");
+
+        let mut config = (*state.config_snapshot()).clone();
+        config.profiles.insert(
+            "work".to_string(),
+            ProfileOverrides {
+                search_url_template: Some("https://www.bing.com/search?q={query}".to_string()),
+                ..Default::default()
+            },
+        );
+        config.active_profile = "work".to_string();
+        state.replace_config(config);
+
+        let result = search(&state);
+        assert_eq!(result.title, "在网络上搜索: rust");
+        assert_eq!(
+            result.id,
+            content_result_id("search", "https://www.bing.com/search?q=rust")
+        );
+    }
+
+    #[test]
+    fn search_template_must_expand_to_a_web_url() {
+        assert_eq!(
+            normalize_search_template("  ").unwrap(),
+            GOOGLE_SEARCH_TEMPLATE
+        );
+        assert!(normalize_search_template("https://duckduckgo.com/?q={query}").is_ok());
+        assert!(normalize_search_template("javascript:alert({query})").is_err());
+        assert!(normalize_search_template("not a url").is_err());
     }
 
-    best
+    #[test]
+    fn markdown_link_escapes_brackets_in_the_title() {
+        assert_eq!(
+            markdown_link(Some("[RFC] a\\b"), "https://example.com"),
+            "[\\[RFC\\] a\\\\b](https://example.com)"
+        );
+    }
+
+    #[test]
+    fn markdown_link_encodes_spaces_and_parentheses_in_the_url() {
+        assert_eq!(
+            markdown_link(
+                Some("Rust (language)"),
+                "https://en.wikipedia.org/wiki/Rust (language)"
+            ),
+            "[Rust (language)](https://en.wikipedia.org/wiki/Rust%20%28language%29)"
+        );
+    }
+
+    #[test]
+    fn markdown_link_falls_back_to_the_host_without_a_title() {
+        assert_eq!(
+            markdown_link(Some("  "), "example.com/a b"),
+            "[example.com](https://example.com/a%20b)"
+        );
+    }
+
+    #[test]
+    fn accented_folder_matches_plain_query() {
+        let mut cv = bookmark("Portfolio");
+        cv.folder_segments = vec!["Résumé".to_string()];
+        cv.folded_folder_segments = vec![fold_diacritics("Résumé")];
+
+        let outcome = match_bookmark(
+            &SkimMatcherV2::default(),
+            &cv,
+            "resume",
+            &ScoringWeights::default(),
+            0,
+            &[],
+            &HashMap::new(),
+        )
+        .expect("folded folder name should match");
+        assert_eq!(outcome.matched, Some("Résumé"));
+    }
 }
@@ -1,10 +1,22 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
+use crate::text_utils::quote_path;
+
 const CONFIG_FILE: &str = "settings.json";
 
+/// Default `search_url_template`.
+pub const GOOGLE_SEARCH_TEMPLATE: &str = "https://google.com/search?q={query}";
+
+/// Script extensions that may be listed in `index_script_extensions`.
+pub const SCRIPT_EXTENSIONS: &[&str] = &["bat", "cmd", "ps1"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub global_hotkey: String,
@@ -31,6 +43,344 @@ pub struct AppConfig {
     pub debug_mode: bool,
     #[serde(default = "default_system_tool_exclusions")]
     pub system_tool_exclusions: Vec<String>,
+    #[serde(default)]
+    pub scoring_weights: ScoringWeights,
+    #[serde(default = "default_confirm_admin_launch")]
+    pub confirm_admin_launch: bool,
+    #[serde(default = "default_skip_dead_shortcuts")]
+    pub skip_dead_shortcuts: bool,
+    #[serde(default = "default_custom_launchers")]
+    pub custom_launchers: Vec<CustomLauncher>,
+    #[serde(default = "default_max_match_query_chars")]
+    pub max_match_query_chars: usize,
+    /// Browser executable used for private-window opens; empty means the system default.
+    #[serde(default = "default_bookmark_browser")]
+    pub bookmark_browser: String,
+    #[serde(default = "default_open_urls_in_background")]
+    pub open_urls_in_background: bool,
+    #[serde(default = "default_max_indexed_apps")]
+    pub max_indexed_apps: usize,
+    #[serde(default = "default_max_indexed_bookmarks")]
+    pub max_indexed_bookmarks: usize,
+    #[serde(default = "default_max_inline_icons")]
+    pub max_inline_icons: usize,
+    /// Application ids hidden from the index by the user.
+    #[serde(default = "default_hidden_ids")]
+    pub hidden_ids: Vec<String>,
+    /// Match case-sensitively only when the query contains uppercase letters.
+    #[serde(default = "default_smart_case")]
+    pub smart_case: bool,
+    /// Allows the run (`>`) mode to execute shell commands. Off by default since it runs arbitrary input.
+    #[serde(default = "default_enable_run_mode")]
+    pub enable_run_mode: bool,
+    #[serde(default = "default_run_shell")]
+    pub run_shell: RunShell,
+    #[serde(default = "default_launch_order")]
+    pub launch_order: LaunchOrder,
+    /// Pause between hiding the launcher and launching when `launch_order` hides first.
+    #[serde(default = "default_launch_focus_delay_ms")]
+    pub launch_focus_delay_ms: u64,
+    #[serde(default = "default_strip_tracking_params")]
+    pub strip_tracking_params: bool,
+    /// Query parameter names removed when `strip_tracking_params` is on; a trailing `*` matches a prefix.
+    #[serde(default = "default_tracking_param_denylist")]
+    pub tracking_param_denylist: Vec<String>,
+    /// Appends the keyword or field that matched to result subtitles when it isn't the title.
+    #[serde(default = "default_show_match_source")]
+    pub show_match_source: bool,
+    /// Separator used when displaying bookmark folder paths.
+    #[serde(default = "default_folder_separator")]
+    pub folder_separator: String,
+    /// Extra folders scanned for portable executables, including a few levels of subfolders.
+    #[serde(default = "default_extra_app_folders")]
+    pub extra_app_folders: Vec<String>,
+    /// Script extensions (`bat`, `cmd`, `ps1`) also indexed from the extra folders. Empty disables
+    /// script indexing, since launching arbitrary scripts is a power-user feature.
+    #[serde(default = "default_index_script_extensions")]
+    pub index_script_extensions: Vec<String>,
+    /// Fuzzy scores below this floor are dropped unless the name literally contains the query.
+    #[serde(default = "default_min_match_score")]
+    pub min_match_score: i64,
+    /// Terminal opened by the "open in terminal" action on folder results.
+    #[serde(default = "default_terminal")]
+    pub terminal: Terminal,
+    /// Index bookmarks from Opera.
+    #[serde(default = "default_enable_opera_bookmarks")]
+    pub enable_opera_bookmarks: bool,
+    /// Index bookmarks from Opera GX.
+    #[serde(default = "default_enable_opera_gx_bookmarks")]
+    pub enable_opera_gx_bookmarks: bool,
+    /// Route admin launches through a single elevated broker process so UAC only prompts once.
+    #[serde(default = "default_use_elevation_broker")]
+    pub use_elevation_broker: bool,
+    /// Chrome profile directory names to read bookmarks from; `None` reads every profile.
+    #[serde(default = "default_chrome_profile_allowlist")]
+    pub chrome_profile_allowlist: Option<Vec<String>>,
+    /// Largest base64 icon kept after indexing; bigger icons are downscaled. `0` disables the cap.
+    #[serde(default = "default_max_icon_bytes")]
+    pub max_icon_bytes: usize,
+    /// Air-gapped mode: suppresses the web-search fallback and "open URL" results (URL-like queries
+    /// only open matching local paths) and refuses to open URLs. Bookmark results are kept.
+    #[serde(default = "default_offline_mode")]
+    pub offline_mode: bool,
+    /// Extra Chromium `Bookmarks` files, e.g. from portable browser installs.
+    #[serde(default = "default_custom_bookmark_files")]
+    pub custom_bookmark_files: Vec<CustomBookmarkFile>,
+    /// Key combo (e.g. `Tab` or `Ctrl+M`) that cycles query modes in the open window; empty
+    /// disables it.
+    #[serde(default = "default_mode_cycle_hotkey")]
+    pub mode_cycle_hotkey: String,
+    /// After launching a Win32 app, move its main window to the monitor the launcher was shown on.
+    #[serde(default = "default_launch_on_active_monitor")]
+    pub launch_on_active_monitor: bool,
+    /// Additive score per result id (`app-<id>`, `bookmark-<id>`), applied after fuzzy scoring.
+    #[serde(default = "default_score_overrides")]
+    pub score_overrides: HashMap<String, i64>,
+    /// Glob patterns (`*`, `?`) for subfolders skipped while scanning `extra_app_folders`,
+    /// matched case-insensitively against the folder name or its full path.
+    #[serde(default = "default_extra_app_dir_excludes")]
+    pub extra_app_dir_excludes: Vec<String>,
+    /// Global hotkey that runs the top result of the last query without showing the window. Empty disables it.
+    #[serde(default = "default_quick_open_hotkey")]
+    pub quick_open_hotkey: String,
+    /// Writes panics with a backtrace to a local crash log. Also enabled by `debug_mode`.
+    #[serde(default = "default_capture_crashes")]
+    pub capture_crashes: bool,
+    /// Adds romanized Japanese kana and Korean Hangul app names as keywords. Applies at the next reindex.
+    #[serde(default = "default_enable_cjk_transliteration")]
+    pub enable_cjk_transliteration: bool,
+    /// Hide the launcher after executing a result; when false it stays open with a cleared input.
+    #[serde(default = "default_hide_after_launch")]
+    pub hide_after_launch: bool,
+    /// Deepest Start Menu subfolder level scanned for shortcuts; each root is level 0.
+    #[serde(default = "default_start_menu_max_depth")]
+    pub start_menu_max_depth: usize,
+    /// Appends how long ago an app or bookmark was last launched to its subtitle.
+    #[serde(default = "default_show_last_used")]
+    pub show_last_used: bool,
+    /// Adds apps listed in `shell:AppsFolder` that no other index source found.
+    #[serde(default = "default_index_apps_folder")]
+    pub index_apps_folder: bool,
+    /// Asks the frontend to confirm before opening URL, web-search and bookmark results.
+    #[serde(default = "default_confirm_url_open")]
+    pub confirm_url_open: bool,
+    /// BCP 47 tag controlling how equal-score results are ordered by title; `zh` locales sort CJK
+    /// titles by pinyin.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Name of the entry in `profiles` merged over this config; empty uses the base config only.
+    #[serde(default = "default_active_profile")]
+    pub active_profile: String,
+    /// Named sets of overrides for machine-specific settings, e.g. `laptop` and `desktop`.
+    #[serde(default = "default_profiles")]
+    pub profiles: HashMap<String, ProfileOverrides>,
+    /// When set, only apps whose target lies under one of these folders are indexed. Apps without
+    /// a file path, such as UWP apps, are dropped too. `None` indexes everything.
+    #[serde(default = "default_app_dir_allowlist")]
+    pub app_dir_allowlist: Option<Vec<String>>,
+    /// Adds an info result telling how many matches were cut off by `max_results`.
+    #[serde(default = "default_show_truncation_hint")]
+    pub show_truncation_hint: bool,
+    /// Allows pasting copy results straight into the window that was focused before the launcher
+    /// opened, by simulating Ctrl+V.
+    #[serde(default = "default_enable_paste_action")]
+    pub enable_paste_action: bool,
+    /// Web-search URL with `{query}` standing for the encoded query.
+    #[serde(default = "default_search_url_template")]
+    pub search_url_template: String,
+}
+
+/// Tunable constants used when ranking fuzzy matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringWeights {
+    /// Penalty applied to application keyword matches so the primary name wins ties.
+    pub keyword_penalty: i64,
+    /// Penalty applied when an application matches through its description or publisher.
+    pub description_penalty: i64,
+    /// Penalty applied when a bookmark matches through its folder path.
+    pub bookmark_folder_penalty: i64,
+    /// Penalty applied when a bookmark matches through its URL.
+    pub bookmark_url_penalty: i64,
+    /// Penalty applied when a bookmark matches through a generated keyword.
+    pub bookmark_keyword_penalty: i64,
+    /// Bonus added when the name starts with the query (case-insensitive).
+    pub prefix_boost: i64,
+    /// Bonus added when the name contains the query as a contiguous substring.
+    pub substring_boost: i64,
+    /// Bonus for the entry launched most recently in this session; earlier launches in the
+    /// session list get proportionally smaller bonuses.
+    pub recent_launch_boost: i64,
+    /// Case-insensitive substrings of an app's name or path that mark it as an uninstaller,
+    /// updater or similar helper that slipped past the indexer's filters.
+    pub deprioritize_patterns: Vec<String>,
+    /// Penalty applied to apps matching `deprioritize_patterns`; they stay searchable but rank
+    /// below the real app.
+    pub deprioritize_penalty: i64,
+}
+
+/// Terminal used by the "open in terminal here" folder action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Terminal {
+    /// Windows Terminal (`wt.exe`); falls back to `Cmd` when it isn't installed.
+    WindowsTerminal,
+    Cmd,
+}
+
+impl Terminal {
+    /// Returns the program and argument string that open this terminal in `directory`.
+    pub fn command_line(self, directory: &str) -> (&'static str, String) {
+        let directory = quote_path(directory);
+        match self {
+            Self::WindowsTerminal => ("wt.exe", format!("-d {directory}")),
+            Self::Cmd => ("cmd.exe", format!("/k cd /d {directory}")),
+        }
+    }
+}
+
+/// Whether `execute_action` launches the target before or after hiding the launcher window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchOrder {
+    LaunchThenHide,
+    /// Hides first so the launched app isn't opened behind the disappearing launcher.
+    HideThenLaunch,
+}
+
+/// Shell used to execute commands typed in run mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunShell {
+    Cmd,
+    PowerShell,
+}
+
+impl RunShell {
+    /// Returns the program and argument string that run `script` in this shell.
+    pub fn command_line(self, script: &str) -> (&'static str, String) {
+        match self {
+            Self::Cmd => ("cmd.exe", format!("/c {script}")),
+            Self::PowerShell => ("powershell.exe", format!("-NoProfile -Command {script}")),
+        }
+    }
+}
+
+/// A user-defined launcher for targets the indexer can't discover on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomLauncher {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Executable path, URI (e.g. `steam://...`) or web URL.
+    pub target: String,
+    #[serde(default)]
+    pub arguments: Option<String>,
+    #[serde(default)]
+    pub working_directory: Option<String>,
+    #[serde(default)]
+    pub icon_path: Option<String>,
+}
+
+/// Settings a named profile can override; `None` keeps the base config's value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileOverrides {
+    pub system_tool_exclusions: Option<Vec<String>>,
+    pub extra_app_folders: Option<Vec<String>>,
+    pub extra_app_dir_excludes: Option<Vec<String>>,
+    pub search_url_template: Option<String>,
+}
+
+/// A Chromium `Bookmarks` JSON file outside the auto-discovered profile directories.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomBookmarkFile {
+    /// Label shown in place of the profile name; falls back to the parent folder name.
+    #[serde(default)]
+    pub name: String,
+    pub path: String,
+}
+
+impl CustomBookmarkFile {
+    pub fn label(&self) -> String {
+        let name = self.name.trim();
+        if !name.is_empty() {
+            return name.to_string();
+        }
+        Path::new(&self.path)
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "自定义书签".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomTargetKind {
+    File,
+    Uri,
+    Url,
+}
+
+impl CustomLauncher {
+    pub fn target_kind(&self) -> CustomTargetKind {
+        let target = self.target.trim().to_ascii_lowercase();
+        if target.starts_with("http://") || target.starts_with("https://") {
+            CustomTargetKind::Url
+        } else if has_uri_scheme(&target) {
+            CustomTargetKind::Uri
+        } else {
+            CustomTargetKind::File
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("自定义启动项名称不能为空".into());
+        }
+        let target = self.target.trim();
+        if target.is_empty() {
+            return Err(format!("自定义启动项 {} 的目标不能为空", self.name));
+        }
+        if self.target_kind() == CustomTargetKind::File && !Path::new(target).exists() {
+            return Err(format!("自定义启动项 {} 的目标不存在: {target}", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `target` starts with an RFC 3986 scheme (`ms-settings:`, `mailto:`, `steam://`).
+/// Single-letter schemes are drive letters (`C:\...`) and don't count.
+fn has_uri_scheme(target: &str) -> bool {
+    let Some((scheme, _)) = target.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    scheme.len() > 1
+        && chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            keyword_penalty: 5,
+            description_penalty: 15,
+            bookmark_folder_penalty: 5,
+            bookmark_url_penalty: 8,
+            bookmark_keyword_penalty: 8,
+            prefix_boost: 60,
+            substring_boost: 25,
+            recent_launch_boost: 200,
+            deprioritize_patterns: ["unins", "updater", "crash", "helper"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            deprioritize_penalty: 40,
+        }
+    }
 }
 
 fn default_system_tool_exclusions() -> Vec<String> {
@@ -59,6 +409,56 @@ impl Default for AppConfig {
             force_english_input: default_force_english_input(),
             debug_mode: default_debug_mode(),
             system_tool_exclusions: default_system_tool_exclusions(),
+            scoring_weights: ScoringWeights::default(),
+            confirm_admin_launch: default_confirm_admin_launch(),
+            skip_dead_shortcuts: default_skip_dead_shortcuts(),
+            custom_launchers: default_custom_launchers(),
+            max_match_query_chars: default_max_match_query_chars(),
+            bookmark_browser: default_bookmark_browser(),
+            open_urls_in_background: default_open_urls_in_background(),
+            max_indexed_apps: default_max_indexed_apps(),
+            max_indexed_bookmarks: default_max_indexed_bookmarks(),
+            max_inline_icons: default_max_inline_icons(),
+            hidden_ids: default_hidden_ids(),
+            smart_case: default_smart_case(),
+            enable_run_mode: default_enable_run_mode(),
+            run_shell: default_run_shell(),
+            launch_order: default_launch_order(),
+            launch_focus_delay_ms: default_launch_focus_delay_ms(),
+            strip_tracking_params: default_strip_tracking_params(),
+            tracking_param_denylist: default_tracking_param_denylist(),
+            show_match_source: default_show_match_source(),
+            folder_separator: default_folder_separator(),
+            extra_app_folders: default_extra_app_folders(),
+            index_script_extensions: default_index_script_extensions(),
+            min_match_score: default_min_match_score(),
+            terminal: default_terminal(),
+            enable_opera_bookmarks: default_enable_opera_bookmarks(),
+            enable_opera_gx_bookmarks: default_enable_opera_gx_bookmarks(),
+            use_elevation_broker: default_use_elevation_broker(),
+            chrome_profile_allowlist: default_chrome_profile_allowlist(),
+            max_icon_bytes: default_max_icon_bytes(),
+            offline_mode: default_offline_mode(),
+            custom_bookmark_files: default_custom_bookmark_files(),
+            mode_cycle_hotkey: default_mode_cycle_hotkey(),
+            launch_on_active_monitor: default_launch_on_active_monitor(),
+            score_overrides: default_score_overrides(),
+            extra_app_dir_excludes: default_extra_app_dir_excludes(),
+            quick_open_hotkey: default_quick_open_hotkey(),
+            capture_crashes: default_capture_crashes(),
+            enable_cjk_transliteration: default_enable_cjk_transliteration(),
+            hide_after_launch: default_hide_after_launch(),
+            start_menu_max_depth: default_start_menu_max_depth(),
+            show_last_used: default_show_last_used(),
+            index_apps_folder: default_index_apps_folder(),
+            confirm_url_open: default_confirm_url_open(),
+            locale: default_locale(),
+            active_profile: default_active_profile(),
+            profiles: default_profiles(),
+            app_dir_allowlist: default_app_dir_allowlist(),
+            show_truncation_hint: default_show_truncation_hint(),
+            enable_paste_action: default_enable_paste_action(),
+            search_url_template: default_search_url_template(),
         }
     }
 }
@@ -103,6 +503,212 @@ const fn default_debug_mode() -> bool {
     false
 }
 
+const fn default_confirm_admin_launch() -> bool {
+    false
+}
+
+const fn default_skip_dead_shortcuts() -> bool {
+    true
+}
+
+fn default_custom_launchers() -> Vec<CustomLauncher> {
+    Vec::new()
+}
+
+const fn default_max_match_query_chars() -> usize {
+    256
+}
+
+fn default_bookmark_browser() -> String {
+    String::new()
+}
+
+const fn default_open_urls_in_background() -> bool {
+    false
+}
+
+const fn default_max_indexed_apps() -> usize {
+    10_000
+}
+
+const fn default_max_indexed_bookmarks() -> usize {
+    50_000
+}
+
+const fn default_max_inline_icons() -> usize {
+    10_000
+}
+
+fn default_hidden_ids() -> Vec<String> {
+    Vec::new()
+}
+
+const fn default_smart_case() -> bool {
+    true
+}
+
+const fn default_enable_run_mode() -> bool {
+    false
+}
+
+const fn default_run_shell() -> RunShell {
+    RunShell::Cmd
+}
+
+const fn default_launch_order() -> LaunchOrder {
+    LaunchOrder::LaunchThenHide
+}
+
+const fn default_launch_focus_delay_ms() -> u64 {
+    0
+}
+
+const fn default_strip_tracking_params() -> bool {
+    false
+}
+
+fn default_tracking_param_denylist() -> Vec<String> {
+    vec![
+        "utm_*".to_string(),
+        "fbclid".to_string(),
+        "gclid".to_string(),
+        "dclid".to_string(),
+        "msclkid".to_string(),
+        "mc_eid".to_string(),
+        "igshid".to_string(),
+        "yclid".to_string(),
+        "_hsenc".to_string(),
+        "_hsmi".to_string(),
+    ]
+}
+
+const fn default_show_match_source() -> bool {
+    false
+}
+
+fn default_folder_separator() -> String {
+    " / ".to_string()
+}
+
+fn default_extra_app_folders() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_index_script_extensions() -> Vec<String> {
+    Vec::new()
+}
+
+const fn default_min_match_score() -> i64 {
+    0
+}
+
+const fn default_terminal() -> Terminal {
+    Terminal::WindowsTerminal
+}
+
+const fn default_enable_opera_bookmarks() -> bool {
+    true
+}
+
+const fn default_enable_opera_gx_bookmarks() -> bool {
+    true
+}
+
+const fn default_use_elevation_broker() -> bool {
+    false
+}
+
+fn default_chrome_profile_allowlist() -> Option<Vec<String>> {
+    None
+}
+
+const fn default_max_icon_bytes() -> usize {
+    32 * 1024
+}
+
+const fn default_offline_mode() -> bool {
+    false
+}
+
+fn default_custom_bookmark_files() -> Vec<CustomBookmarkFile> {
+    Vec::new()
+}
+
+fn default_mode_cycle_hotkey() -> String {
+    "Tab".to_string()
+}
+
+const fn default_launch_on_active_monitor() -> bool {
+    false
+}
+
+fn default_score_overrides() -> HashMap<String, i64> {
+    HashMap::new()
+}
+
+fn default_extra_app_dir_excludes() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_quick_open_hotkey() -> String {
+    String::new()
+}
+
+const fn default_capture_crashes() -> bool {
+    false
+}
+
+const fn default_enable_cjk_transliteration() -> bool {
+    false
+}
+
+const fn default_hide_after_launch() -> bool {
+    true
+}
+
+const fn default_start_menu_max_depth() -> usize {
+    16
+}
+
+const fn default_show_last_used() -> bool {
+    false
+}
+
+const fn default_index_apps_folder() -> bool {
+    true
+}
+
+const fn default_confirm_url_open() -> bool {
+    false
+}
+
+fn default_locale() -> String {
+    "zh-CN".to_string()
+}
+
+fn default_active_profile() -> String {
+    String::new()
+}
+
+fn default_profiles() -> HashMap<String, ProfileOverrides> {
+    HashMap::new()
+}
+
+const fn default_app_dir_allowlist() -> Option<Vec<String>> {
+    None
+}
+
+const fn default_show_truncation_hint() -> bool {
+    false
+}
+
+const fn default_enable_paste_action() -> bool {
+    false
+}
+
+fn default_search_url_template() -> String {
+    GOOGLE_SEARCH_TEMPLATE.to_string()
+}
 
 impl AppConfig {
     pub fn load(handle: &AppHandle) -> Self {
@@ -120,6 +726,55 @@ impl AppConfig {
         }
     }
 
+    /// Returns the config with the active profile's overrides applied. Unknown or empty
+    /// profile names yield the base config unchanged.
+    pub fn effective(&self) -> AppConfig {
+        let mut config = self.clone();
+        let Some(overrides) = self.profiles.get(&self.active_profile) else {
+            return config;
+        };
+        if let Some(value) = &overrides.system_tool_exclusions {
+            config.system_tool_exclusions = value.clone();
+        }
+        if let Some(value) = &overrides.extra_app_folders {
+            config.extra_app_folders = value.clone();
+        }
+        if let Some(value) = &overrides.extra_app_dir_excludes {
+            config.extra_app_dir_excludes = value.clone();
+        }
+        if let Some(value) = &overrides.search_url_template {
+            config.search_url_template = value.clone();
+        }
+        config
+    }
+
+    /// Name of the first base-config field that differs from `previous` while the active
+    /// profile overrides it. Such an edit would be saved but never take effect.
+    pub fn shadowed_change(&self, previous: &AppConfig) -> Option<&'static str> {
+        let overrides = self.profiles.get(&self.active_profile)?;
+        if overrides.system_tool_exclusions.is_some()
+            && self.system_tool_exclusions != previous.system_tool_exclusions
+        {
+            return Some("system_tool_exclusions");
+        }
+        if overrides.extra_app_folders.is_some()
+            && self.extra_app_folders != previous.extra_app_folders
+        {
+            return Some("extra_app_folders");
+        }
+        if overrides.extra_app_dir_excludes.is_some()
+            && self.extra_app_dir_excludes != previous.extra_app_dir_excludes
+        {
+            return Some("extra_app_dir_excludes");
+        }
+        if overrides.search_url_template.is_some()
+            && self.search_url_template != previous.search_url_template
+        {
+            return Some("search_url_template");
+        }
+        None
+    }
+
     pub fn save(&self, handle: &AppHandle) -> Result<(), String> {
         let Some(path) = config_path(handle) else {
             return Err("无法确定配置目录".into());
@@ -132,10 +787,97 @@ impl AppConfig {
     }
 }
 
-fn config_path(handle: &AppHandle) -> Option<PathBuf> {
+/// Absolute path of the settings file inside the app config directory.
+pub(crate) fn config_path(handle: &AppHandle) -> Option<PathBuf> {
     handle
         .path()
         .app_config_dir()
         .ok()
         .map(|dir| dir.join(CONFIG_FILE))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launcher(target: &str) -> CustomLauncher {
+        CustomLauncher {
+            id: String::new(),
+            name: "test".into(),
+            keywords: Vec::new(),
+            target: target.into(),
+            arguments: None,
+            working_directory: None,
+            icon_path: None,
+        }
+    }
+
+    #[test]
+    fn target_kind_detects_schemes_without_slashes() {
+        let cases = [
+            ("https://example.com", CustomTargetKind::Url),
+            ("steam://run/570", CustomTargetKind::Uri),
+            ("ms-settings:display", CustomTargetKind::Uri),
+            ("mailto:me@example.com", CustomTargetKind::Uri),
+            ("shell:startup", CustomTargetKind::Uri),
+            (r"C:\Tools\app.exe", CustomTargetKind::File),
+            (r"\\server\share\app.exe", CustomTargetKind::File),
+        ];
+        for (target, kind) in cases {
+            assert_eq!(launcher(target).target_kind(), kind, "{target}");
+        }
+    }
+
+    fn with_profile(overrides: ProfileOverrides) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.profiles.insert("laptop".into(), overrides);
+        config.active_profile = "laptop".into();
+        config
+    }
+
+    #[test]
+    fn effective_applies_the_active_profile() {
+        let mut config = with_profile(ProfileOverrides {
+            extra_app_folders: Some(vec![r"D:\Portable".into()]),
+            search_url_template: Some("https://duckduckgo.com/?q={query}".into()),
+            ..Default::default()
+        });
+        let effective = config.effective();
+        assert_eq!(effective.extra_app_folders, [r"D:\Portable"]);
+        assert_eq!(
+            effective.search_url_template,
+            "https://duckduckgo.com/?q={query}"
+        );
+        assert_eq!(
+            effective.system_tool_exclusions,
+            config.system_tool_exclusions
+        );
+
+        config.active_profile.clear();
+        assert_eq!(
+            config.effective().search_url_template,
+            GOOGLE_SEARCH_TEMPLATE
+        );
+    }
+
+    #[test]
+    fn shadowed_change_flags_only_overridden_fields() {
+        let previous = with_profile(ProfileOverrides {
+            system_tool_exclusions: Some(Vec::new()),
+            ..Default::default()
+        });
+
+        let mut edited = previous.clone();
+        edited.extra_app_folders.push(r"D:\Tools".into());
+        assert_eq!(edited.shadowed_change(&previous), None);
+
+        edited.system_tool_exclusions.push(r"C:\Windows".into());
+        assert_eq!(
+            edited.shadowed_change(&previous),
+            Some("system_tool_exclusions")
+        );
+
+        edited.active_profile.clear();
+        assert_eq!(edited.shadowed_change(&previous), None);
+    }
+}
@@ -0,0 +1,66 @@
+//! Local crash capture. While enabled, a panic's message and backtrace are written to
+//! [`CRASH_LOG_FILE`] in the data directory so they survive the process. Nothing is sent
+//! anywhere; the file only holds the most recent crash.
+
+use std::{
+    backtrace::Backtrace,
+    fs, io,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::windows_utils;
+
+const CRASH_LOG_FILE: &str = "crash.log";
+
+static CAPTURE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Chains a panic hook after the default one. The default hook still prints the panic as
+/// before; the crash log is only written while capture is enabled via [`configure`].
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if CAPTURE_ENABLED.load(Ordering::SeqCst) {
+            if let Err(err) = write_crash_log(info) {
+                log::warn!("failed to write crash log: {err}");
+            }
+        }
+    }));
+}
+
+pub fn configure(enabled: bool) {
+    CAPTURE_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Contents of the crash log, if a crash has been captured.
+pub fn last_crash() -> Option<String> {
+    fs::read_to_string(crash_log_path()?).ok()
+}
+
+fn write_crash_log(info: &PanicHookInfo<'_>) -> io::Result<()> {
+    let path = crash_log_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "data directory unavailable"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let current = thread::current();
+    let report = format!(
+        "time: {timestamp}\nversion: {}\nthread: {}\n{info}\n\nbacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        current.name().unwrap_or("<unnamed>"),
+        Backtrace::force_capture(),
+    );
+    fs::write(path, report)
+}
+
+fn crash_log_path() -> Option<PathBuf> {
+    Some(windows_utils::data_dir()?.join(CRASH_LOG_FILE))
+}
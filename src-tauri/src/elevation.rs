@@ -0,0 +1,466 @@
+//! Optional elevation broker: an elevated copy of the launcher that runs admin launches on
+//! behalf of the main process, so only the broker's own start triggers a UAC prompt.
+//!
+//! The broker is the same executable started with [`BROKER_ARG`]. It listens on a named pipe
+//! whose name carries a random token, only serves the process that spawned it and exits as soon
+//! as that process does.
+
+use std::{
+    env,
+    ffi::OsStr,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    os::windows::io::{AsRawHandle, FromRawHandle},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{
+            CloseHandle, GetLastError, ERROR_FILE_NOT_FOUND, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED,
+            HANDLE,
+        },
+        Security::{
+            Authorization::{
+                ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+            },
+            Cryptography::{BCryptGenRandom, BCRYPT_ALG_HANDLE, BCRYPT_USE_SYSTEM_PREFERRED_RNG},
+            PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+        },
+        Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::{
+            Pipes::{
+                ConnectNamedPipe, CreateNamedPipeW, GetNamedPipeClientProcessId,
+                GetNamedPipeServerProcessId, WaitNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE,
+                PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+            },
+            Threading::{OpenProcess, WaitForSingleObject, INFINITE, PROCESS_SYNCHRONIZE},
+        },
+        UI::WindowsAndMessaging::SW_SHOWNORMAL,
+    },
+};
+
+use crate::{
+    commands::{shell_execute_for_process, shell_execute_internal},
+    windows_utils::os_str_to_wide,
+};
+
+/// Command-line switch that starts the executable as an elevation broker.
+pub const BROKER_ARG: &str = "--elevation-broker";
+
+const PIPE_PREFIX: &str = r"\\.\pipe\egg-elevation-";
+const PIPE_BUFFER_SIZE: u32 = 4096;
+/// How long to wait for a freshly spawned broker (including the UAC prompt) to open its pipe.
+const BROKER_START_TIMEOUT: Duration = Duration::from_secs(60);
+const BROKER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long a request waits for a free pipe instance before giving up.
+const PIPE_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Upper bound on a single request line; longer input is cut off and rejected.
+const MAX_REQUEST: u64 = 64 * 1024;
+/// Interactive users may connect; the pipe is labelled medium integrity so the non-elevated
+/// launcher can write to it. Callers are further checked by process id and token.
+const PIPE_SDDL: &str = "D:(A;;GRGW;;;IU)S:(ML;;NW;;;ME)";
+
+static BROKER_ENABLED: AtomicBool = AtomicBool::new(false);
+static BROKER: Lazy<Mutex<Option<BrokerHandle>>> = Lazy::new(|| Mutex::new(None));
+/// Serialises broker start-up so concurrent launches share one UAC prompt without holding
+/// [`BROKER`] while the prompt is open.
+static BROKER_START: Mutex<()> = Mutex::new(());
+
+#[derive(Clone)]
+struct BrokerHandle {
+    pipe_name: String,
+    token: String,
+    process_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum BrokerRequest {
+    Launch {
+        token: String,
+        target: String,
+        arguments: Option<String>,
+        working_directory: Option<String>,
+    },
+    Shutdown {
+        token: String,
+    },
+}
+
+impl BrokerRequest {
+    fn token(&self) -> &str {
+        match self {
+            Self::Launch { token, .. } | Self::Shutdown { token } => token,
+        }
+    }
+}
+
+/// Enables or disables routing admin launches through the broker. Disabling shuts down a
+/// running broker.
+pub fn configure(enabled: bool) {
+    BROKER_ENABLED.store(enabled, Ordering::SeqCst);
+    if enabled {
+        return;
+    }
+    let previous = BROKER.lock().ok().and_then(|mut guard| guard.take());
+    if let Some(broker) = previous {
+        shutdown(&broker);
+    }
+}
+
+fn shutdown(broker: &BrokerHandle) {
+    let request = BrokerRequest::Shutdown {
+        token: broker.token.clone(),
+    };
+    if let Err(err) = send_request(broker, &request) {
+        warn!("failed to stop elevation broker: {err}");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    BROKER_ENABLED.load(Ordering::SeqCst)
+}
+
+/// Launches `target` elevated through the broker, starting the broker first if needed.
+pub fn launch(
+    target: &str,
+    arguments: Option<&str>,
+    working_directory: Option<&str>,
+) -> Result<(), String> {
+    let broker = ensure_broker()?;
+    let request = BrokerRequest::Launch {
+        token: broker.token.clone(),
+        target: target.to_string(),
+        arguments: arguments.map(str::to_string),
+        working_directory: working_directory.map(str::to_string),
+    };
+    let pipe = match connect(&broker) {
+        Ok(pipe) => pipe,
+        Err(err) => {
+            // 重试后仍连不上说明代理已退出，下次重新启动
+            if let Ok(mut guard) = BROKER.lock() {
+                *guard = None;
+            }
+            return Err(err);
+        }
+    };
+    exchange(pipe, &request)
+}
+
+fn current_broker() -> Result<Option<BrokerHandle>, String> {
+    BROKER
+        .lock()
+        .map(|guard| guard.clone())
+        .map_err(|_| "无法获取提权代理状态".to_string())
+}
+
+fn ensure_broker() -> Result<BrokerHandle, String> {
+    if let Some(broker) = current_broker()? {
+        return Ok(broker);
+    }
+    let _starting = BROKER_START
+        .lock()
+        .map_err(|_| "无法获取提权代理状态".to_string())?;
+    // 等锁期间可能已由其他请求启动
+    if let Some(broker) = current_broker()? {
+        return Ok(broker);
+    }
+
+    let token = random_token()?;
+    let pipe_name = format!("{PIPE_PREFIX}{}-{token}", process::id());
+    let exe = env::current_exe().map_err(|err| err.to_string())?;
+    let arguments = format!("{BROKER_ARG} {pipe_name} {token} {}", process::id());
+    let process_id = shell_execute_for_process(
+        exe.as_os_str(),
+        Some(OsStr::new(&arguments)),
+        Some(OsStr::new("runas")),
+    )?
+    .ok_or_else(|| "无法获取提权代理进程".to_string())?;
+
+    // 等待代理创建管道
+    let started = Instant::now();
+    while !pipe_exists(&pipe_name) {
+        if started.elapsed() > BROKER_START_TIMEOUT {
+            return Err("提权代理启动超时".into());
+        }
+        thread::sleep(BROKER_POLL_INTERVAL);
+    }
+
+    info!("elevation broker started on {pipe_name}");
+    let broker = BrokerHandle {
+        pipe_name,
+        token,
+        process_id,
+    };
+    let mut guard = BROKER
+        .lock()
+        .map_err(|_| "无法获取提权代理状态".to_string())?;
+    // 启动期间代理被禁用时立即关闭
+    if !is_enabled() {
+        drop(guard);
+        shutdown(&broker);
+        return Err("提权代理已禁用".into());
+    }
+    *guard = Some(broker.clone());
+    Ok(broker)
+}
+
+fn pipe_exists(pipe_name: &str) -> bool {
+    let wide_name = os_str_to_wide(OsStr::new(pipe_name));
+    unsafe { WaitNamedPipeW(PCWSTR(wide_name.as_ptr()), 0).as_bool() }
+}
+
+/// Opens the broker pipe, waiting for a free instance while the broker is busy, and checks that
+/// the pipe is served by the broker process that was started.
+fn connect(broker: &BrokerHandle) -> Result<File, String> {
+    let wide_name = os_str_to_wide(OsStr::new(&broker.pipe_name));
+    let deadline = Instant::now() + PIPE_CONNECT_TIMEOUT;
+    let pipe = loop {
+        let err = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&broker.pipe_name)
+        {
+            Ok(pipe) => break pipe,
+            Err(err) => err,
+        };
+        let retryable = matches!(
+            err.raw_os_error(),
+            Some(code) if code == ERROR_PIPE_BUSY.0 as i32 || code == ERROR_FILE_NOT_FOUND.0 as i32
+        );
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !retryable || remaining.is_zero() {
+            return Err(format!("无法连接提权代理: {err}"));
+        }
+        // 实例全忙时等待空闲实例；代理切换实例的间隙管道可能暂时不存在
+        let timeout = u32::try_from(remaining.as_millis()).unwrap_or(u32::MAX);
+        if !unsafe { WaitNamedPipeW(PCWSTR(wide_name.as_ptr()), timeout) }.as_bool() {
+            thread::sleep(BROKER_POLL_INTERVAL.min(remaining));
+        }
+    };
+
+    let mut server_pid = 0u32;
+    let verified =
+        unsafe { GetNamedPipeServerProcessId(HANDLE(pipe.as_raw_handle()), &mut server_pid) }
+            .is_ok()
+            && server_pid == broker.process_id;
+    if !verified {
+        return Err("提权代理管道不属于已启动的代理进程".into());
+    }
+    Ok(pipe)
+}
+
+fn send_request(broker: &BrokerHandle, request: &BrokerRequest) -> Result<(), String> {
+    exchange(connect(broker)?, request)
+}
+
+/// Writes `request` to an open broker pipe and reads back the broker's reply.
+fn exchange(mut pipe: File, request: &BrokerRequest) -> Result<(), String> {
+    let mut payload = serde_json::to_string(request).map_err(|err| err.to_string())?;
+    payload.push('\n');
+    pipe.write_all(payload.as_bytes())
+        .map_err(|err| format!("无法发送提权请求: {err}"))?;
+
+    let mut response = String::new();
+    BufReader::new(pipe)
+        .read_line(&mut response)
+        .map_err(|err| format!("无法读取提权代理响应: {err}"))?;
+    match response.trim() {
+        "ok" => Ok(()),
+        other => Err(other.strip_prefix("error: ").unwrap_or(other).to_string()),
+    }
+}
+
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    let status = unsafe {
+        BCryptGenRandom(
+            BCRYPT_ALG_HANDLE::default(),
+            &mut bytes,
+            BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+        )
+    };
+    if status.is_err() {
+        return Err(format!("无法生成提权代理令牌 (NTSTATUS {:#x})", status.0));
+    }
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Runs the broker loop when the process was started with [`BROKER_ARG`]. Returns `false`
+/// for a normal launch.
+pub fn run_broker_if_requested() -> bool {
+    let args: Vec<String> = env::args().collect();
+    let Some(position) = args.iter().position(|arg| arg == BROKER_ARG) else {
+        return false;
+    };
+    let (Some(pipe_name), Some(token), Some(parent_pid)) = (
+        args.get(position + 1),
+        args.get(position + 2),
+        args.get(position + 3)
+            .and_then(|pid| pid.parse::<u32>().ok()),
+    ) else {
+        return true;
+    };
+    if !pipe_name.starts_with(PIPE_PREFIX) {
+        return true;
+    }
+
+    exit_with_parent(parent_pid);
+    if let Err(err) = serve(pipe_name, token, parent_pid) {
+        warn!("elevation broker stopped: {err}");
+    }
+    true
+}
+
+/// Exits the broker once the launcher that spawned it is gone.
+fn exit_with_parent(parent_pid: u32) {
+    let parent = unsafe { OpenProcess(PROCESS_SYNCHRONIZE, false, parent_pid) };
+    let Ok(parent) = parent else {
+        process::exit(0);
+    };
+    let parent = parent.0 as isize;
+    thread::spawn(move || unsafe {
+        let handle = HANDLE(parent as *mut _);
+        WaitForSingleObject(handle, INFINITE);
+        let _ = CloseHandle(handle);
+        process::exit(0);
+    });
+}
+
+fn serve(pipe_name: &str, token: &str, parent_pid: u32) -> Result<(), String> {
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+    let sddl = os_str_to_wide(OsStr::new(PIPE_SDDL));
+    unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    let attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor.0,
+        bInheritHandle: false.into(),
+    };
+    let wide_name = os_str_to_wide(OsStr::new(pipe_name));
+
+    // 首个实例带 FILE_FLAG_FIRST_PIPE_INSTANCE，管道名已被他人占用时直接失败
+    let mut pipe = create_pipe_instance(&wide_name, &attributes, true)?;
+    loop {
+        let connected = unsafe { ConnectNamedPipe(pipe, None) }.is_ok()
+            || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+        // 处理当前客户端前先创建下一个实例，保证管道名始终由代理持有
+        let next = create_pipe_instance(&wide_name, &attributes, false)?;
+        if !connected {
+            unsafe {
+                let _ = CloseHandle(pipe);
+            }
+            pipe = next;
+            continue;
+        }
+
+        let mut client_pid = 0u32;
+        let trusted = unsafe { GetNamedPipeClientProcessId(pipe, &mut client_pid) }.is_ok()
+            && client_pid == parent_pid;
+        // File 接管句柄，离开作用域时关闭
+        let file = unsafe { File::from_raw_handle(pipe.0 as _) };
+        if handle_client(file, token, trusted) {
+            unsafe {
+                let _ = CloseHandle(next);
+            }
+            return Ok(());
+        }
+        pipe = next;
+    }
+}
+
+fn create_pipe_instance(
+    wide_name: &[u16],
+    attributes: &SECURITY_ATTRIBUTES,
+    first: bool,
+) -> Result<HANDLE, String> {
+    let open_mode = if first {
+        PIPE_ACCESS_DUPLEX | FILE_FLAG_FIRST_PIPE_INSTANCE
+    } else {
+        PIPE_ACCESS_DUPLEX
+    };
+    let pipe = unsafe {
+        CreateNamedPipeW(
+            PCWSTR(wide_name.as_ptr()),
+            open_mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+            PIPE_UNLIMITED_INSTANCES,
+            PIPE_BUFFER_SIZE,
+            PIPE_BUFFER_SIZE,
+            0,
+            Some(attributes),
+        )
+    };
+    if pipe.is_invalid() {
+        return Err("无法创建提权代理管道".into());
+    }
+    Ok(pipe)
+}
+
+/// Serves one request. Returns `true` when the broker should shut down.
+fn handle_client(file: File, token: &str, trusted: bool) -> bool {
+    let mut request = String::new();
+    // 限制单个请求的长度，避免恶意客户端耗尽内存
+    let mut reader = BufReader::new((&file).take(MAX_REQUEST));
+    if reader.read_line(&mut request).is_err() {
+        return false;
+    }
+
+    let request = serde_json::from_str::<BrokerRequest>(request.trim())
+        .map_err(|err| err.to_string())
+        .and_then(|request| {
+            if trusted && request.token() == token {
+                Ok(request)
+            } else {
+                Err("拒绝来自未知进程的提权请求".to_string())
+            }
+        });
+
+    let (response, shutdown) = match request {
+        Ok(BrokerRequest::Launch {
+            target,
+            arguments,
+            working_directory,
+            ..
+        }) => {
+            let result = shell_execute_internal(
+                OsStr::new(&target),
+                arguments.as_deref().map(OsStr::new),
+                working_directory.as_deref().map(OsStr::new),
+                None,
+                SW_SHOWNORMAL,
+            );
+            (result, false)
+        }
+        Ok(BrokerRequest::Shutdown { .. }) => (Ok(()), true),
+        Err(err) => (Err(err), false),
+    };
+
+    let line = match response {
+        Ok(()) => "ok\n".to_string(),
+        Err(err) => format!("error: {err}\n"),
+    };
+    let mut writer = &file;
+    let _ = writer.write_all(line.as_bytes());
+    // 等待客户端读取完响应后再关闭管道
+    let _ = file.sync_all();
+    shutdown
+}
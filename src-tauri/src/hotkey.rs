@@ -1,19 +1,81 @@
-use std::sync::atomic::Ordering;
+use std::{
+    str::FromStr,
+    sync::{atomic::Ordering, Mutex},
+};
 
-use tauri::{AppHandle, Manager};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 
-use crate::{show_window, state::AppState};
+use crate::{commands, main_window, show_window, state::AppState};
 
-pub fn bind_hotkey(
-    app_handle: &AppHandle,
-    state: &AppState,
-    hotkey: &str,
-    window_label: &str,
-) -> Result<(), String> {
-    if hotkey.trim().is_empty() {
+/// Parses a hotkey string with the same rules the global shortcut plugin applies on registration.
+pub fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    let hotkey = hotkey.trim();
+    if hotkey.is_empty() {
         return Err("快捷键不能为空".into());
     }
+    Shortcut::from_str(hotkey).map_err(|err| format!("无效的快捷键 {hotkey}: {err}"))
+}
+
+/// Parses a hotkey and rejects combos that make poor launcher hotkeys: plain keys other than
+/// function keys, and combos reserved by Windows. Never touches registration state.
+pub fn validate_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    if shortcut.mods.is_empty() && !is_function_key(shortcut.key) {
+        return Err("快捷键需要至少包含一个修饰键（Ctrl、Alt、Shift 或 Win）".into());
+    }
+    if is_reserved_hotkey(&shortcut) {
+        return Err(format!("{} 已被系统占用，请换一个组合", hotkey.trim()));
+    }
+    Ok(shortcut)
+}
+
+fn is_function_key(code: Code) -> bool {
+    matches!(
+        code,
+        Code::F1
+            | Code::F2
+            | Code::F3
+            | Code::F4
+            | Code::F5
+            | Code::F6
+            | Code::F7
+            | Code::F8
+            | Code::F9
+            | Code::F10
+            | Code::F11
+            | Code::F12
+            | Code::F13
+            | Code::F14
+            | Code::F15
+            | Code::F16
+            | Code::F17
+            | Code::F18
+            | Code::F19
+            | Code::F20
+            | Code::F21
+            | Code::F22
+            | Code::F23
+            | Code::F24
+    )
+}
+
+fn is_reserved_hotkey(shortcut: &Shortcut) -> bool {
+    let mods = shortcut.mods;
+    match shortcut.key {
+        Code::F4 => mods == Modifiers::ALT,
+        Code::Tab => mods == Modifiers::ALT || mods == Modifiers::ALT | Modifiers::SHIFT,
+        Code::Delete => mods == Modifiers::CONTROL | Modifiers::ALT,
+        Code::Escape => mods == Modifiers::CONTROL || mods == Modifiers::CONTROL | Modifiers::SHIFT,
+        Code::KeyL => mods == Modifiers::SUPER,
+        _ => false,
+    }
+}
+
+/// Registers the launcher hotkey. Only parsing is checked here: `validate_hotkey` runs when the
+/// user changes the hotkey, so a stored hotkey that later rules would reject still binds.
+pub fn bind_hotkey(app_handle: &AppHandle, state: &AppState, hotkey: &str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)?;
 
     let mut current_hotkey = state
         .registered_hotkey
@@ -27,20 +89,17 @@ pub fn bind_hotkey(
     }
 
     let hotkey_string = hotkey.trim().to_string();
-    let shortcut_literal = hotkey_string.clone();
-    let window_label_string = window_label.to_string();
     let capture_guard = state.hotkey_capture_suspended.clone();
     app_handle
         .global_shortcut()
-        .on_shortcut(shortcut_literal.as_str(), {
-            let window_label = window_label_string;
+        .on_shortcut(shortcut, {
             let capture_guard = capture_guard.clone();
             move |app_handle, _, event| {
                 if event.state == ShortcutState::Pressed {
                     if capture_guard.load(Ordering::SeqCst) {
                         return;
                     }
-                    if let Some(window) = app_handle.get_webview_window(&window_label) {
+                    if let Some(window) = main_window(app_handle) {
                         if window.is_visible().unwrap_or(false) {
                             let _ = window.hide();
                         } else {
@@ -55,3 +114,85 @@ pub fn bind_hotkey(
     *current_hotkey = Some(hotkey_string);
     Ok(())
 }
+
+/// Binds the quick-open hotkey, which runs the top result of the remembered query without
+/// showing the window. An empty `hotkey` only unregisters the current one.
+pub fn bind_quick_open_hotkey(
+    app_handle: &AppHandle,
+    state: &AppState,
+    hotkey: &str,
+) -> Result<(), String> {
+    let hotkey = hotkey.trim();
+    if hotkey.is_empty() {
+        unregister_slot(app_handle, &state.registered_quick_open_hotkey);
+        return Ok(());
+    }
+    let shortcut = parse_hotkey(hotkey)?;
+    unregister_slot(app_handle, &state.registered_quick_open_hotkey);
+
+    let capture_guard = state.hotkey_capture_suspended.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _, event| {
+            if event.state == ShortcutState::Pressed && !capture_guard.load(Ordering::SeqCst) {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(commands::quick_open_last_query(app_handle));
+            }
+        })
+        .map_err(|err| err.to_string())?;
+
+    if let Ok(mut current) = state.registered_quick_open_hotkey.lock() {
+        *current = Some(hotkey.to_string());
+    }
+    Ok(())
+}
+
+/// Re-registers the configured launcher and quick-open hotkeys, e.g. after the system resumed and
+/// Windows dropped the registrations. Skipped while a hotkey capture owns the registrations.
+pub fn rebind_hotkeys(app_handle: &AppHandle, state: &AppState) -> Result<(), String> {
+    if state.hotkey_capture_suspended.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    let config = state.config_snapshot();
+    unbind_hotkey(app_handle, state);
+
+    if let Err(err) = bind_quick_open_hotkey(app_handle, state, &config.quick_open_hotkey) {
+        log::warn!(
+            "failed to rebind quick-open shortcut {}: {err}",
+            config.quick_open_hotkey
+        );
+    }
+    match bind_hotkey(app_handle, state, &config.global_hotkey) {
+        Ok(()) => {
+            log::info!("rebound global shortcut {}", config.global_hotkey);
+            Ok(())
+        }
+        Err(err) => {
+            log::warn!(
+                "failed to rebind global shortcut {}: {err}",
+                config.global_hotkey
+            );
+            Err(format!(
+                "无法重新注册快捷键 {}，可能已被其他程序占用: {err}",
+                config.global_hotkey
+            ))
+        }
+    }
+}
+
+/// Unregisters the launcher and quick-open hotkeys, if bound.
+pub fn unbind_hotkey(app_handle: &AppHandle, state: &AppState) {
+    unregister_slot(app_handle, &state.registered_hotkey);
+    unregister_slot(app_handle, &state.registered_quick_open_hotkey);
+}
+
+fn unregister_slot(app_handle: &AppHandle, slot: &Mutex<Option<String>>) {
+    let Ok(mut current_hotkey) = slot.lock() else {
+        return;
+    };
+    if let Some(previous) = current_hotkey.take() {
+        if let Err(err) = app_handle.global_shortcut().unregister(previous.as_str()) {
+            log::warn!("failed to unregister hotkey {previous}: {err}");
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, PoisonError,
     },
 };
 
@@ -11,9 +11,13 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
 
-use crate::{hotkey::bind_hotkey, state::AppState};
+use crate::{
+    hotkey::{bind_hotkey, parse_hotkey},
+    state::AppState,
+};
 
-pub const HOTKEY_CAPTURE_RESULT_EVENT: &str = "hotkey_capture_result";
+/// Emitted with a combo that is known to parse, ready to pass to `update_hotkey`.
+pub const HOTKEY_CAPTURED_EVENT: &str = "hotkey_captured";
 pub const HOTKEY_CAPTURE_CANCELLED_EVENT: &str = "hotkey_capture_cancelled";
 pub const HOTKEY_CAPTURE_INVALID_EVENT: &str = "hotkey_capture_invalid";
 
@@ -81,7 +85,7 @@ pub fn start(app_handle: AppHandle, state: AppState) -> Result<(), String> {
         if registered_shortcuts.is_empty() {
             log::error!("无法注册任何快捷键用于捕捉，放弃启动");
             if let Some(previous) = previous_hotkey.as_deref() {
-                if let Err(rebind_err) = bind_hotkey(&handler_app, &state, previous, "main") {
+                if let Err(rebind_err) = bind_hotkey(&handler_app, &state, previous) {
                     log::error!("恢复快捷键 {previous} 失败: {rebind_err}");
                 }
             }
@@ -108,11 +112,13 @@ pub fn stop() -> Result<(), String> {
 }
 
 fn stop_internal(handle_hint: Option<AppHandle>) -> Result<(), String> {
+    // 锁中毒时仍取出上下文，确保挂起标志总能被复位
     let mut guard = CAPTURE_CONTEXT
         .lock()
-        .map_err(|_| "无法访问快捷键捕捉状态".to_string())?;
+        .unwrap_or_else(PoisonError::into_inner);
 
     if let Some(ctx) = guard.take() {
+        ctx.suspension_flag.store(false, Ordering::SeqCst);
         let app_handle = handle_hint.unwrap_or_else(|| ctx.app_handle.clone());
 
         if !ctx.registered_shortcuts.is_empty() {
@@ -130,12 +136,10 @@ fn stop_internal(handle_hint: Option<AppHandle>) -> Result<(), String> {
         }
 
         if let Some(previous) = ctx.previous_hotkey.as_deref() {
-            if let Err(err) = bind_hotkey(&app_handle, &ctx.app_state, previous, "main") {
+            if let Err(err) = bind_hotkey(&app_handle, &ctx.app_state, previous) {
                 log::error!("恢复默认快捷键 {previous} 失败: {err}");
             }
         }
-
-        ctx.suspension_flag.store(false, Ordering::SeqCst);
     }
 
     Ok(())
@@ -160,12 +164,16 @@ fn handle_shortcut_event(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEv
             .and_then(|ctx| ctx.display_map.get(&normalized).cloned())
     });
 
-    if let Some(shortcut) = display_value {
-        let payload = HotkeyCaptureResultPayload { shortcut };
-        let _ = app.emit(HOTKEY_CAPTURE_RESULT_EVENT, payload);
-        enqueue_stop(app.clone());
-    } else {
-        let _ = app.emit(HOTKEY_CAPTURE_INVALID_EVENT, ());
+    // 只把能被 bind_hotkey 解析的组合交给前端，避免应用无效快捷键
+    match display_value.filter(|value| parse_hotkey(value).is_ok()) {
+        Some(shortcut) => {
+            let payload = HotkeyCaptureResultPayload { shortcut };
+            let _ = app.emit(HOTKEY_CAPTURED_EVENT, payload);
+            enqueue_stop(app.clone());
+        }
+        None => {
+            let _ = app.emit(HOTKEY_CAPTURE_INVALID_EVENT, ());
+        }
     }
 }
 
@@ -226,7 +234,8 @@ fn modifier_literals(mask: u8) -> (String, String) {
     }
     if mask & MOD_SUPER != 0 {
         shortcut_parts.push("super");
-        display_parts.push("Win");
+        // 快捷键解析器不识别 "Win"，统一使用 "Super"
+        display_parts.push("Super");
     }
 
     (shortcut_parts.join("+"), display_parts.join("+"))
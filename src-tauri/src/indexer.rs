@@ -1,37 +1,153 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     env, fs,
+    os::windows::fs::MetadataExt,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::UNIX_EPOCH,
 };
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
+use serde_json::Value;
 use tauri::async_runtime;
 use windows::{
-    core::Result as WinResult, Foundation::Size, Management::Deployment::PackageManager,
-    Storage::Streams::DataReader,
+    core::Result as WinResult, ApplicationModel::Package, Foundation::Size,
+    Management::Deployment::PackageManager, Storage::Streams::DataReader,
+    Win32::Storage::FileSystem::FILE_ATTRIBUTE_REPARSE_POINT,
 };
 use winreg::{enums::*, RegKey};
 
 use crate::{
+    config::AppConfig,
     models::{AppType, ApplicationInfo},
-    text_utils::extend_keywords_with_pinyin,
+    text_utils::{extend_keywords_with_pinyin, extend_keywords_with_transliteration},
     windows_utils::{
-        expand_env_vars, extract_icon_from_path, parse_internet_shortcut, resolve_shell_link,
+        apps_folder_entries, cached_icon_len_for_path, expand_env_vars, extract_icon_from_path,
+        known_folders, parse_internet_shortcut, quick_access_folders, refresh_icon_from_path,
+        resolve_shell_link, shrink_icon_base64, store_icon_for_path, FolderShortcut,
+        APPS_FOLDER_NAMESPACE,
     },
 };
 
-/// Build the application index by scanning Start Menu shortcuts, installed Win32 software and UWP apps.
-pub async fn build_index(exclusion_paths: Vec<String>) -> Vec<ApplicationInfo> {
-    let mut results = Vec::new();
+const FOLDER_ICON_SOURCE: &str = r"%SystemRoot%\System32\shell32.dll";
+const FOLDER_ICON_INDEX: i32 = 3;
+const CMD_ICON_SOURCE: &str = r"%SystemRoot%\System32\cmd.exe";
+const POWERSHELL_ICON_SOURCE: &str = r"%SystemRoot%\System32\WindowsPowerShell\v1.0\powershell.exe";
+/// Deepest subfolder level searched below each extra folder; the folder itself is level 0.
+const MAX_EXTRA_FOLDER_DEPTH: usize = 3;
+/// Directory entries examined across all extra folders before the scan gives up.
+const MAX_EXTRA_FOLDER_ENTRIES: usize = 50_000;
+
+/// Settings that influence which entries end up in the application index.
+#[derive(Debug, Clone)]
+pub struct IndexOptions {
+    pub exclusion_paths: Vec<String>,
+    /// Folders apps must live under to be indexed; `None` disables the allowlist.
+    pub allowed_dirs: Option<Vec<String>>,
+    pub skip_dead_shortcuts: bool,
+    pub hidden_ids: HashSet<String>,
+    pub extra_folders: Vec<String>,
+    pub extra_folder_excludes: Vec<String>,
+    pub script_extensions: Vec<String>,
+    pub max_icon_bytes: usize,
+    pub cjk_transliteration: bool,
+    pub start_menu_max_depth: usize,
+    pub apps_folder: bool,
+}
+
+impl From<&AppConfig> for IndexOptions {
+    fn from(config: &AppConfig) -> Self {
+        // 配置方案只影响索引相关的设置，在此合并
+        let config = &config.effective();
+        Self {
+            exclusion_paths: config.system_tool_exclusions.clone(),
+            allowed_dirs: config.app_dir_allowlist.clone(),
+            skip_dead_shortcuts: config.skip_dead_shortcuts,
+            hidden_ids: config.hidden_ids.iter().cloned().collect(),
+            extra_folders: config.extra_app_folders.clone(),
+            extra_folder_excludes: config.extra_app_dir_excludes.clone(),
+            script_extensions: config.index_script_extensions.clone(),
+            max_icon_bytes: config.max_icon_bytes,
+            cjk_transliteration: config.enable_cjk_transliteration,
+            start_menu_max_depth: config.start_menu_max_depth,
+            apps_folder: config.index_apps_folder,
+        }
+    }
+}
+
+/// Identifies one [`build_index`] run. Starting a newer run supersedes older ones, which then
+/// stop at the next stage or directory entry instead of finishing with stale data.
+#[derive(Debug, Clone)]
+pub struct IndexGeneration {
+    id: u64,
+    latest: Arc<AtomicU64>,
+}
+
+impl IndexGeneration {
+    /// Starts a new generation, superseding every earlier one sharing `latest`.
+    pub fn next(latest: &Arc<AtomicU64>) -> Self {
+        let id = latest.fetch_add(1, Ordering::SeqCst) + 1;
+        Self {
+            id,
+            latest: Arc::clone(latest),
+        }
+    }
 
-    let start_menu = match async_runtime::spawn_blocking(enumerate_start_menu_programs).await {
+    pub fn is_superseded(&self) -> bool {
+        self.latest.load(Ordering::SeqCst) != self.id
+    }
+
+    /// `None` once superseded, so stages can bail out with `?`.
+    fn ensure_current(&self) -> Option<()> {
+        if self.is_superseded() {
+            debug!("index generation {} superseded, stopping", self.id);
+            return None;
+        }
+        Some(())
+    }
+}
+
+/// Applications found by [`build_index`].
+pub struct IndexOutput {
+    /// Filtered applications used for normal searches.
+    pub applications: Vec<ApplicationInfo>,
+    /// Every UWP app, including system components without a Start menu entry and apps the user
+    /// hid, for the `sys` query mode. May contain entries that fail to launch.
+    pub system_uwp_apps: Vec<ApplicationInfo>,
+}
+
+/// Build the application index by scanning Start Menu shortcuts, installed Win32 software, UWP apps,
+/// JetBrains Toolbox IDEs, user-configured extra folders and frequently used folders.
+pub async fn build_index(
+    options: IndexOptions,
+    generation: IndexGeneration,
+) -> Option<IndexOutput> {
+    let mut results = Vec::new();
+    let mut system_uwp_apps = Vec::new();
+
+    let skip_dead_shortcuts = options.skip_dead_shortcuts;
+    let start_menu_max_depth = options.start_menu_max_depth;
+    let start_menu_generation = generation.clone();
+    let start_menu = match async_runtime::spawn_blocking(move || {
+        enumerate_start_menu_programs(
+            skip_dead_shortcuts,
+            start_menu_max_depth,
+            &start_menu_generation,
+        )
+    })
+    .await
+    {
         Ok(apps) => apps,
         Err(err) => {
             warn!("start menu index task failed: {err}");
             Vec::new()
         }
     };
+    generation.ensure_current()?;
     debug!("indexed {} start menu shortcuts", start_menu.len());
     results.extend(start_menu);
 
@@ -42,20 +158,147 @@ pub async fn build_index(exclusion_paths: Vec<String>) -> Vec<ApplicationInfo> {
             Vec::new()
         }
     };
+    generation.ensure_current()?;
     debug!("indexed {} installed Win32 apps", win32.len());
     results.extend(win32);
 
     match enumerate_uwp_apps().await {
-        Ok(mut uwp_apps) => {
-            debug!("indexed {} UWP entries", uwp_apps.len());
+        Ok((mut uwp_apps, unlisted)) => {
+            debug!(
+                "indexed {} UWP entries ({} unlisted)",
+                uwp_apps.len(),
+                unlisted.len()
+            );
+            system_uwp_apps.extend(uwp_apps.iter().cloned());
+            system_uwp_apps.extend(unlisted);
             results.append(&mut uwp_apps);
         }
         Err(err) => warn!("failed to enumerate UWP apps: {err}"),
     }
+    generation.ensure_current()?;
+
+    if options.apps_folder {
+        // 只补充其他来源遗漏的应用：按 AppUserModelId 与名称去重
+        let known_ids: HashSet<String> = results
+            .iter()
+            .filter(|app| app.app_type == AppType::Uwp)
+            .map(|app| app.path.to_lowercase())
+            .collect();
+        let known_names: HashSet<String> =
+            results.iter().map(|app| app.name.to_lowercase()).collect();
+        let apps_folder = match async_runtime::spawn_blocking(move || {
+            enumerate_apps_folder(&known_ids, &known_names)
+        })
+        .await
+        {
+            Ok(apps) => apps,
+            Err(err) => {
+                warn!("apps folder index task failed: {err}");
+                Vec::new()
+            }
+        };
+        generation.ensure_current()?;
+        debug!(
+            "indexed {} extra apps from shell:AppsFolder",
+            apps_folder.len()
+        );
+        results.extend(apps_folder);
+    }
+
+    let toolbox = match async_runtime::spawn_blocking(enumerate_toolbox_apps).await {
+        Ok(apps) => apps,
+        Err(err) => {
+            warn!("toolbox index task failed: {err}");
+            Vec::new()
+        }
+    };
+    generation.ensure_current()?;
+    debug!("indexed {} JetBrains Toolbox apps", toolbox.len());
+    results.extend(toolbox);
+
+    let extra_folders = options.extra_folders.clone();
+    let extra_folder_excludes = options.extra_folder_excludes.clone();
+    let script_extensions = options.script_extensions.clone();
+    let extra_generation = generation.clone();
+    let extra = match async_runtime::spawn_blocking(move || {
+        enumerate_extra_folder_apps(
+            &extra_folders,
+            &extra_folder_excludes,
+            &script_extensions,
+            &extra_generation,
+        )
+    })
+    .await
+    {
+        Ok(apps) => apps,
+        Err(err) => {
+            warn!("extra folder index task failed: {err}");
+            Vec::new()
+        }
+    };
+    generation.ensure_current()?;
+    debug!("indexed {} entries from extra folders", extra.len());
+    results.extend(extra);
+
+    let folders = match async_runtime::spawn_blocking(enumerate_folder_shortcuts).await {
+        Ok(folders) => folders,
+        Err(err) => {
+            warn!("folder index task failed: {err}");
+            Vec::new()
+        }
+    };
+    generation.ensure_current()?;
+    debug!("indexed {} folder shortcuts", folders.len());
+    results.extend(folders);
+
+    let mut results = dedup_applications(results);
+    results.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    
+    // Filter out system tools based on path
+    results.retain(|app| !is_system_tool(app, &options.exclusion_paths));
+
+    if let Some(allowed_dirs) = &options.allowed_dirs {
+        let roots: Vec<PathBuf> = allowed_dirs
+            .iter()
+            .map(|dir| normalized_path(&expand_env_vars(dir).unwrap_or_else(|| dir.clone())))
+            .collect();
+        let before = results.len();
+        results.retain(|app| is_in_allowed_dir(app, &roots));
+        info!(
+            "app directory allowlist kept {} of {before} apps",
+            results.len()
+        );
+    }
+
+    // 用户手动隐藏的应用在每次重建索引时都会被过滤
+    results.retain(|app| !options.hidden_ids.contains(&app.id));
+
+    for app in results.iter_mut() {
+        let icon = std::mem::take(&mut app.icon_b64);
+        app.icon_b64 = cap_icon_size(app, icon, options.max_icon_bytes);
+    }
+    generation.ensure_current()?;
+
+    // 假名与韩文名称的罗马字写法作为额外关键词，沿用关键词扣分
+    if options.cjk_transliteration {
+        for app in results.iter_mut().chain(system_uwp_apps.iter_mut()) {
+            extend_keywords_with_transliteration(&app.name, &mut app.keywords);
+        }
+    }
 
-    // De-duplicate by resolved target path while keeping Start Menu preference over registry entries.
-    let mut seen: HashSet<(AppType, String, Option<String>)> = HashSet::new();
-    results.retain(|app| {
+    Some(IndexOutput {
+        applications: results,
+        system_uwp_apps,
+    })
+}
+
+/// De-duplicates apps by resolved target path, keeping Start Menu entries over registry ones.
+/// Arguments are part of the key, so shortcuts to the same exe with different arguments
+/// (e.g. "Word" and "Word (Safe Mode)") are kept as separate entries.
+fn dedup_applications(results: Vec<ApplicationInfo>) -> Vec<ApplicationInfo> {
+    let mut seen: HashMap<(AppType, String, Option<String>), usize> = HashMap::new();
+    let mut deduped: Vec<ApplicationInfo> = Vec::with_capacity(results.len());
+    for app in results {
         let key_path = app
             .source_path
             .as_ref()
@@ -65,14 +308,60 @@ pub async fn build_index(exclusion_paths: Vec<String>) -> Vec<ApplicationInfo> {
             .arguments
             .as_ref()
             .map(|value| value.to_ascii_lowercase());
-        seen.insert((app.app_type.clone(), key_path, argument_key))
-    });
-    results.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    
-    // Filter out system tools based on path
-    results.retain(|app| !is_system_tool(app, &exclusion_paths));
-    
-    results
+        let key = (app.app_type.clone(), key_path, argument_key);
+        match seen.get(&key) {
+            // 被去重的注册表项仍可为保留的开始菜单项提供卸载命令
+            Some(&index) => {
+                if deduped[index].uninstall_string.is_none() {
+                    deduped[index].uninstall_string = app.uninstall_string;
+                }
+            }
+            None => {
+                seen.insert(key, deduped.len());
+                deduped.push(app);
+            }
+        }
+    }
+    deduped
+}
+
+/// Downscales `icon` when its base64 form exceeds `max_bytes` (`0` disables the cap) and
+/// re-caches the smaller version when the cached copy is itself over the cap, so later loads
+/// stay under it without rewriting the cache on every rebuild.
+pub fn cap_icon_size(app: &ApplicationInfo, icon: String, max_bytes: usize) -> String {
+    if max_bytes == 0 || icon.len() <= max_bytes {
+        return icon;
+    }
+    let Some(shrunk) = shrink_icon_base64(&icon, max_bytes) else {
+        return icon;
+    };
+    // UWP 图标不经过磁盘缓存，只有从文件提取的图标需要回写
+    if app.app_type != AppType::Uwp {
+        if let Some(source) = app.icon_source.as_deref() {
+            let cached_oversized = cached_icon_len_for_path(source, app.icon_index)
+                .is_some_and(|len| len > max_bytes as u64);
+            if cached_oversized {
+                store_icon_for_path(source, app.icon_index, &shrunk);
+            }
+        }
+    }
+    shrunk
+}
+
+/// Whether the app's target (its shortcut target when known) lies under one of `roots`, which
+/// must already be [`normalized_path`]s. Matching is per path component, so `C:\Tools` does
+/// not admit `C:\ToolsX`.
+fn is_in_allowed_dir(app: &ApplicationInfo, roots: &[PathBuf]) -> bool {
+    let target = app.source_path.as_deref().unwrap_or(&app.path);
+    let target = normalized_path(target);
+    roots.iter().any(|root| target.starts_with(root))
+}
+
+/// Canonicalizes and lowercases a path for case-insensitive comparisons, falling back to the
+/// lowercase input when it can't be canonicalized.
+fn normalized_path(path: &str) -> PathBuf {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    PathBuf::from(canonical.to_string_lossy().to_lowercase())
 }
 
 /// Check if an application is a Windows system tool based on its path
@@ -96,18 +385,59 @@ const UNINSTALL_SUBKEYS: &[&str] = &[
 ];
 
 const SUPPORTED_URL_PROTOCOLS: &[&str] = &["steam://", "com.epicgames.launcher://apps/"];
+/// Start Menu folder names (English and localized) too generic to be useful as keywords.
+const GENERIC_START_MENU_FOLDERS: &[&str] = &[
+    "programs",
+    "程序",
+    "start menu",
+    "「开始」菜单",
+    "开始菜单",
+    "accessories",
+    "附件",
+    "windows accessories",
+    "windows 附件",
+    "administrative tools",
+    "管理工具",
+    "windows administrative tools",
+    "windows 管理工具",
+    "windows tools",
+    "windows 工具",
+    "system tools",
+    "系统工具",
+    "windows system",
+    "windows 系统",
+    "maintenance",
+    "维护",
+    "startup",
+    "启动",
+];
 
-fn enumerate_start_menu_programs() -> Vec<ApplicationInfo> {
+fn enumerate_start_menu_programs(
+    skip_dead_shortcuts: bool,
+    max_depth: usize,
+    generation: &IndexGeneration,
+) -> Vec<ApplicationInfo> {
     let startup_dirs = startup_directories();
     let mut applications = Vec::new();
+    let mut skipped_dead = 0usize;
+    let mut pruned_dirs = 0usize;
+    // 按规范化路径记录已访问目录，防止联接点构成的环路导致重复遍历
+    let mut visited = HashSet::new();
 
     for root in start_menu_roots() {
         if !root.is_dir() {
             continue;
         }
 
-        let mut stack = vec![root];
-        while let Some(dir) = stack.pop() {
+        let mut stack = vec![(root, 0usize)];
+        while let Some((dir, depth)) = stack.pop() {
+            // 已被新的重建取代时放弃剩余目录，结果会被丢弃
+            if generation.is_superseded() {
+                return applications;
+            }
+            if !visited.insert(fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone())) {
+                continue;
+            }
             let entries = match fs::read_dir(&dir) {
                 Ok(entries) => entries,
                 Err(_) => continue,
@@ -120,7 +450,11 @@ fn enumerate_start_menu_programs() -> Vec<ApplicationInfo> {
                 };
 
                 if file_type.is_dir() {
-                    stack.push(path);
+                    if depth < max_depth {
+                        stack.push((path, depth + 1));
+                    } else {
+                        pruned_dirs += 1;
+                    }
                     continue;
                 }
 
@@ -139,7 +473,9 @@ fn enumerate_start_menu_programs() -> Vec<ApplicationInfo> {
 
                 match extension.as_deref() {
                     Some("lnk") => {
-                        if let Some(app) = shortcut_to_application(&path) {
+                        if let Some(app) =
+                            shortcut_to_application(&path, skip_dead_shortcuts, &mut skipped_dead)
+                        {
                             applications.push(app);
                         }
                     }
@@ -154,10 +490,32 @@ fn enumerate_start_menu_programs() -> Vec<ApplicationInfo> {
         }
     }
 
+    if skipped_dead > 0 {
+        info!("skipped {skipped_dead} start menu shortcuts with missing targets");
+    }
+    if pruned_dirs > 0 {
+        info!("skipped {pruned_dirs} start menu folders deeper than {max_depth} levels");
+    }
+
     applications
 }
 
-fn shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
+/// Returns the shortcut's immediate parent folder name (e.g. "JetBrains") unless it is a
+/// generic Start Menu folder.
+fn vendor_folder_name(path: &Path) -> Option<String> {
+    let folder = path.parent()?.file_name()?.to_str()?.trim();
+    let lower = folder.to_lowercase();
+    if folder.is_empty() || GENERIC_START_MENU_FOLDERS.contains(&lower.as_str()) {
+        return None;
+    }
+    Some(folder.to_string())
+}
+
+fn shortcut_to_application(
+    path: &Path,
+    skip_dead_shortcuts: bool,
+    skipped_dead: &mut usize,
+) -> Option<ApplicationInfo> {
     let shortcut = resolve_shell_link(path)?;
     let name = path
         .file_stem()
@@ -172,6 +530,14 @@ fn shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
         .target_path
         .as_deref()
         .and_then(|raw| sanitize_executable_path(raw));
+    if skip_dead_shortcuts && resolved_target.is_none() {
+        if let Some(raw) = shortcut.target_path.as_deref() {
+            if is_dead_shortcut_target(raw) {
+                *skipped_dead += 1;
+                return None;
+            }
+        }
+    }
     let display_target = resolved_target
         .clone()
         .or_else(|| shortcut.target_path.clone())
@@ -200,6 +566,9 @@ fn shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
     if let Some(desc) = shortcut.description.clone() {
         keywords.push(desc.clone());
     }
+    if let Some(folder) = vendor_folder_name(path) {
+        keywords.push(folder);
+    }
     keywords.retain(|value| !value.trim().is_empty());
     extend_keywords_with_pinyin(&mut keywords);
     keywords.sort();
@@ -239,13 +608,33 @@ fn shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
         source_path: display_target,
         app_type: AppType::Win32,
         icon_b64,
+        icon_source: Some(icon_source),
+        icon_index: shortcut.icon_index,
         description,
         keywords,
         working_directory,
         arguments,
+        installed_at: file_modified_unix(path),
+        uninstall_string: None,
     })
 }
 
+/// A shortcut target is considered dead when it points at a filesystem path that no longer
+/// exists. URI-style and activation targets (e.g. `steam://`, `com.squirrel.*`) are kept.
+fn is_dead_shortcut_target(raw: &str) -> bool {
+    let trimmed = raw.trim().trim_matches(|c| c == '"' || c == '\'');
+    if trimmed.is_empty() {
+        return false;
+    }
+    let is_filesystem_path = trimmed.contains('\\') || trimmed.contains('/');
+    if trimmed.contains("://") || trimmed.contains('!') || !is_filesystem_path {
+        return false;
+    }
+
+    let expanded = expand_env_vars(trimmed).unwrap_or_else(|| trimmed.to_string());
+    !Path::new(&expanded).exists()
+}
+
 fn internet_shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
     let shortcut = parse_internet_shortcut(path)?;
     let url = shortcut.url.trim();
@@ -299,13 +688,459 @@ fn internet_shortcut_to_application(path: &Path) -> Option<ApplicationInfo> {
         source_path: Some(normalized_url),
         app_type: AppType::Win32,
         icon_b64,
+        icon_source: Some(icon_source),
+        icon_index: shortcut.icon_index,
+        description,
+        keywords,
+        working_directory: None,
+        arguments: None,
+        installed_at: file_modified_unix(path),
+        uninstall_string: None,
+    })
+}
+
+struct ToolboxTool {
+    name: String,
+    version: Option<String>,
+    executable: PathBuf,
+}
+
+/// Lists `shell:AppsFolder` apps whose AppUserModelId and name are not in the given sets. They
+/// are launched through the shell namespace, which also covers unpackaged apps that register an
+/// AppUserModelId.
+fn enumerate_apps_folder(
+    known_ids: &HashSet<String>,
+    known_names: &HashSet<String>,
+) -> Vec<ApplicationInfo> {
+    apps_folder_entries(|name, app_id| {
+        !known_ids.contains(&app_id.to_lowercase()) && !known_names.contains(&name.to_lowercase())
+    })
+    .into_iter()
+    .map(|entry| {
+        let mut keywords = vec![entry.name.clone(), entry.app_id.clone()];
+        keywords.extend(aumid_keywords(&entry.app_id));
+        keywords.retain(|value| !value.is_empty());
+        extend_keywords_with_pinyin(&mut keywords);
+        keywords.sort();
+        keywords.dedup();
+
+        ApplicationInfo {
+            id: format!("appsfolder:{}", entry.app_id.to_lowercase()),
+            name: entry.name,
+            path: format!("{APPS_FOLDER_NAMESPACE}\\{}", entry.app_id),
+            source_path: None,
+            app_type: AppType::Win32,
+            icon_b64: entry.icon_b64.unwrap_or_default(),
+            icon_source: None,
+            icon_index: 0,
+            description: None,
+            keywords,
+            working_directory: None,
+            arguments: None,
+            installed_at: None,
+            uninstall_string: None,
+        }
+    })
+    .collect()
+}
+
+/// Lists IDEs installed through JetBrains Toolbox. The executable comes from the install
+/// location Toolbox records for the current version, so entries stay valid across IDE updates
+/// that Start Menu shortcuts may not follow.
+fn enumerate_toolbox_apps() -> Vec<ApplicationInfo> {
+    let Some(base) = env::var_os("LOCALAPPDATA") else {
+        return Vec::new();
+    };
+    let root = PathBuf::from(base).join("JetBrains").join("Toolbox");
+    if !root.is_dir() {
+        return Vec::new();
+    }
+
+    // Toolbox 2.x 使用 state.json，1.x 则只有各渠道目录下的 .history.json
+    let tools = read_toolbox_state(&root).unwrap_or_else(|| read_toolbox_history(&root));
+    tools
+        .into_iter()
+        .filter_map(toolbox_tool_to_application)
+        .collect()
+}
+
+fn read_toolbox_state(root: &Path) -> Option<Vec<ToolboxTool>> {
+    let content = fs::read_to_string(root.join("state.json")).ok()?;
+    let state: Value = serde_json::from_str(&content).ok()?;
+    let tools = state.get("tools")?.as_array()?;
+
+    Some(
+        tools
+            .iter()
+            .filter_map(|tool| {
+                let name = tool.get("displayName")?.as_str()?.trim().to_string();
+                let location = tool.get("installLocation")?.as_str()?;
+                let launch_command = tool.get("launchCommand")?.as_str()?;
+                let version = tool
+                    .get("displayVersion")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                Some(ToolboxTool {
+                    name,
+                    version,
+                    executable: Path::new(location).join(launch_command),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Toolbox 1.x layout: `apps/<tool>/ch-<n>/<build>/`, where the channel's `.history.json`
+/// names the currently installed build.
+fn read_toolbox_history(root: &Path) -> Vec<ToolboxTool> {
+    let mut tools = Vec::new();
+    let Ok(apps) = fs::read_dir(root.join("apps")) else {
+        return tools;
+    };
+
+    for app_dir in apps.flatten().map(|entry| entry.path()) {
+        let Ok(channels) = fs::read_dir(&app_dir) else {
+            continue;
+        };
+        for channel in channels.flatten().map(|entry| entry.path()) {
+            let Some(build) = latest_toolbox_build(&channel) else {
+                continue;
+            };
+            if let Some(tool) = read_product_info(&channel.join(build)) {
+                tools.push(tool);
+            }
+        }
+    }
+
+    tools
+}
+
+fn latest_toolbox_build(channel: &Path) -> Option<String> {
+    let content = fs::read_to_string(channel.join(".history.json")).ok()?;
+    let history: Value = serde_json::from_str(&content).ok()?;
+    history
+        .get("history")?
+        .as_array()?
+        .iter()
+        .rev()
+        .filter(|entry| entry.get("action").and_then(Value::as_str) == Some("install"))
+        .find_map(|entry| {
+            entry
+                .get("item")?
+                .get("build")?
+                .as_str()
+                .map(str::to_string)
+        })
+}
+
+/// Reads the IDE's `product-info.json` for its display name and Windows launcher.
+fn read_product_info(install_dir: &Path) -> Option<ToolboxTool> {
+    let content = fs::read_to_string(install_dir.join("product-info.json")).ok()?;
+    let info: Value = serde_json::from_str(&content).ok()?;
+    let name = info.get("name")?.as_str()?.trim().to_string();
+    let launcher = info
+        .get("launch")?
+        .as_array()?
+        .iter()
+        .find(|entry| entry.get("os").and_then(Value::as_str) == Some("Windows"))?
+        .get("launcherPath")?
+        .as_str()?;
+    let version = info
+        .get("version")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Some(ToolboxTool {
+        name,
+        version,
+        executable: install_dir.join(launcher),
+    })
+}
+
+fn toolbox_tool_to_application(tool: ToolboxTool) -> Option<ApplicationInfo> {
+    if tool.name.is_empty() || !tool.executable.is_file() {
+        return None;
+    }
+
+    let path = tool.executable.to_string_lossy().into_owned();
+    let mut keywords = vec![tool.name.clone(), "JetBrains".to_string()];
+    if let Some(file_name) = tool.executable.file_name().and_then(|value| value.to_str()) {
+        keywords.push(file_name.to_string());
+    }
+    extend_keywords_with_pinyin(&mut keywords);
+    keywords.sort();
+    keywords.dedup();
+
+    let description = Some(match &tool.version {
+        Some(version) => format!("JetBrains Toolbox · {version}"),
+        None => "JetBrains Toolbox".to_string(),
+    });
+    let icon_b64 = extract_icon_from_path(&path, 0).unwrap_or_default();
+
+    Some(ApplicationInfo {
+        // 使用名称而非版本化路径作为 id，升级后使用统计和隐藏设置依然有效
+        id: format!("win32:toolbox:{}", tool.name.to_lowercase()),
+        name: tool.name,
+        path: path.clone(),
+        source_path: None,
+        app_type: AppType::Win32,
+        icon_b64,
+        icon_source: Some(path),
+        icon_index: 0,
         description,
         keywords,
         working_directory: None,
         arguments: None,
+        installed_at: file_modified_unix(&tool.executable),
+        uninstall_string: None,
     })
 }
 
+/// Scans each extra folder, down to [`MAX_EXTRA_FOLDER_DEPTH`] levels of subfolders, for
+/// executables and, when enabled, scripts. Subfolders matching `excludes` and reparse points
+/// (junctions, symlinks) are skipped; the whole scan stops after [`MAX_EXTRA_FOLDER_ENTRIES`].
+fn enumerate_extra_folder_apps(
+    folders: &[String],
+    excludes: &[String],
+    script_extensions: &[String],
+    generation: &IndexGeneration,
+) -> Vec<ApplicationInfo> {
+    let mut apps = Vec::new();
+    let mut examined = 0usize;
+    for folder in folders {
+        let resolved = expand_env_vars(folder).unwrap_or_else(|| folder.clone());
+        let mut stack = vec![(PathBuf::from(&resolved), 0usize)];
+        while let Some((dir, depth)) = stack.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    if depth == 0 {
+                        warn!("failed to read extra folder {resolved}: {err}");
+                    }
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                if generation.is_superseded() {
+                    return apps;
+                }
+                examined += 1;
+                if examined > MAX_EXTRA_FOLDER_ENTRIES {
+                    warn!(
+                        "extra folder scan stopped after {MAX_EXTRA_FOLDER_ENTRIES} entries, \
+                         some apps may be missing"
+                    );
+                    return apps;
+                }
+                let path = entry.path();
+                // DirEntry::metadata 不跟随链接，可据此识别联接点与符号链接
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT.0 != 0 {
+                    continue;
+                }
+                if metadata.is_dir() {
+                    if is_excluded_dir(&path, excludes) {
+                        continue;
+                    }
+                    if depth < MAX_EXTRA_FOLDER_DEPTH {
+                        stack.push((path, depth + 1));
+                    } else {
+                        debug!("extra folder depth limit reached at {}", path.display());
+                    }
+                    continue;
+                }
+                if let Some(app) = extra_folder_entry_to_application(&path, script_extensions) {
+                    apps.push(app);
+                }
+            }
+        }
+    }
+    apps
+}
+
+fn extra_folder_entry_to_application(
+    path: &Path,
+    script_extensions: &[String],
+) -> Option<ApplicationInfo> {
+    if !path.is_file() {
+        return None;
+    }
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())?;
+
+    if extension == "exe" {
+        portable_exe_to_application(path)
+    } else if script_extensions.iter().any(|ext| ext == &extension) {
+        script_to_application(path, &extension)
+    } else {
+        None
+    }
+}
+
+/// True when the folder's name or full path matches one of the exclude globs.
+fn is_excluded_dir(path: &Path, excludes: &[String]) -> bool {
+    if excludes.is_empty() {
+        return false;
+    }
+    let full = path.to_string_lossy().to_lowercase();
+    let name = path
+        .file_name()
+        .map(|value| value.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    excludes.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        glob_match(&pattern, &name) || glob_match(&pattern, &full)
+    })
+}
+
+/// Minimal glob matching where `*` matches any run of characters and `?` a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 * 的位置及其当时对应的文本位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn portable_exe_to_application(path: &Path) -> Option<ApplicationInfo> {
+    let name = path.file_stem()?.to_str()?.to_string();
+    let path_string = path.to_string_lossy().into_owned();
+    let mut keywords = vec![name.clone()];
+    if let Some(file_name) = path.file_name().and_then(|value| value.to_str()) {
+        keywords.push(file_name.to_string());
+    }
+    extend_keywords_with_pinyin(&mut keywords);
+    keywords.sort();
+    keywords.dedup();
+
+    let icon_b64 = extract_icon_from_path(&path_string, 0).unwrap_or_default();
+
+    Some(ApplicationInfo {
+        id: format!("win32:extra:{}", path_string.to_lowercase()),
+        name,
+        path: path_string.clone(),
+        source_path: None,
+        app_type: AppType::Win32,
+        icon_b64,
+        icon_source: Some(path_string),
+        icon_index: 0,
+        description: None,
+        keywords,
+        working_directory: path
+            .parent()
+            .map(|parent| parent.to_string_lossy().into_owned()),
+        arguments: None,
+        installed_at: file_modified_unix(path),
+        uninstall_string: None,
+    })
+}
+
+fn script_to_application(path: &Path, extension: &str) -> Option<ApplicationInfo> {
+    let name = path.file_stem()?.to_str()?.to_string();
+    let path_string = path.to_string_lossy().into_owned();
+    let mut keywords = vec![name.clone(), extension.to_string()];
+    if let Some(file_name) = path.file_name().and_then(|value| value.to_str()) {
+        keywords.push(file_name.to_string());
+    }
+    extend_keywords_with_pinyin(&mut keywords);
+    keywords.sort();
+    keywords.dedup();
+
+    // 使用解释器图标区分脚本与普通程序
+    let icon_source = if extension == "ps1" {
+        POWERSHELL_ICON_SOURCE
+    } else {
+        CMD_ICON_SOURCE
+    };
+    let icon_b64 = extract_icon_from_path(icon_source, 0).unwrap_or_default();
+
+    Some(ApplicationInfo {
+        id: format!("script:{}", path_string.to_lowercase()),
+        name,
+        path: path_string.clone(),
+        source_path: None,
+        app_type: AppType::Script,
+        icon_b64,
+        icon_source: Some(icon_source.to_string()),
+        icon_index: 0,
+        description: Some(format!(
+            "{} 脚本 · {path_string}",
+            extension.to_ascii_uppercase()
+        )),
+        keywords,
+        working_directory: path
+            .parent()
+            .map(|parent| parent.to_string_lossy().into_owned()),
+        arguments: None,
+        installed_at: file_modified_unix(path),
+        uninstall_string: None,
+    })
+}
+
+fn enumerate_folder_shortcuts() -> Vec<ApplicationInfo> {
+    let icon_b64 =
+        extract_icon_from_path(FOLDER_ICON_SOURCE, FOLDER_ICON_INDEX).unwrap_or_default();
+    let mut seen = HashSet::new();
+
+    known_folders()
+        .into_iter()
+        .chain(quick_access_folders())
+        .filter(|folder| seen.insert(folder.path.to_lowercase()))
+        .map(|folder| folder_to_application(folder, &icon_b64))
+        .collect()
+}
+
+fn folder_to_application(folder: FolderShortcut, icon_b64: &str) -> ApplicationInfo {
+    let mut keywords = vec![folder.name.clone(), folder.path.clone()];
+    if let Some(file_name) = Path::new(&folder.path)
+        .file_name()
+        .and_then(|value| value.to_str())
+    {
+        keywords.push(file_name.to_string());
+    }
+    keywords.retain(|value| !value.trim().is_empty());
+    extend_keywords_with_pinyin(&mut keywords);
+    keywords.sort();
+    keywords.dedup();
+
+    ApplicationInfo {
+        id: format!("folder:{}", folder.path.to_lowercase()),
+        name: folder.name,
+        path: folder.path,
+        source_path: None,
+        app_type: AppType::Folder,
+        icon_b64: icon_b64.to_string(),
+        icon_source: Some(FOLDER_ICON_SOURCE.to_string()),
+        icon_index: FOLDER_ICON_INDEX,
+        description: None,
+        keywords,
+        working_directory: None,
+        arguments: None,
+        installed_at: None,
+        uninstall_string: None,
+    }
+}
+
 fn start_menu_roots() -> Vec<PathBuf> {
     let mut roots = Vec::new();
     if let Some(app_data) = env::var_os("APPDATA") {
@@ -430,6 +1265,17 @@ fn registry_entry_to_app(
         .ok()
         .filter(|value| !value.trim().is_empty());
 
+    let installed_at = key
+        .get_value::<String, _>("InstallDate")
+        .ok()
+        .and_then(|value| parse_install_date(&value));
+
+    let uninstall_string = key
+        .get_value::<String, _>("UninstallString")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
     let mut keywords = Vec::new();
     keywords.push(display_name.clone());
     if let Some(desc) = description.clone() {
@@ -456,35 +1302,110 @@ fn registry_entry_to_app(
         source_path: Some(path),
         app_type: AppType::Win32,
         icon_b64,
+        icon_source: Some(icon_source),
+        icon_index: 0,
         description,
         keywords,
         working_directory: None,
         arguments: None,
+        installed_at,
+        uninstall_string,
     })
 }
 
+/// Parses the registry `InstallDate` value (`YYYYMMDD`) into a Unix timestamp at midnight UTC.
+fn parse_install_date(raw: &str) -> Option<i64> {
+    let digits = raw.trim();
+    if digits.len() != 8 || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let year: i64 = digits[0..4].parse().ok()?;
+    let month: i64 = digits[4..6].parse().ok()?;
+    let day: i64 = digits[6..8].parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn file_modified_unix(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    modified
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|elapsed| elapsed.as_secs() as i64)
+}
+
+/// Extracts an existing executable or icon file from a registry value such as `DisplayIcon`,
+/// accepting quoted paths, a `path,index` suffix, forward slashes and trailing arguments.
 fn sanitize_executable_path(raw: &str) -> Option<String> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return None;
     }
 
-    let without_quotes = trimmed.trim_matches(|c| c == '"' || c == '\'');
-    let candidate = without_quotes
-        .split(&[',', ';'][..])
-        .next()
-        .map(str::trim)?;
+    // 带引号的路径只取引号内的部分，其后的图标序号或参数一并丢弃
+    let candidate = match trimmed.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => trimmed.trim_matches('\'').split(&[',', ';'][..]).next()?,
+    }
+    .trim();
     if candidate.is_empty() {
         return None;
     }
 
-    let expanded = expand_env_vars(candidate).unwrap_or_else(|| candidate.to_string());
-    let path = Path::new(&expanded);
-    if path.is_file() {
-        Some(expanded)
-    } else {
-        None
+    // 先试完整路径，再从右向左在空格处截掉命令行参数，如 `app.exe /foo`
+    std::iter::once(candidate.len())
+        .chain(candidate.rmatch_indices(' ').map(|(index, _)| index))
+        .find_map(|end| existing_file_path(&candidate[..end]))
+}
+
+/// Splits a registry command line such as `UninstallString` into program and arguments.
+/// Unquoted programs may contain spaces, so the longest prefix naming an existing file wins;
+/// otherwise the first token is the program, e.g. `MsiExec.exe /X{...}`.
+pub(crate) fn split_command_line(raw: &str) -> (String, String) {
+    let trimmed = raw.trim();
+    if let Some(quoted) = trimmed.strip_prefix('"') {
+        if let Some((program, arguments)) = quoted.split_once('"') {
+            return (program.to_string(), arguments.trim().to_string());
+        }
     }
+
+    std::iter::once(trimmed.len())
+        .chain(trimmed.rmatch_indices(' ').map(|(index, _)| index))
+        .find_map(|end| {
+            existing_file_path(&trimmed[..end])
+                .map(|program| (program, trimmed[end..].trim().to_string()))
+        })
+        .unwrap_or_else(|| match trimmed.split_once(char::is_whitespace) {
+            Some((program, arguments)) => (program.to_string(), arguments.trim().to_string()),
+            None => (trimmed.to_string(), String::new()),
+        })
+}
+
+/// Expands environment variables, normalizes `/` to `\` and returns the path if it is a file.
+fn existing_file_path(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let expanded = expand_env_vars(raw)
+        .unwrap_or_else(|| raw.to_string())
+        .replace('/', "\\");
+    Path::new(&expanded).is_file().then_some(expanded)
 }
 
 fn fallback_executable_from_folder(raw: &str) -> Option<String> {
@@ -532,9 +1453,12 @@ fn looks_like_uninstaller(path: &str) -> bool {
     lower.contains("unins") || lower.contains("uninstall")
 }
 
-async fn enumerate_uwp_apps() -> WinResult<Vec<ApplicationInfo>> {
+/// Returns the UWP apps with a Start menu entry, and the apps declared by packages that have
+/// none (typically system components).
+async fn enumerate_uwp_apps() -> WinResult<(Vec<ApplicationInfo>, Vec<ApplicationInfo>)> {
     let manager = PackageManager::new()?;
     let mut applications = Vec::new();
+    let mut unlisted = Vec::new();
 
     let iterable = manager.FindPackages()?;
     let iterator = iterable.First()?;
@@ -542,10 +1466,20 @@ async fn enumerate_uwp_apps() -> WinResult<Vec<ApplicationInfo>> {
         let package = iterator.Current()?;
         iterator.MoveNext()?;
 
+        // WinRT DateTime 以 1601-01-01 起的 100ns 为单位
+        let installed_at = package
+            .InstalledDate()
+            .ok()
+            .map(|date| date.UniversalTime / 10_000_000 - 11_644_473_600);
+
         let entries_future = package.GetAppListEntriesAsync()?;
         let entries = entries_future.get()?;
 
         let size = entries.Size()?;
+        if size == 0 {
+            unlisted.extend(unlisted_package_apps(&package, installed_at));
+            continue;
+        }
         for index in 0..size {
             let entry = entries.GetAt(index)?;
 
@@ -564,6 +1498,7 @@ async fn enumerate_uwp_apps() -> WinResult<Vec<ApplicationInfo>> {
             }
             keywords.push(display_name.clone());
             keywords.push(app_id.clone());
+            keywords.extend(aumid_keywords(&app_id));
 
             if let Ok(package_id) = package.Id() {
                 if let Ok(name) = package_id.Name() {
@@ -590,15 +1525,190 @@ async fn enumerate_uwp_apps() -> WinResult<Vec<ApplicationInfo>> {
                 source_path: None,
                 app_type: AppType::Uwp,
                 icon_b64,
+                icon_source: None,
+                icon_index: 0,
                 description,
                 keywords,
                 working_directory: None,
                 arguments: None,
+                installed_at,
+                uninstall_string: None,
             });
         }
     }
 
-    Ok(applications)
+    Ok((applications, unlisted))
+}
+
+/// Builds entries for the applications declared in the manifest of a package without Start
+/// menu entries. Framework and resource packages are skipped as they never contain apps.
+fn unlisted_package_apps(package: &Package, installed_at: Option<i64>) -> Vec<ApplicationInfo> {
+    if package.IsFramework().unwrap_or(true) || package.IsResourcePackage().unwrap_or(true) {
+        return Vec::new();
+    }
+    let Ok(installed_path) = package.InstalledPath() else {
+        return Vec::new();
+    };
+    let manifest_path = Path::new(&installed_path.to_string()).join("AppxManifest.xml");
+    let Ok(manifest) = fs::read_to_string(manifest_path) else {
+        return Vec::new();
+    };
+    let Ok(package_id) = package.Id() else {
+        return Vec::new();
+    };
+    let Ok(family) = package_id.FamilyName().map(|value| value.to_string()) else {
+        return Vec::new();
+    };
+    let package_name = package_id
+        .Name()
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    let display_name = package
+        .DisplayName()
+        .map(|value| value.to_string())
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| package_name.clone());
+
+    let application_ids = manifest_application_ids(&manifest);
+    let multiple = application_ids.len() > 1;
+    application_ids
+        .into_iter()
+        .map(|application_id| {
+            let app_id = format!("{family}!{application_id}");
+            // 同一包声明多个应用时附上应用 Id 以便区分
+            let name = if multiple {
+                format!("{display_name} ({application_id})")
+            } else {
+                display_name.clone()
+            };
+            let mut keywords = vec![
+                name.clone(),
+                app_id.clone(),
+                package_name.clone(),
+                family.clone(),
+            ];
+            keywords.extend(aumid_keywords(&app_id));
+            keywords.retain(|value| !value.is_empty());
+            extend_keywords_with_pinyin(&mut keywords);
+            keywords.sort();
+            keywords.dedup();
+
+            ApplicationInfo {
+                id: format!("uwp:{}", app_id.to_lowercase()),
+                name,
+                path: app_id,
+                source_path: None,
+                app_type: AppType::Uwp,
+                icon_b64: String::new(),
+                icon_source: None,
+                icon_index: 0,
+                description: None,
+                keywords,
+                working_directory: None,
+                arguments: None,
+                installed_at,
+                uninstall_string: None,
+            }
+        })
+        .collect()
+}
+
+/// Splits an AppUserModelId such as `Microsoft.WindowsCalculator_8wekyb3d8bbwe!App` into
+/// segments that fuzzy-match better than the whole id: the package name, its dotted parts after
+/// the publisher (`WindowsCalculator`) and the application id after `!`.
+fn aumid_keywords(app_id: &str) -> Vec<String> {
+    let (family, application_id) = app_id.split_once('!').unwrap_or((app_id, ""));
+    let package_name = family.split_once('_').map_or(family, |(name, _)| name);
+
+    let mut keywords = vec![package_name.to_string()];
+    keywords.extend(
+        package_name
+            .split('.')
+            .skip(1)
+            .filter(|part| part.len() > 1)
+            .map(str::to_string),
+    );
+    // 大多数包的应用 Id 都是泛泛的 "App"，作为关键词没有区分度
+    if !application_id.is_empty() && !application_id.eq_ignore_ascii_case("app") {
+        keywords.push(application_id.to_string());
+    }
+    keywords
+}
+
+/// Extracts the `Id` attribute of every `<Application>` element in an AppX manifest.
+fn manifest_application_ids(manifest: &str) -> Vec<String> {
+    manifest
+        .match_indices("<Application ")
+        .filter_map(|(start, _)| {
+            let rest = &manifest[start..];
+            let tag = &rest[..rest.find('>')?];
+            let value_start = tag
+                .match_indices("Id=\"")
+                .find(|(index, _)| {
+                    tag[..*index]
+                        .chars()
+                        .next_back()
+                        .is_some_and(char::is_whitespace)
+                })
+                .map(|(index, pattern)| index + pattern.len())?;
+            let value_len = tag[value_start..].find('"')?;
+            Some(tag[value_start..value_start + value_len].to_string())
+        })
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Loads an application's icon, preferring the on-disk icon cache. Used to lazily restore
+/// icons that were dropped from memory by the index size limits.
+pub fn load_icon(app: &ApplicationInfo) -> Option<String> {
+    match app.app_type {
+        AppType::Uwp => load_uwp_logo_by_app_id(&app.path),
+        AppType::Win32 | AppType::Folder | AppType::Script => {
+            let source = app.icon_source.as_deref()?;
+            extract_icon_from_path(source, app.icon_index)
+        }
+    }
+}
+
+/// Re-extracts an application's icon, bypassing the on-disk icon cache.
+pub fn reload_icon(app: &ApplicationInfo) -> Option<String> {
+    match app.app_type {
+        AppType::Uwp => load_uwp_logo_by_app_id(&app.path),
+        AppType::Win32 | AppType::Folder | AppType::Script => {
+            let source = app.icon_source.as_deref()?;
+            refresh_icon_from_path(source, app.icon_index)
+        }
+    }
+}
+
+fn load_uwp_logo_by_app_id(app_id: &str) -> Option<String> {
+    let manager = PackageManager::new().ok()?;
+    let iterator = manager.FindPackages().ok()?.First().ok()?;
+    while iterator.HasCurrent().unwrap_or(false) {
+        let package = iterator.Current().ok()?;
+        let _ = iterator.MoveNext();
+
+        let Ok(entries) = package.GetAppListEntriesAsync().and_then(|op| op.get()) else {
+            continue;
+        };
+        let size = entries.Size().unwrap_or(0);
+        for index in 0..size {
+            let Ok(entry) = entries.GetAt(index) else {
+                continue;
+            };
+            let matches = entry
+                .AppUserModelId()
+                .map(|value| value.to_string().eq_ignore_ascii_case(app_id))
+                .unwrap_or(false);
+            if matches {
+                let display_info = entry.DisplayInfo().ok()?;
+                return load_uwp_logo(&display_info);
+            }
+        }
+    }
+
+    None
 }
 
 fn load_uwp_logo(display_info: &windows::ApplicationModel::AppDisplayInfo) -> Option<String> {
@@ -629,3 +1739,123 @@ fn load_uwp_logo(display_info: &windows::ApplicationModel::AppDisplayInfo) -> Op
 
     Some(BASE64.encode(buffer))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, path: &str, arguments: Option<&str>) -> ApplicationInfo {
+        ApplicationInfo {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            path: path.to_string(),
+            source_path: None,
+            app_type: AppType::Win32,
+            icon_b64: String::new(),
+            icon_source: None,
+            icon_index: 0,
+            description: None,
+            keywords: Vec::new(),
+            working_directory: None,
+            arguments: arguments.map(str::to_string),
+            installed_at: None,
+            uninstall_string: None,
+        }
+    }
+
+    #[test]
+    fn dedup_keeps_shortcuts_with_different_arguments() {
+        let word = r"C:\Office\WINWORD.EXE";
+        let mut registry = app("Microsoft Word", word, None);
+        registry.uninstall_string = Some(r"C:\Office\setup.exe /uninstall".into());
+        let deduped = dedup_applications(vec![
+            app("Word", word, None),
+            app("Word (Safe Mode)", word, Some("/safe")),
+            registry,
+        ]);
+
+        let names: Vec<&str> = deduped.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(names, ["Word", "Word (Safe Mode)"]);
+        // 同一目标的注册表项被合并，但卸载命令保留下来
+        assert!(deduped[0].uninstall_string.is_some());
+        assert!(deduped[1].uninstall_string.is_none());
+    }
+
+    #[test]
+    fn sanitize_executable_path_strips_icon_index_and_arguments() {
+        let root = env::temp_dir().join(format!("egg-test-{}", std::process::id()));
+        let dir = root.join("Program Files").join("App");
+        fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("app.exe");
+        fs::write(&exe, b"").unwrap();
+        let exe = exe.to_string_lossy().into_owned();
+
+        // DisplayIcon 常见写法：正斜杠、带引号并附带图标序号
+        let quoted = sanitize_executable_path(&format!("\"{}\",0", exe.replace('\\', "/")));
+        let with_arguments = sanitize_executable_path(&format!("{exe} /foo"));
+        let missing = sanitize_executable_path(&format!("{exe}.missing /foo"));
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(quoted.as_deref(), Some(exe.as_str()));
+        assert_eq!(with_arguments.as_deref(), Some(exe.as_str()));
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn aumid_keywords_include_package_segments() {
+        let keywords = aumid_keywords("Microsoft.WindowsCalculator_8wekyb3d8bbwe!App");
+        assert_eq!(
+            keywords,
+            ["Microsoft.WindowsCalculator", "WindowsCalculator"]
+        );
+
+        let keywords = aumid_keywords("Microsoft.Office.OneNote_8wekyb3d8bbwe!onenoteim");
+        assert_eq!(
+            keywords,
+            ["Microsoft.Office.OneNote", "Office", "OneNote", "onenoteim"]
+        );
+    }
+
+    #[test]
+    fn only_the_latest_generation_stays_current() {
+        let latest = Arc::new(AtomicU64::new(0));
+        let first = IndexGeneration::next(&latest);
+        let in_flight = first.clone();
+        assert!(!first.is_superseded());
+
+        let second = IndexGeneration::next(&latest);
+        assert!(first.is_superseded());
+        assert!(in_flight.is_superseded());
+        assert!(!second.is_superseded());
+        assert_eq!(first.ensure_current(), None);
+        assert_eq!(second.ensure_current(), Some(()));
+
+        let third = IndexGeneration::next(&latest);
+        assert!(second.is_superseded());
+        assert!(!third.is_superseded());
+    }
+
+    fn allowed(path: &str, roots: &[&str]) -> bool {
+        let roots: Vec<PathBuf> = roots.iter().map(|root| normalized_path(root)).collect();
+        is_in_allowed_dir(&app("App", path, None), &roots)
+    }
+
+    #[test]
+    fn allowlist_ignores_case() {
+        assert!(allowed(r"C:\Apps\Tool\tool.exe", &[r"c:\apps"]));
+        assert!(allowed(r"c:\apps\tool\tool.exe", &[r"C:\APPS"]));
+    }
+
+    #[test]
+    fn allowlist_ignores_trailing_separators() {
+        assert!(allowed(r"C:\Apps\tool.exe", &[r"C:\Apps\"]));
+        assert!(allowed(r"C:\Apps\tool.exe", &["C:/Apps/"]));
+    }
+
+    #[test]
+    fn allowlist_matches_whole_path_components() {
+        assert!(!allowed(r"C:\Apps2\tool.exe", &[r"C:\Apps"]));
+        assert!(allowed(r"C:\Apps2\tool.exe", &[r"C:\Apps", r"C:\Apps2"]));
+        assert!(!allowed(r"D:\Apps\tool.exe", &[r"C:\Apps"]));
+    }
+}
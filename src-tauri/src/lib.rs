@@ -1,34 +1,68 @@
+mod actions;
 mod bookmarks;
 mod commands;
 mod config;
+mod crash;
+mod elevation;
 mod hotkey;
 mod hotkey_capture;
 mod indexer;
 mod models;
 mod state;
 mod text_utils;
+mod usage;
 mod windows_utils;
 
 use commands::{
-    begin_hotkey_capture, end_hotkey_capture, execute_action, get_settings, submit_query,
-    trigger_reindex, update_hotkey, update_settings, FOCUS_INPUT_EVENT, HIDE_WINDOW_EVENT,
-    OPEN_SETTINGS_EVENT,
+    add_custom_launcher, begin_hotkey_capture, benchmark_query, clear_score_override,
+    diagnose_query, end_hotkey_capture, ensure_english_input, execute_action, execute_nth,
+    export_index, filter_results, get_config_path, get_data_dir, get_last_crash, get_modes,
+    get_result_details, get_settings, get_usage_stats, hide_app, is_index_ready,
+    list_chrome_profiles, list_hidden, preview_icon, prune_usage_stats, rebind_hotkey,
+    refresh_icon, remove_custom_launcher, restart_app, restore_input, set_score_override,
+    submit_query, switch_profile, trigger_reindex, unhide_app, update_hotkey, update_settings,
+    validate_hotkey, FOCUS_INPUT_EVENT, HIDE_WINDOW_EVENT, OPEN_SETTINGS_EVENT,
 };
 use config::AppConfig;
-use hotkey::bind_hotkey;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+use hotkey::{bind_hotkey, bind_quick_open_hotkey, unbind_hotkey};
 use log::warn;
 use state::AppState;
-use tauri::{menu::MenuBuilder, tray::TrayIconBuilder, AppHandle, Emitter, Manager};
+use tauri::{
+    menu::MenuBuilder, tray::TrayIconBuilder, AppHandle, Emitter, Manager, RunEvent, WebviewWindow,
+};
 
-const MAIN_WINDOW_LABEL: &str = "main";
+pub(crate) const MAIN_WINDOW_LABEL: &str = "main";
 const TRAY_ID: &str = "main-tray";
 const MENU_SHOW: &str = "tray-show";
 const MENU_HIDE: &str = "tray-hide";
 const MENU_SETTINGS: &str = "tray-settings";
 const MENU_QUIT: &str = "tray-quit";
 
+/// Upper bound on how long exit waits for state to be written to disk.
+const EXIT_SAVE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Wait after a resume before re-registering hotkeys, as registration can fail right after wake.
+const RESUME_REBIND_DELAY: Duration = Duration::from_secs(2);
+
+static MAIN_WINDOW_MISSING_WARNED: AtomicBool = AtomicBool::new(false);
+static EXIT_PREPARED: AtomicBool = AtomicBool::new(false);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 以提权代理身份启动时不创建窗口，只处理提权请求
+    if elevation::run_broker_if_requested() {
+        return;
+    }
+    crash::install_hook();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
@@ -40,32 +74,87 @@ pub fn run() {
             submit_query,
             execute_action,
             trigger_reindex,
+            refresh_icon,
             get_settings,
             begin_hotkey_capture,
             end_hotkey_capture,
             update_hotkey,
-            update_settings
+            update_settings,
+            add_custom_launcher,
+            remove_custom_launcher,
+            restart_app,
+            get_usage_stats,
+            prune_usage_stats,
+            hide_app,
+            unhide_app,
+            list_hidden,
+            get_config_path,
+            get_data_dir,
+            validate_hotkey,
+            ensure_english_input,
+            restore_input,
+            benchmark_query,
+            filter_results,
+            list_chrome_profiles,
+            export_index,
+            get_modes,
+            diagnose_query,
+            set_score_override,
+            clear_score_override,
+            get_last_crash,
+            rebind_hotkey,
+            is_index_ready,
+            get_result_details,
+            preview_icon,
+            execute_nth,
+            switch_profile
         ])
         .setup(|app| {
             let handle = app.handle();
             let state = app.state::<AppState>();
 
             let config = AppConfig::load(handle);
-            if let Ok(mut guard) = state.config.lock() {
-                *guard = config.clone();
+            state.replace_config(config.clone());
+            state.configure_matcher(config.smart_case);
+            elevation::configure(config.use_elevation_broker);
+            crash::configure(config.capture_crashes || config.debug_mode);
+
+            if let Ok(mut guard) = state.usage_stats.lock() {
+                *guard = usage::UsageStats::load(handle);
             }
 
             if let Err(err) = windows_utils::configure_launch_on_startup(config.launch_on_startup) {
                 warn!("failed to sync launch-on-startup setting: {err}");
             }
 
-            if let Err(err) = bind_hotkey(handle, &state, &config.global_hotkey, MAIN_WINDOW_LABEL)
-            {
+            if let Err(err) = bind_hotkey(handle, &state, &config.global_hotkey) {
                 warn!(
                     "failed to register global shortcut {}: {}",
                     config.global_hotkey, err
                 );
             }
+            if let Err(err) = bind_quick_open_hotkey(handle, &state, &config.quick_open_hotkey) {
+                warn!(
+                    "failed to register quick-open shortcut {}: {}",
+                    config.quick_open_hotkey, err
+                );
+            }
+
+            let resume_handle = handle.clone();
+            windows_utils::watch_system_resume(move |reason| {
+                log::info!("system resumed ({reason:?}), rebinding hotkeys");
+                let handle = resume_handle.clone();
+                thread::spawn(move || {
+                    thread::sleep(RESUME_REBIND_DELAY);
+                    if EXIT_PREPARED.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Some(state) = handle.try_state::<AppState>() {
+                        // 失败时 rebind_hotkeys 已记录日志
+                        let _ = hotkey::rebind_hotkeys(&handle, &state);
+                    }
+                });
+            });
 
             let tray_menu = MenuBuilder::new(app)
                 .text(MENU_SHOW, "显示窗口")
@@ -95,6 +184,7 @@ pub fn run() {
                         let _ = app_handle.emit(OPEN_SETTINGS_EVENT, ());
                     }
                     MENU_QUIT => {
+                        prepare_for_exit(app_handle);
                         app_handle.exit(0);
                     }
                     _ => {}
@@ -111,46 +201,126 @@ pub fn run() {
                 if let WindowEvent::Focused(false) = event {
                     let app_handle = window.app_handle();
 
-                    // 通知前端重置搜索状态
-                    let _ = app_handle.emit(HIDE_WINDOW_EVENT, ());
-
                     // 恢复之前保存的输入法
-                    if let Some(state) = app_handle.try_state::<AppState>() {
-                        if let Ok(mut guard) = state.saved_ime.lock() {
-                            if let Some(layout_id) = *guard {
-                                windows_utils::restore_input_method(layout_id);
-                                *guard = None; // Clear after restore
-                            }
-                        }
-                    }
+                    restore_saved_input_method(app_handle);
 
-                    // 隐藏主窗口
-                    if let Some(main_window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) {
-                        let _ = main_window.hide();
-                    }
+                    // 通知前端重置搜索状态并隐藏主窗口
+                    hide_main_window(app_handle);
                 }
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let RunEvent::ExitRequested { .. } = event {
+                prepare_for_exit(app_handle);
+            }
+        });
+}
+
+/// Looks up the launcher's main window, logging a single warning the first time it is missing.
+pub(crate) fn main_window(app_handle: &AppHandle) -> Option<WebviewWindow> {
+    let window = app_handle.get_webview_window(MAIN_WINDOW_LABEL);
+    if window.is_none() && !MAIN_WINDOW_MISSING_WARNED.swap(true, Ordering::Relaxed) {
+        warn!("main window `{MAIN_WINDOW_LABEL}` not found, window operations will be skipped");
+    }
+    window
+}
+
+/// Tells the frontend to reset its search state and hides the main window if it exists.
+pub(crate) fn hide_main_window(app_handle: &AppHandle) {
+    let _ = app_handle.emit(HIDE_WINDOW_EVENT, ());
+    if let Some(window) = main_window(app_handle) {
+        let _ = window.hide();
+    }
+}
+
+/// Restores the keyboard layout saved when the window was shown, if any.
+pub(crate) fn restore_saved_input_method(app_handle: &AppHandle) {
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        if let Ok(mut guard) = state.saved_ime.lock() {
+            if let Some(layout_id) = guard.take() {
+                windows_utils::restore_input_method(layout_id);
+            }
+        }
+    }
+}
+
+/// Saves the current keyboard layout into `saved_ime` and switches to English (US).
+pub(crate) fn force_english_input(state: &AppState) -> Result<(), String> {
+    {
+        let mut guard = state
+            .saved_ime
+            .lock()
+            .map_err(|_| "无法获取输入法状态".to_string())?;
+        // Only save if we haven't saved yet (to avoid overwriting with English layout)
+        if guard.is_none() {
+            *guard = windows_utils::get_current_input_method();
+        }
+    }
+    windows_utils::switch_to_english_input_method()
+}
+
+/// Releases global resources before the process exits or restarts: stops any hotkey capture,
+/// unregisters the launcher hotkey and persists the current config and usage stats. Icon cache
+/// entries are written synchronously during extraction and COM is scoped per call via
+/// `ComGuard`, so neither needs flushing here.
+///
+/// Runs at most once per process; the disk writes are bounded by [`EXIT_SAVE_TIMEOUT`].
+pub(crate) fn prepare_for_exit(app_handle: &AppHandle) {
+    if EXIT_PREPARED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if let Err(err) = hotkey_capture::stop() {
+        warn!("failed to stop hotkey capture before exit: {err}");
+    }
+
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    unbind_hotkey(app_handle, &state);
+
+    // 写盘放到后台线程，超时则直接退出，避免卡住关闭流程
+    let handle = app_handle.clone();
+    let state = state.inner().clone();
+    let (done_tx, done_rx) = mpsc::channel();
+    thread::spawn(move || {
+        if let Err(err) = state.config_snapshot().save(&handle) {
+            warn!("failed to save config before exit: {err}");
+        }
+        if let Ok(stats) = state.usage_stats.lock() {
+            if let Err(err) = stats.save(&handle) {
+                warn!("failed to save usage stats before exit: {err}");
+            }
+        }
+        let _ = done_tx.send(());
+    });
+    if done_rx.recv_timeout(EXIT_SAVE_TIMEOUT).is_err() {
+        warn!("saving state before exit timed out");
+    }
 }
 
 pub(crate) fn show_window(app_handle: &AppHandle) {
-    if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) {
+    if let Some(window) = main_window(app_handle) {
+        // 记下显示前的前台窗口，粘贴操作需要把焦点还给它
+        if !window.is_visible().unwrap_or(false) {
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                if let Ok(mut guard) = state.previous_foreground.lock() {
+                    *guard = windows_utils::foreground_window();
+                }
+            }
+        }
         let _ = window.show();
         let _ = window.set_focus();
         
         // Save current IME and switch to English if needed
         if should_force_english_input(app_handle) {
             if let Some(state) = app_handle.try_state::<AppState>() {
-                if let Ok(mut guard) = state.saved_ime.lock() {
-                    // Only save if we haven't saved yet (to avoid overwriting with English layout)
-                    if guard.is_none() {
-                        *guard = windows_utils::get_current_input_method();
-                    }
+                if let Err(err) = force_english_input(&state) {
+                    warn!("failed to switch to English input: {err}");
                 }
             }
-            windows_utils::switch_to_english_input_method();
         }
         
         let _ = app_handle.emit(FOCUS_INPUT_EVENT, ());
@@ -160,6 +330,6 @@ pub(crate) fn show_window(app_handle: &AppHandle) {
 fn should_force_english_input(app_handle: &AppHandle) -> bool {
     app_handle
         .try_state::<AppState>()
-        .and_then(|state| state.config.lock().ok().map(|cfg| cfg.force_english_input))
+        .map(|state| state.config_snapshot().force_english_input)
         .unwrap_or(true)
 }
@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::CustomLauncher;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum AppType {
     Win32,
     Uwp,
+    Folder,
+    Script,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,18 +18,183 @@ pub struct ApplicationInfo {
     pub source_path: Option<String>,
     pub app_type: AppType,
     pub icon_b64: String,
+    /// File the icon was extracted from, kept so it can be re-extracted on demand.
+    pub icon_source: Option<String>,
+    pub icon_index: i32,
     pub description: Option<String>,
     pub keywords: Vec<String>,
     pub working_directory: Option<String>,
     pub arguments: Option<String>,
+    /// Unix timestamp (seconds) of when the app was installed, if known.
+    pub installed_at: Option<i64>,
+    /// `UninstallString` of the app's registry uninstall entry, if known.
+    pub uninstall_string: Option<String>,
+}
+
+/// A query mode as listed by `get_modes`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeInfo {
+    /// Value passed back to `submit_query` as `mode`.
+    pub id: String,
+    pub name: String,
+    /// Input prefix that switches to this mode; `None` for the default mode.
+    pub prefix: Option<String>,
+    /// False when the mode's result source is turned off in the settings.
+    pub enabled: bool,
+}
+
+/// Timings returned by `benchmark_query`, in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub iterations: u32,
+    /// Apps, custom launchers and bookmarks scanned per iteration.
+    pub entry_count: usize,
+    /// Matches found per iteration.
+    pub result_count: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Per-source counts reported by `diagnose_query`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceDiagnostics {
+    /// Entries scanned; zero when the source was skipped for this query.
+    pub considered: usize,
+    pub matched: usize,
+    pub top_score: Option<i64>,
+}
+
+impl SourceDiagnostics {
+    pub fn record(&mut self, score: i64) {
+        self.matched += 1;
+        self.top_score = Some(self.top_score.map_or(score, |top| top.max(score)));
+    }
+}
+
+/// What `diagnose_query` observed while running a query through the `submit_query` path.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryDiagnostics {
+    pub mode: String,
+    pub apps: SourceDiagnostics,
+    pub custom_launchers: SourceDiagnostics,
+    pub bookmarks: SourceDiagnostics,
+    /// The query looked like a URL or a local path.
+    pub url_detected: bool,
+    pub web_search_offered: bool,
+    /// The query exceeded the matchable length and fuzzy matching was skipped.
+    pub matching_skipped: bool,
+    /// Results returned, including URL and web search entries.
+    pub result_count: usize,
+    pub elapsed_ms: f64,
+}
+
+/// Outcome of `execute_action`, letting the frontend ask for confirmation before retrying.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteOutcome {
+    Executed,
+    /// Opened without taking focus; the launcher window stays open.
+    ExecutedInBackground,
+    /// Executed with `hide_after_launch` disabled; the launcher stays open with a cleared input.
+    ExecutedKeepOpen,
+    NeedsConfirmation,
+}
+
+/// Full information behind one result, returned by `get_result_details` for a details pane so
+/// query results don't have to carry it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResultDetails {
+    /// `icon_b64` is left empty; the result already carries the icon.
+    Application(ApplicationInfo),
+    Bookmark {
+        title: String,
+        url: String,
+        /// Folder names joined with the configured `folder_separator`.
+        folder_path: String,
+        keywords: Vec<String>,
+    },
+    CustomLauncher(CustomLauncher),
+    Url {
+        url: String,
+    },
+    Path {
+        path: String,
+    },
+    Command {
+        program: String,
+        arguments: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
+    /// Deterministic for the same entry or input, so the frontend can keep selection across
+    /// queries. Forms: `app-<app id>`, `custom-<launcher id>`, `bookmark-<bookmark id>`, and
+    /// `url-`/`path-`/`run-`/`search-` followed by a hash of the URL, path or command.
+    /// Secondary actions append a suffix such as `:edit`; `indexing` is the placeholder and
+    /// `truncated` the hint about results cut off by the limit.
     pub id: String,
     pub title: String,
     pub subtitle: String,
     pub icon: String,
     pub score: i64,
     pub action_id: String,
+    /// Section the result belongs to, see [`result_group`].
+    pub group: String,
+    /// Score components, only filled in when `debug_mode` is enabled.
+    pub score_breakdown: Option<ScoreBreakdown>,
+    /// How often the underlying app or bookmark was launched; `None` without usage stats.
+    pub launch_count: Option<u32>,
+}
+
+/// Components that add up to a result's `score`, for diagnosing ranking.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreBreakdown {
+    /// Raw fuzzy score of the winning field.
+    pub base_fuzzy: i64,
+    /// Penalty subtracted because a keyword or secondary field matched instead of the title.
+    pub keyword_penalty: i64,
+    /// Bonus from launch history. The current ranking doesn't use it, so it is always 0.
+    pub frecency_bonus: i64,
+    /// Prefix or substring bonus from [`ScoringWeights`](crate::config::ScoringWeights).
+    pub prefix_boost: i64,
+    /// Temporary bonus for entries launched earlier in this session.
+    pub session_boost: i64,
+    /// User-configured delta from `score_overrides`.
+    pub score_override: i64,
+    /// Penalty for apps matching the configured `deprioritize_patterns`.
+    pub deprioritize_penalty: i64,
+}
+
+impl ScoreBreakdown {
+    /// Sum of the components, equal to the result's `score`.
+    pub fn total(&self) -> i64 {
+        self.base_fuzzy - self.keyword_penalty
+            + self.frecency_bonus
+            + self.prefix_boost
+            + self.session_boost
+            + self.score_override
+            - self.deprioritize_penalty
+    }
+}
+
+/// Maps an `action_id` to a stable group identifier so frontends can render localized
+/// section headers. The set of identifiers is fixed:
+///
+/// - `application`: apps, folders, scripts, custom launchers and typed paths (`app`, `uwp`,
+///   `folder`, `script`, `custom`, `path`)
+/// - `bookmark`: browser bookmarks (`bookmark`)
+/// - `web`: URLs and web searches (`url`, `search`)
+/// - `command`: shell commands from run mode (`run`)
+/// - `other`: anything else
+pub fn result_group(action_id: &str) -> &'static str {
+    match action_id {
+        "app" | "uwp" | "folder" | "script" | "custom" | "path" => "application",
+        "bookmark" => "bookmark",
+        "url" | "search" => "web",
+        "run" => "command",
+        _ => "other",
+    }
 }
@@ -1,27 +1,66 @@
 use std::{
-    collections::HashMap,
-    sync::{atomic::AtomicBool, Arc, Mutex},
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use crate::{bookmarks::BookmarkEntry, config::AppConfig, models::ApplicationInfo};
+use fuzzy_matcher::skim::SkimMatcherV2;
 
-#[derive(Clone)]
-pub enum PendingAction {
-    Application(ApplicationInfo),
-    Bookmark(BookmarkEntry),
-    Url(String),
-    Search(String),
-}
+use crate::{
+    actions::PendingAction,
+    bookmarks::{BookmarkCache, BookmarkEntry},
+    config::AppConfig,
+    models::{ApplicationInfo, SearchResult},
+    usage::UsageStats,
+};
+
+/// Number of launched ids remembered for the session boost.
+pub const MAX_RECENT_LAUNCHES: usize = 5;
 
 #[derive(Default, Clone)]
 pub struct AppState {
     pub app_index: Arc<Mutex<Vec<ApplicationInfo>>>,
     pub bookmark_index: Arc<Mutex<Vec<BookmarkEntry>>>,
-    pub config: Arc<Mutex<AppConfig>>,
+    /// Parsed bookmark files reused by `trigger_reindex` while they are unchanged on disk.
+    pub bookmark_cache: Arc<Mutex<BookmarkCache>>,
+    /// Current configuration. Readers take a cheap `Arc` clone via [`AppState::config_snapshot`];
+    /// writers build a new `AppConfig` and swap it in.
+    pub config: Arc<Mutex<Arc<AppConfig>>>,
     pub registered_hotkey: Arc<Mutex<Option<String>>>,
+    pub registered_quick_open_hotkey: Arc<Mutex<Option<String>>>,
     pub pending_actions: Arc<Mutex<HashMap<String, PendingAction>>>,
+    /// Result ids of the most recent `submit_query` in the order they were returned, used by
+    /// `execute_nth`.
+    pub result_order: Arc<Mutex<Vec<String>>>,
+    /// Results of the most recent `submit_query`, used by `filter_results`.
+    pub last_results: Arc<Mutex<Vec<SearchResult>>>,
     pub hotkey_capture_suspended: Arc<AtomicBool>,
     pub saved_ime: Arc<Mutex<Option<isize>>>,
+    /// Window that had focus before the launcher was shown, as an `HWND`; paste actions
+    /// return focus to it.
+    pub previous_foreground: Arc<Mutex<Option<isize>>>,
+    pub usage_stats: Arc<Mutex<UsageStats>>,
+    /// Fuzzy matcher shared by all queries, rebuilt when case settings change.
+    pub matcher: Arc<Mutex<Arc<SkimMatcherV2>>>,
+    /// Cancellation flag of the most recent `submit_query` call.
+    pub query_cancel: Arc<Mutex<Arc<AtomicBool>>>,
+    /// Set while `trigger_reindex` is rebuilding the indexes.
+    pub reindex_in_progress: Arc<AtomicBool>,
+    /// Generation of the most recent `trigger_reindex`; older runs stop once it moves on.
+    pub reindex_generation: Arc<AtomicU64>,
+    /// Set once the first `trigger_reindex` has populated the indexes; never cleared.
+    pub index_ready: Arc<AtomicBool>,
+    /// Unfiltered UWP apps searched by the `sys` mode, rebuilt with the app index.
+    pub system_uwp_index: Arc<Mutex<Vec<ApplicationInfo>>>,
+    /// Application and bookmark ids launched in this session, most recent first. Not persisted.
+    pub recent_launches: Arc<Mutex<VecDeque<String>>>,
+    /// Text and mode id of the most recent non-empty `submit_query`, replayed by the quick-open
+    /// hotkey.
+    pub last_query: Arc<Mutex<Option<(String, String)>>>,
+    /// Extracted custom launcher icons keyed by icon source path, empty when extraction failed.
+    pub custom_launcher_icons: Arc<Mutex<HashMap<String, String>>>,
 }
 
 impl AppState {
@@ -29,11 +68,125 @@ impl AppState {
         Self {
             app_index: Arc::new(Mutex::new(Vec::new())),
             bookmark_index: Arc::new(Mutex::new(Vec::new())),
-            config: Arc::new(Mutex::new(AppConfig::default())),
+            bookmark_cache: Arc::new(Mutex::new(BookmarkCache::default())),
+            config: Arc::new(Mutex::new(Arc::new(AppConfig::default()))),
             registered_hotkey: Arc::new(Mutex::new(None)),
+            registered_quick_open_hotkey: Arc::new(Mutex::new(None)),
             pending_actions: Arc::new(Mutex::new(HashMap::new())),
+            result_order: Arc::new(Mutex::new(Vec::new())),
+            last_results: Arc::new(Mutex::new(Vec::new())),
             hotkey_capture_suspended: Arc::new(AtomicBool::new(false)),
             saved_ime: Arc::new(Mutex::new(None)),
+            previous_foreground: Arc::new(Mutex::new(None)),
+            usage_stats: Arc::new(Mutex::new(UsageStats::default())),
+            matcher: Arc::new(Mutex::new(Arc::new(build_matcher(true)))),
+            query_cancel: Arc::new(Mutex::new(Arc::new(AtomicBool::new(false)))),
+            reindex_in_progress: Arc::new(AtomicBool::new(false)),
+            reindex_generation: Arc::new(AtomicU64::new(0)),
+            index_ready: Arc::new(AtomicBool::new(false)),
+            system_uwp_index: Arc::new(Mutex::new(Vec::new())),
+            recent_launches: Arc::new(Mutex::new(VecDeque::new())),
+            last_query: Arc::new(Mutex::new(None)),
+            custom_launcher_icons: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Moves `id` to the front of the session launch list, dropping the oldest beyond the cap.
+    pub fn note_launch(&self, id: &str) {
+        if let Ok(mut guard) = self.recent_launches.lock() {
+            guard.retain(|existing| existing != id);
+            guard.push_front(id.to_string());
+            guard.truncate(MAX_RECENT_LAUNCHES);
+        }
+    }
+
+    pub fn recent_launches_snapshot(&self) -> Vec<String> {
+        self.recent_launches
+            .lock()
+            .map(|guard| guard.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Cancels the in-flight query, if any, and returns the flag for a new one.
+    pub fn begin_query(&self) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut guard) = self.query_cancel.lock() {
+            guard.store(true, Ordering::Relaxed);
+            *guard = Arc::clone(&flag);
+        }
+        flag
+    }
+
+    /// Returns the shared fuzzy matcher.
+    pub fn matcher_snapshot(&self) -> Arc<SkimMatcherV2> {
+        self.matcher
+            .lock()
+            .map(|guard| Arc::clone(&guard))
+            .unwrap_or_else(|_| Arc::new(build_matcher(true)))
+    }
+
+    /// Rebuilds the shared matcher with the given case sensitivity mode.
+    pub fn configure_matcher(&self, smart_case: bool) {
+        if let Ok(mut guard) = self.matcher.lock() {
+            *guard = Arc::new(build_matcher(smart_case));
+        }
+    }
+
+    /// Returns the current configuration without deep-cloning it.
+    pub fn config_snapshot(&self) -> Arc<AppConfig> {
+        self.config
+            .lock()
+            .map(|guard| Arc::clone(&guard))
+            .unwrap_or_default()
+    }
+
+    /// Returns the configuration with the active profile's overrides applied, for reading
+    /// settings. Edits go through [`config_snapshot`](Self::config_snapshot), the base config.
+    pub fn effective_config(&self) -> Arc<AppConfig> {
+        let config = self.config_snapshot();
+        if config.profiles.contains_key(&config.active_profile) {
+            Arc::new(config.effective())
+        } else {
+            config
         }
     }
+
+    /// Replaces the current configuration.
+    pub fn replace_config(&self, config: AppConfig) {
+        if let Ok(mut guard) = self.config.lock() {
+            *guard = Arc::new(config);
+        }
+    }
+}
+
+fn build_matcher(smart_case: bool) -> SkimMatcherV2 {
+    let matcher = SkimMatcherV2::default();
+    if smart_case {
+        matcher.smart_case()
+    } else {
+        matcher.ignore_case()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fuzzy_matcher::FuzzyMatcher;
+
+    use super::*;
+
+    #[test]
+    fn smart_case_only_distinguishes_case_for_uppercase_queries() {
+        let matcher = build_matcher(true);
+        assert!(matcher.fuzzy_match("Chrome", "chrome").is_some());
+        assert!(matcher.fuzzy_match("chrome beta", "chrome").is_some());
+
+        let exact_case = matcher
+            .fuzzy_match("Chrome", "Chrome")
+            .expect("exact case matches");
+        let other_case = matcher.fuzzy_match("chrome beta", "Chrome");
+        assert!(other_case.is_none_or(|score| score < exact_case));
+
+        let ignore_case = build_matcher(false);
+        assert!(ignore_case.fuzzy_match("chrome beta", "Chrome").is_some());
+    }
 }
@@ -1,4 +1,38 @@
 use pinyin::ToPinyin;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Lowercases the input and strips diacritics, so "Résumé" folds to "resume".
+pub fn fold_diacritics(value: &str) -> String {
+    value
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Builds the key used to order titles under `locale`. Chinese locales (`zh`, `zh-CN`, ...)
+/// spell CJK characters out in pinyin, so "苹果" sorts as "pingguo" among Latin titles
+/// instead of after all of them; other locales only fold case and diacritics.
+pub fn collation_key(value: &str, locale: &str) -> String {
+    let folded = fold_diacritics(value);
+    if !is_chinese_locale(locale) {
+        return folded;
+    }
+
+    let mut key = String::with_capacity(folded.len());
+    for (ch, pinyin) in folded.chars().zip(folded.as_str().to_pinyin()) {
+        match pinyin {
+            Some(pinyin) => key.push_str(pinyin.plain()),
+            None => key.push(ch),
+        }
+    }
+    key
+}
+
+fn is_chinese_locale(locale: &str) -> bool {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    language.eq_ignore_ascii_case("zh")
+}
 
 /// Extend the given keyword list with pinyin variants so that
 /// fuzzy matching can work with full pinyin and initials.
@@ -63,3 +97,254 @@ fn extend_single_keyword(source: &str, target: &mut Vec<String>) {
         target.push(initials);
     }
 }
+
+/// Hepburn romaji for hiragana U+3041..=U+3096; katakana maps onto it by offset.
+const KANA_ROMAJI: [&str; 86] = [
+    "a", "a", "i", "i", "u", "u", "e", "e", "o", "o", // ぁ-お
+    "ka", "ga", "ki", "gi", "ku", "gu", "ke", "ge", "ko", "go", // か-ご
+    "sa", "za", "shi", "ji", "su", "zu", "se", "ze", "so", "zo", // さ-ぞ
+    "ta", "da", "chi", "ji", "tsu", "tsu", "zu", "te", "de", "to", "do", // た-ど
+    "na", "ni", "nu", "ne", "no", // な-の
+    "ha", "ba", "pa", "hi", "bi", "pi", "fu", "bu", "pu", // は-ぷ
+    "he", "be", "pe", "ho", "bo", "po", // へ-ぽ
+    "ma", "mi", "mu", "me", "mo", // ま-も
+    "ya", "ya", "yu", "yu", "yo", "yo", // ゃ-よ
+    "ra", "ri", "ru", "re", "ro", // ら-ろ
+    "wa", "wa", "i", "e", "o", "n", "vu", "ka", "ke", // ゎ-ゖ
+];
+const HANGUL_INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+const HANGUL_MEDIALS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+const HANGUL_FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+const HIRAGANA_START: u32 = 0x3041;
+const HIRAGANA_END: u32 = 0x3096;
+const KATAKANA_OFFSET: u32 = 0x60;
+const SMALL_TSU: u32 = 0x3063;
+const PROLONGED_SOUND_MARK: char = 'ー';
+const HANGUL_START: u32 = 0xAC00;
+const HANGUL_END: u32 = 0xD7A3;
+
+/// Extends `keywords` with romanized forms of kana (Hepburn) and Hangul (Revised Romanization)
+/// found in `name` or the keywords, so "kuromu" finds "クローム". Kanji are left as they are.
+pub fn extend_keywords_with_transliteration(name: &str, keywords: &mut Vec<String>) {
+    let mut additions = Vec::new();
+    for source in std::iter::once(name).chain(keywords.iter().map(String::as_str)) {
+        for romanized in transliterate_cjk(source) {
+            if !keywords.contains(&romanized) && !additions.contains(&romanized) {
+                additions.push(romanized);
+            }
+        }
+    }
+    keywords.extend(additions);
+}
+
+/// Romanizes kana and Hangul in `value`, returning nothing when it contains neither. Names with
+/// a long-vowel mark get a second form without the lengthened vowel ("kuroomu" and "kuromu").
+fn transliterate_cjk(value: &str) -> Vec<String> {
+    let mut output = String::new();
+    let mut found = false;
+    // 上一个假名音节的罗马字，用于拗音、小写元音与促音的合并
+    let mut previous_kana: Option<&str> = None;
+    let mut pending_sokuon = false;
+
+    for c in value.chars().flat_map(char::to_lowercase) {
+        let code = c as u32;
+        let hiragana = match code {
+            0x30A1..=0x30F6 => code - KATAKANA_OFFSET,
+            _ => code,
+        };
+
+        if (HIRAGANA_START..=HIRAGANA_END).contains(&hiragana) {
+            found = true;
+            if hiragana == SMALL_TSU {
+                pending_sokuon = true;
+                continue;
+            }
+            let romaji = KANA_ROMAJI[(hiragana - HIRAGANA_START) as usize];
+            let small_y = matches!(hiragana, 0x3083 | 0x3085 | 0x3087);
+            let small_vowel = matches!(hiragana, 0x3041 | 0x3043 | 0x3045 | 0x3047 | 0x3049);
+            match previous_kana {
+                // 拗音：き+ゃ → kya，し+ゃ → sha
+                Some(last) if small_y && last.ends_with('i') && last.len() > 1 => {
+                    output.pop();
+                    if !matches!(last, "shi" | "chi" | "ji") {
+                        output.push('y');
+                    }
+                    output.push_str(&romaji[1..]);
+                }
+                // 外来语的小写元音：フ+ァ → fa，テ+ィ → ti
+                Some(last) if small_vowel && last.len() > 1 => {
+                    output.pop();
+                    output.push_str(romaji);
+                }
+                _ => {
+                    // 促音重复下一个辅音，ch 前写作 t
+                    if pending_sokuon {
+                        if romaji.starts_with("ch") {
+                            output.push('t');
+                        } else if let Some(first) = romaji.chars().next() {
+                            if !"aiueon".contains(first) {
+                                output.push(first);
+                            }
+                        }
+                    }
+                    output.push_str(romaji);
+                }
+            }
+            pending_sokuon = false;
+            previous_kana = Some(romaji);
+            continue;
+        }
+
+        pending_sokuon = false;
+        if c == PROLONGED_SOUND_MARK && previous_kana.is_some() {
+            // 先保留长音符，最后再分别展开为重复元音与省略两种写法
+            output.push(c);
+            continue;
+        }
+        previous_kana = None;
+
+        if (HANGUL_START..=HANGUL_END).contains(&code) {
+            found = true;
+            let index = (code - HANGUL_START) as usize;
+            output.push_str(HANGUL_INITIALS[index / (21 * 28)]);
+            output.push_str(HANGUL_MEDIALS[index % (21 * 28) / 28]);
+            output.push_str(HANGUL_FINALS[index % 28]);
+        } else if c == '・' {
+            output.push(' ');
+        } else {
+            output.push(c);
+        }
+    }
+
+    if !found {
+        return Vec::new();
+    }
+    if !output.contains(PROLONGED_SOUND_MARK) {
+        return vec![output];
+    }
+
+    let mut long = String::with_capacity(output.len());
+    let mut last = None;
+    for c in output.chars() {
+        if c == PROLONGED_SOUND_MARK {
+            long.extend(last);
+        } else {
+            long.push(c);
+            last = Some(c);
+        }
+    }
+    let short = output.replace(PROLONGED_SOUND_MARK, "");
+    vec![long, short]
+}
+
+/// Wraps a filesystem path in double quotes for a command line. A trailing backslash would
+/// escape the closing quote (`"C:\"`), so such paths get a `.` appended first.
+pub fn quote_path(path: &str) -> String {
+    if path.ends_with('\\') {
+        format!("\"{path}.\"")
+    } else {
+        format!("\"{path}\"")
+    }
+}
+
+/// Quotes `arg` as a single argument under the `CommandLineToArgvW` rules: embedded quotes are
+/// escaped and backslashes are doubled where they precede a quote.
+pub fn quote_argument(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_diacritics_strips_accents_and_case() {
+        assert_eq!(fold_diacritics("Résumé"), "resume");
+    }
+
+    #[test]
+    fn collation_key_spells_out_pinyin_for_chinese_locales() {
+        assert_eq!(collation_key("苹果", "zh-CN"), "pingguo");
+        assert!(collation_key("苹果", "zh-CN") < collation_key("Qt Creator", "zh-CN"));
+        assert!(collation_key("苹果", "zh-CN") > collation_key("Notepad", "zh-CN"));
+        assert_eq!(collation_key("苹果", "en-US"), "苹果");
+    }
+
+    #[test]
+    fn quote_path_keeps_trailing_backslash_from_escaping_the_quote() {
+        assert_eq!(quote_path(r"C:\"), r#""C:\.""#);
+        assert_eq!(
+            quote_path(r"C:\Program Files\App"),
+            r#""C:\Program Files\App""#
+        );
+    }
+
+    #[test]
+    fn quote_argument_escapes_quotes_and_trailing_backslashes() {
+        assert_eq!(
+            quote_argument("https://a.com/?q=1"),
+            r#""https://a.com/?q=1""#
+        );
+        assert_eq!(
+            quote_argument(r#"https://a.com/?q="x" --incognito"#),
+            r#""https://a.com/?q=\"x\" --incognito""#
+        );
+        assert_eq!(quote_argument(r"C:\dir\"), r#""C:\dir\\""#);
+        assert_eq!(quote_argument(r#"a\"b"#), r#""a\\\"b""#);
+    }
+
+    #[test]
+    fn pinyin_keywords_include_full_spelling_and_initials() {
+        let mut keywords = vec!["百度一下".to_string()];
+        extend_keywords_with_pinyin(&mut keywords);
+        assert!(keywords.iter().any(|keyword| keyword == "baiduyixia"));
+        assert!(keywords.iter().any(|keyword| keyword == "bdyx"));
+    }
+
+    #[test]
+    fn transliterates_long_vowels_both_ways() {
+        assert_eq!(transliterate_cjk("クローム"), ["kuroomu", "kuromu"]);
+    }
+
+    #[test]
+    fn transliterates_yoon_sokuon_and_small_vowels() {
+        assert_eq!(transliterate_cjk("キャ"), ["kya"]);
+        assert_eq!(transliterate_cjk("ッチ"), ["tchi"]);
+        assert_eq!(transliterate_cjk("ファ"), ["fa"]);
+    }
+
+    #[test]
+    fn transliterates_hangul_syllables() {
+        assert_eq!(transliterate_cjk("한글"), ["hangeul"]);
+        assert!(transliterate_cjk("Notepad").is_empty());
+    }
+}
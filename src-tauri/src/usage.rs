@@ -0,0 +1,134 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const USAGE_FILE: &str = "usage_stats.json";
+/// Prefix of usage keys for opened URLs. They have no index entry, so they are pruned by age
+/// instead.
+pub const URL_USAGE_PREFIX: &str = "url:";
+/// URL entries not opened for this long are dropped when pruning (90 days, in seconds).
+const URL_USAGE_MAX_AGE_SECS: i64 = 90 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub launch_count: u32,
+    /// Unix timestamp (seconds) of the most recent launch.
+    pub last_used: i64,
+}
+
+/// A single usage entry as exposed to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageRecord {
+    pub id: String,
+    pub launch_count: u32,
+    pub last_used: i64,
+}
+
+/// Launch statistics keyed by application or bookmark id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageStats {
+    entries: HashMap<String, UsageEntry>,
+}
+
+impl UsageStats {
+    pub fn load(handle: &AppHandle) -> Self {
+        let Some(path) = usage_path(handle) else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, handle: &AppHandle) -> Result<(), String> {
+        let Some(path) = usage_path(handle) else {
+            return Err("无法确定配置目录".into());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, data).map_err(|err| err.to_string())
+    }
+
+    /// Records a launch of `id` at the current time.
+    pub fn record(&mut self, id: &str) {
+        let now = unix_now();
+        let entry = self.entries.entry(id.to_string()).or_default();
+        entry.launch_count = entry.launch_count.saturating_add(1);
+        entry.last_used = now;
+    }
+
+    pub fn get(&self, id: &str) -> Option<&UsageEntry> {
+        self.entries.get(id)
+    }
+
+    pub fn launch_count(&self, id: &str) -> u32 {
+        self.entries
+            .get(id)
+            .map(|entry| entry.launch_count)
+            .unwrap_or_default()
+    }
+
+    /// Drops entries whose id is not in `valid_ids`, returning how many were removed. URL keys
+    /// are kept until they go unused for 90 days.
+    pub fn prune(&mut self, valid_ids: &HashSet<&str>) -> usize {
+        self.prune_at(valid_ids, unix_now())
+    }
+
+    /// [`prune`](Self::prune) as of the Unix timestamp `now`.
+    pub fn prune_at(&mut self, valid_ids: &HashSet<&str>, now: i64) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|id, entry| {
+            if id.starts_with(URL_USAGE_PREFIX) {
+                now - entry.last_used <= URL_USAGE_MAX_AGE_SECS
+            } else {
+                valid_ids.contains(id.as_str())
+            }
+        });
+        before - self.entries.len()
+    }
+
+    /// Returns all entries, most launched first.
+    pub fn records(&self) -> Vec<UsageRecord> {
+        let mut records: Vec<UsageRecord> = self
+            .entries
+            .iter()
+            .map(|(id, entry)| UsageRecord {
+                id: id.clone(),
+                launch_count: entry.launch_count,
+                last_used: entry.last_used,
+            })
+            .collect();
+        records.sort_by(|a, b| {
+            b.launch_count
+                .cmp(&a.launch_count)
+                .then_with(|| b.last_used.cmp(&a.last_used))
+        });
+        records
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn usage_path(handle: &AppHandle) -> Option<PathBuf> {
+    handle
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(USAGE_FILE))
+}
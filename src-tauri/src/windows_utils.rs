@@ -2,37 +2,67 @@ use std::{
     env,
     ffi::OsStr,
     fs,
+    io::Read,
     os::windows::ffi::OsStrExt,
     path::{Path, PathBuf},
     ptr,
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+use image::{codecs::png::PngEncoder, imageops::FilterType, ColorType, ImageEncoder};
 use log::warn;
+use once_cell::sync::OnceCell;
 use sha1::{Digest, Sha1};
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    ActivateKeyboardLayout, LoadKeyboardLayoutW, KLF_ACTIVATE, KLF_SETFORPROCESS,
+    ActivateKeyboardLayout, GetAsyncKeyState, LoadKeyboardLayoutW, SendInput, INPUT, INPUT_0,
+    INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, KLF_ACTIVATE,
+    KLF_SETFORPROCESS, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT, VK_V,
 };
 use windows::{
-    core::{Error, Interface, Result, PCWSTR},
+    core::{w, Error, Interface, Result, GUID, HSTRING, PCWSTR, PWSTR},
     Win32::{
-        Foundation::RPC_E_CHANGED_MODE,
+        Foundation::{
+            GlobalFree, BOOL, FALSE, HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT,
+            RPC_E_CHANGED_MODE, SIZE, TRUE, WPARAM,
+        },
         Graphics::Gdi::{
-            CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, GetObjectW, BITMAP, BITMAPINFO,
-            BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HDC,
+            CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, GetMonitorInfoW, GetObjectW,
+            MonitorFromPoint, MonitorFromWindow, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+            DIB_RGB_COLORS, HBITMAP, HDC, MONITORINFO, MONITOR_DEFAULTTONEAREST,
         },
         Storage::FileSystem::WIN32_FIND_DATAW,
         System::{
             Com::{
-                CoCreateInstance, CoInitializeEx, CoUninitialize, IPersistFile,
+                CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, IPersistFile,
                 CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, STGM_READ,
             },
+            DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
             Environment::ExpandEnvironmentStringsW,
+            LibraryLoader::GetModuleHandleW,
+            Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+            Ole::CF_UNICODETEXT,
         },
         UI::{
-            Shell::{ExtractIconExW, IShellLinkW, ShellLink, SLGP_RAWPATH, SLGP_UNCPRIORITY},
-            WindowsAndMessaging::{DestroyIcon, GetIconInfo, HICON, ICONINFO},
+            Shell::{
+                BHID_EnumItems, ExtractIconExW, FOLDERID_Desktop, FOLDERID_Documents,
+                FOLDERID_Downloads, FOLDERID_Music, FOLDERID_Pictures, FOLDERID_Videos,
+                IEnumShellItems, IShellItem, IShellItemImageFactory, IShellLinkW,
+                SHCreateItemFromParsingName, SHGetKnownFolderPath, ShellLink, KF_FLAG_DEFAULT,
+                SIGDN_FILESYSPATH, SIGDN_NORMALDISPLAY, SIGDN_PARENTRELATIVEPARSING,
+                SIIGBF_ICONONLY, SLGP_RAWPATH, SLGP_UNCPRIORITY,
+            },
+            WindowsAndMessaging::{
+                CreateWindowExW, DefWindowProcW, DestroyIcon, DispatchMessageW, EnumWindows,
+                GetForegroundWindow, GetIconInfo, GetMessageW, GetWindow, GetWindowRect,
+                GetWindowThreadProcessId, IsWindow, IsWindowVisible, IsZoomed, RegisterClassW,
+                RegisterWindowMessageW, SetForegroundWindow, SetWindowPos, ShowWindow,
+                TranslateMessage, GW_OWNER, HICON, HMENU, ICONINFO, MSG, PBT_APMRESUMEAUTOMATIC,
+                SWP_NOACTIVATE, SWP_NOZORDER, SW_MAXIMIZE, SW_RESTORE, WINDOW_EX_STYLE,
+                WM_POWERBROADCAST, WNDCLASSW, WS_OVERLAPPED,
+            },
         },
     },
 };
@@ -86,6 +116,232 @@ pub(crate) struct InternetShortcutInfo {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct FolderShortcut {
+    pub name: String,
+    pub path: String,
+}
+
+/// A launchable app listed in the `shell:AppsFolder` namespace.
+#[derive(Debug, Clone)]
+pub(crate) struct AppsFolderEntry {
+    pub name: String,
+    /// AppUserModelId; `shell:AppsFolder\<app_id>` launches the app.
+    pub app_id: String,
+    pub icon_b64: Option<String>,
+}
+
+/// `.url` files are small INI files; anything larger is not a real Internet shortcut.
+const MAX_INTERNET_SHORTCUT_BYTES: u64 = 64 * 1024;
+
+/// Shell namespace for Quick Access (pinned and frequent folders).
+const QUICK_ACCESS_NAMESPACE: &str = "shell:::{679f85cb-0220-4080-b29b-5540cc05aab6}";
+/// Shell namespace listing every launchable app, packaged or not.
+pub(crate) const APPS_FOLDER_NAMESPACE: &str = "shell:AppsFolder";
+/// Edge length in pixels of icons rendered for `shell:AppsFolder` entries.
+const APPS_FOLDER_ICON_SIZE: i32 = 32;
+/// Edge length `.ico` previews are scaled down to, matching the large icons `ExtractIconExW` returns.
+const ICO_PREVIEW_SIZE: u32 = 32;
+/// Pause after refocusing the target window so it is ready to receive the paste keystrokes.
+const PASTE_FOCUS_DELAY: Duration = Duration::from_millis(80);
+/// How long to wait for the user to let go of the paste shortcut's modifiers before releasing
+/// them with synthetic key-ups.
+const MODIFIER_RELEASE_TIMEOUT: Duration = Duration::from_millis(500);
+/// Modifiers of the paste shortcut that are released before injecting Ctrl+V, so it isn't
+/// turned into a different shortcut.
+const PASTE_MODIFIERS: [VIRTUAL_KEY; 5] = [VK_CONTROL, VK_MENU, VK_SHIFT, VK_LWIN, VK_RWIN];
+
+const KNOWN_FOLDERS: &[(&str, GUID)] = &[
+    ("桌面", FOLDERID_Desktop),
+    ("文档", FOLDERID_Documents),
+    ("下载", FOLDERID_Downloads),
+    ("图片", FOLDERID_Pictures),
+    ("音乐", FOLDERID_Music),
+    ("视频", FOLDERID_Videos),
+];
+
+/// Resolves the current user's well-known folders (Desktop, Documents, Downloads, ...).
+pub(crate) fn known_folders() -> Vec<FolderShortcut> {
+    let mut folders = Vec::new();
+    for (label, folder_id) in KNOWN_FOLDERS {
+        let path = unsafe {
+            SHGetKnownFolderPath(folder_id, KF_FLAG_DEFAULT, HANDLE::default())
+                .ok()
+                .and_then(|value| take_co_task_string(value))
+        };
+        if let Some(path) = path.filter(|value| Path::new(value).is_dir()) {
+            folders.push(FolderShortcut {
+                name: label.to_string(),
+                path,
+            });
+        }
+    }
+    folders
+}
+
+/// Enumerates the folders listed under Quick Access through the shell namespace.
+pub(crate) fn quick_access_folders() -> Vec<FolderShortcut> {
+    let mut folders = Vec::new();
+    unsafe {
+        let Ok(_guard) = ComGuard::new() else {
+            return folders;
+        };
+        let namespace = HSTRING::from(QUICK_ACCESS_NAMESPACE);
+        let Ok(root) = SHCreateItemFromParsingName::<_, _, IShellItem>(&namespace, None) else {
+            return folders;
+        };
+        let Ok(items) = root.BindToHandler::<_, IEnumShellItems>(None, &BHID_EnumItems) else {
+            return folders;
+        };
+
+        loop {
+            let mut batch = [None];
+            let mut fetched = 0u32;
+            if items.Next(&mut batch, Some(&mut fetched)).is_err() || fetched == 0 {
+                break;
+            }
+            let Some(item) = batch[0].take() else {
+                break;
+            };
+
+            // Quick Access 也会列出最近文件，这里只保留真实存在的目录
+            let Some(path) = item
+                .GetDisplayName(SIGDN_FILESYSPATH)
+                .ok()
+                .and_then(|value| take_co_task_string(value))
+            else {
+                continue;
+            };
+            if !Path::new(&path).is_dir() {
+                continue;
+            }
+
+            let name = item
+                .GetDisplayName(SIGDN_NORMALDISPLAY)
+                .ok()
+                .and_then(|value| take_co_task_string(value))
+                .filter(|value| !value.trim().is_empty())
+                .or_else(|| {
+                    Path::new(&path)
+                        .file_name()
+                        .and_then(|value| value.to_str())
+                        .map(|value| value.to_string())
+                })
+                .unwrap_or_else(|| path.clone());
+            folders.push(FolderShortcut { name, path });
+        }
+    }
+    folders
+}
+
+/// Enumerates the apps in `shell:AppsFolder`. Icons are only rendered for entries accepted by
+/// `include`, which receives the display name and AppUserModelId.
+pub(crate) fn apps_folder_entries(include: impl Fn(&str, &str) -> bool) -> Vec<AppsFolderEntry> {
+    let mut entries = Vec::new();
+    unsafe {
+        let Ok(_guard) = ComGuard::new() else {
+            return entries;
+        };
+        let namespace = HSTRING::from(APPS_FOLDER_NAMESPACE);
+        let Ok(root) = SHCreateItemFromParsingName::<_, _, IShellItem>(&namespace, None) else {
+            return entries;
+        };
+        let Ok(items) = root.BindToHandler::<_, IEnumShellItems>(None, &BHID_EnumItems) else {
+            return entries;
+        };
+
+        loop {
+            let mut batch = [None];
+            let mut fetched = 0u32;
+            if items.Next(&mut batch, Some(&mut fetched)).is_err() || fetched == 0 {
+                break;
+            }
+            let Some(item) = batch[0].take() else {
+                break;
+            };
+
+            // 在 AppsFolder 中，相对解析名即为应用的 AppUserModelId
+            let Some(app_id) = item
+                .GetDisplayName(SIGDN_PARENTRELATIVEPARSING)
+                .ok()
+                .and_then(|value| take_co_task_string(value))
+                .filter(|value| !value.trim().is_empty())
+            else {
+                continue;
+            };
+            let Some(name) = item
+                .GetDisplayName(SIGDN_NORMALDISPLAY)
+                .ok()
+                .and_then(|value| take_co_task_string(value))
+                .filter(|value| !value.trim().is_empty())
+            else {
+                continue;
+            };
+            if !include(&name, &app_id) {
+                continue;
+            }
+
+            let icon_b64 = shell_item_icon(&item);
+            entries.push(AppsFolderEntry {
+                name,
+                app_id,
+                icon_b64,
+            });
+        }
+    }
+    entries
+}
+
+/// Renders a shell item's icon through `IShellItemImageFactory`.
+unsafe fn shell_item_icon(item: &IShellItem) -> Option<String> {
+    let factory: IShellItemImageFactory = item.cast().ok()?;
+    let size = SIZE {
+        cx: APPS_FOLDER_ICON_SIZE,
+        cy: APPS_FOLDER_ICON_SIZE,
+    };
+    let bitmap = factory.GetImage(size, SIIGBF_ICONONLY).ok()?;
+    let encoded = bitmap_to_base64(bitmap);
+    let _ = DeleteObject(bitmap);
+    encoded
+}
+
+unsafe fn bitmap_to_base64(bitmap: HBITMAP) -> Option<String> {
+    let mut info: BITMAP = std::mem::zeroed();
+    if GetObjectW(
+        bitmap,
+        std::mem::size_of::<BITMAP>() as i32,
+        Some(&mut info as *mut _ as *mut _),
+    ) == 0
+    {
+        return None;
+    }
+    let (width, height) = (info.bmWidth, info.bmHeight.abs());
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let dc = CreateCompatibleDC(HDC::default());
+    if dc.is_invalid() {
+        return None;
+    }
+    let pixels = read_bitmap_bits(dc, bitmap, width, height);
+    let _ = DeleteDC(dc);
+
+    let mut pixels = pixels?;
+    bgra_to_rgba(&mut pixels);
+    encode_png_base64(&pixels, width as u32, height as u32)
+}
+
+/// Converts a shell-allocated wide string into a [`String`] and frees the original buffer.
+unsafe fn take_co_task_string(value: PWSTR) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    let result = value.to_string().ok();
+    CoTaskMemFree(Some(value.0 as *const _));
+    result
+}
+
 /// Resolves `.lnk` shortcuts and extracts metadata such as target executable, arguments and icon info.
 pub(crate) fn resolve_shell_link(path: &Path) -> Option<ShortcutInfo> {
     #[cfg(target_os = "windows")]
@@ -165,9 +421,15 @@ pub(crate) fn resolve_shell_link(path: &Path) -> Option<ShortcutInfo> {
 }
 
 /// Parses a `.url` Internet shortcut and extracts the target URL plus optional icon metadata.
+/// Files larger than [`MAX_INTERNET_SHORTCUT_BYTES`] are rejected without being read in full.
 pub(crate) fn parse_internet_shortcut(path: &Path) -> Option<InternetShortcutInfo> {
-    let bytes = fs::read(path).ok()?;
-    if bytes.is_empty() {
+    let mut bytes = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(MAX_INTERNET_SHORTCUT_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if bytes.is_empty() || bytes.len() as u64 > MAX_INTERNET_SHORTCUT_BYTES {
         return None;
     }
 
@@ -185,6 +447,10 @@ pub(crate) fn parse_internet_shortcut(path: &Path) -> Option<InternetShortcutInf
         }
 
         if line.starts_with('[') && line.ends_with(']') {
+            // [InternetShortcut] 段结束且已取得网址时不再解析后续内容
+            if in_section && url.is_some() {
+                break;
+            }
             in_section = line.eq_ignore_ascii_case("[internetshortcut]");
             continue;
         }
@@ -265,6 +531,116 @@ pub(crate) fn expand_env_vars(value: &str) -> Option<String> {
     }
 }
 
+/// Replaces the clipboard contents with `text`.
+pub(crate) fn set_clipboard_text(text: &str) -> std::result::Result<(), String> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        OpenClipboard(HWND::default()).map_err(|err| format!("无法打开剪贴板: {err}"))?;
+        let result = write_clipboard_text(&wide);
+        let _ = CloseClipboard();
+        result
+    }
+}
+
+/// Writes a NUL-terminated UTF-16 string to the already opened clipboard.
+unsafe fn write_clipboard_text(wide: &[u16]) -> std::result::Result<(), String> {
+    EmptyClipboard().map_err(|err| format!("无法清空剪贴板: {err}"))?;
+    let memory = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(wide))
+        .map_err(|err| format!("无法分配剪贴板内存: {err}"))?;
+    let target = GlobalLock(memory) as *mut u16;
+    if target.is_null() {
+        let _ = GlobalFree(memory);
+        return Err("无法写入剪贴板".into());
+    }
+    ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+    let _ = GlobalUnlock(memory);
+    // 写入成功后内存归剪贴板所有，只有失败时才需要释放
+    if let Err(err) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(memory.0)) {
+        let _ = GlobalFree(memory);
+        return Err(format!("无法写入剪贴板: {err}"));
+    }
+    Ok(())
+}
+
+/// Returns the handle of the current foreground window, if any.
+pub(crate) fn foreground_window() -> Option<isize> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    (!hwnd.is_invalid()).then_some(hwnd.0 as isize)
+}
+
+/// Whether `hwnd` still refers to an existing window.
+pub(crate) fn is_window_alive(hwnd: isize) -> bool {
+    unsafe { IsWindow(HWND(hwnd as *mut _)).as_bool() }
+}
+
+/// Brings `hwnd` to the foreground and sends Ctrl+V so it pastes the clipboard.
+pub(crate) fn paste_into_window(hwnd: isize) -> std::result::Result<(), String> {
+    if !is_window_alive(hwnd) {
+        return Err("原窗口已关闭".into());
+    }
+    // 焦点未能还原时粘贴会落到其他窗口，宁可只保留剪贴板内容
+    if !unsafe { SetForegroundWindow(HWND(hwnd as *mut _)) }.as_bool() {
+        warn!("failed to bring window 0x{hwnd:x} to the foreground before pasting");
+        return Err("无法切换到原窗口，链接已复制到剪贴板".into());
+    }
+    thread::sleep(PASTE_FOCUS_DELAY);
+
+    // 触发粘贴的快捷键还按着 Ctrl/Alt/Shift 时，Ctrl+V 会变成其他快捷键，等待松开后再发送
+    let mut inputs = Vec::with_capacity(PASTE_MODIFIERS.len() + 4);
+    let deadline = Instant::now() + MODIFIER_RELEASE_TIMEOUT;
+    loop {
+        let held: Vec<VIRTUAL_KEY> = PASTE_MODIFIERS
+            .into_iter()
+            .filter(|key| is_key_down(*key))
+            .collect();
+        if held.is_empty() {
+            break;
+        }
+        if Instant::now() >= deadline {
+            inputs.extend(held.into_iter().map(|key| key_input(key, true)));
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    inputs.extend([
+        key_input(VK_CONTROL, false),
+        key_input(VK_V, false),
+        key_input(VK_V, true),
+        key_input(VK_CONTROL, true),
+    ]);
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err("无法发送粘贴按键".into());
+    }
+    Ok(())
+}
+
+/// Whether `key` is physically held down right now.
+fn is_key_down(key: VIRTUAL_KEY) -> bool {
+    // 最高位表示按键当前处于按下状态
+    let state = unsafe { GetAsyncKeyState(i32::from(key.0)) };
+    state < 0
+}
+
+fn key_input(key: VIRTUAL_KEY, release: bool) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: key,
+                wScan: 0,
+                dwFlags: if release {
+                    KEYEVENTF_KEYUP
+                } else {
+                    KEYBD_EVENT_FLAGS(0)
+                },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
 /// Extracts a large application icon and returns it as PNG encoded base64.
 pub(crate) fn extract_icon_from_path(path: &str, icon_index: i32) -> Option<String> {
     if path.is_empty() {
@@ -311,6 +687,83 @@ pub(crate) fn extract_icon_from_path(path: &str, icon_index: i32) -> Option<Stri
     }
 }
 
+/// Extracts the icon `path` would produce for previews. `.ico` files are decoded with the image
+/// crate, which picks the largest embedded size; other files go through
+/// [`extract_icon_from_path`]. Both paths share the icon cache.
+pub(crate) fn preview_icon_from_path(path: &str, icon_index: i32) -> Option<String> {
+    let resolved = expand_env_vars(path).unwrap_or_else(|| path.to_string());
+    let is_ico = Path::new(&resolved)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ico"));
+    if !is_ico {
+        return extract_icon_from_path(path, icon_index);
+    }
+
+    let cache_key = icon_cache_key(&resolved, 0);
+    if let Some(encoded) = load_cached_icon(&cache_key) {
+        return Some(encoded);
+    }
+    let source = image::open(&resolved).ok()?.to_rgba8();
+    let (width, height) = source.dimensions();
+    let encoded = if width > ICO_PREVIEW_SIZE || height > ICO_PREVIEW_SIZE {
+        let resized = image::imageops::resize(
+            &source,
+            ICO_PREVIEW_SIZE,
+            ICO_PREVIEW_SIZE,
+            FilterType::Triangle,
+        );
+        encode_png_base64(resized.as_raw(), ICO_PREVIEW_SIZE, ICO_PREVIEW_SIZE)?
+    } else {
+        encode_png_base64(source.as_raw(), width, height)?
+    };
+    store_cached_icon(&cache_key, &encoded);
+    Some(encoded)
+}
+
+/// Drops any cached copy of the icon and extracts it again from the source file.
+pub(crate) fn refresh_icon_from_path(path: &str, icon_index: i32) -> Option<String> {
+    let resolved = expand_env_vars(path).unwrap_or_else(|| path.to_string());
+    if let Some(cache_path) = cache_file_path(&icon_cache_key(&resolved, icon_index.max(0))) {
+        let _ = fs::remove_file(cache_path);
+    }
+    extract_icon_from_path(path, icon_index)
+}
+
+/// Size in bytes of the cached copy of an icon, if one exists.
+pub(crate) fn cached_icon_len_for_path(path: &str, icon_index: i32) -> Option<u64> {
+    let resolved = expand_env_vars(path).unwrap_or_else(|| path.to_string());
+    let cache_path = cache_file_path(&icon_cache_key(&resolved, icon_index.max(0)))?;
+    fs::metadata(cache_path).ok().map(|metadata| metadata.len())
+}
+
+/// Replaces the cached copy of an icon, e.g. with a downscaled version.
+pub(crate) fn store_icon_for_path(path: &str, icon_index: i32, data: &str) {
+    let resolved = expand_env_vars(path).unwrap_or_else(|| path.to_string());
+    store_cached_icon(&icon_cache_key(&resolved, icon_index.max(0)), data);
+}
+
+/// Downscales a base64 encoded image by halving its size until the base64 output fits in
+/// `max_bytes` (or the image is a single pixel). Returns `None` for undecodable data.
+pub(crate) fn shrink_icon_base64(encoded: &str, max_bytes: usize) -> Option<String> {
+    let bytes = BASE64.decode(encoded).ok()?;
+    let source = image::load_from_memory(&bytes).ok()?.to_rgba8();
+    let (source_width, source_height) = source.dimensions();
+
+    let mut divisor = 2;
+    loop {
+        // 每次都从原图缩放，避免反复缩放导致过度模糊
+        let width = (source_width / divisor).max(1);
+        let height = (source_height / divisor).max(1);
+        let resized = image::imageops::resize(&source, width, height, FilterType::Triangle);
+        let shrunk = encode_png_base64(resized.as_raw(), width, height)?;
+        if shrunk.len() <= max_bytes || (width == 1 && height == 1) {
+            return Some(shrunk);
+        }
+        divisor *= 2;
+    }
+}
+
 fn icon_cache_key(path: &str, icon_index: i32) -> String {
     let mut hasher = Sha1::new();
     hasher.update(path.to_lowercase().as_bytes());
@@ -374,9 +827,14 @@ fn decode_utf16(data: &[u8], little_endian: bool) -> String {
     String::from_utf16_lossy(&units)
 }
 
-fn icon_cache_dir() -> Option<PathBuf> {
+/// Root directory for local caches such as extracted icons.
+pub(crate) fn data_dir() -> Option<PathBuf> {
     let base = env::var("LOCALAPPDATA").ok()?;
-    Some(Path::new(&base).join("egg").join("icons"))
+    Some(Path::new(&base).join("egg"))
+}
+
+fn icon_cache_dir() -> Option<PathBuf> {
+    Some(data_dir()?.join("icons"))
 }
 
 unsafe fn icon_to_base64(icon: HICON) -> Option<String> {
@@ -422,14 +880,6 @@ unsafe fn icon_to_base64(icon: HICON) -> Option<String> {
         return None;
     }
 
-    let mut info: BITMAPINFO = std::mem::zeroed();
-    info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
-    info.bmiHeader.biWidth = width;
-    info.bmiHeader.biHeight = -height; // top-down DIB
-    info.bmiHeader.biPlanes = 1;
-    info.bmiHeader.biBitCount = 32;
-    info.bmiHeader.biCompression = BI_RGB.0;
-
     let dc = CreateCompatibleDC(HDC::default());
     if dc.is_invalid() {
         cleanup_icon(&icon_info);
@@ -437,38 +887,43 @@ unsafe fn icon_to_base64(icon: HICON) -> Option<String> {
         return None;
     }
 
-    let mut pixels = vec![0u8; (width * height * 4) as usize];
-    if GetDIBits(
-        dc,
-        color_bitmap,
-        0,
-        height as u32,
-        Some(pixels.as_mut_ptr() as *mut _),
-        &mut info,
-        DIB_RGB_COLORS,
-    ) == 0
-    {
-        let _ = DeleteDC(dc);
-        cleanup_icon(&icon_info);
-        let _ = DestroyIcon(icon);
-        return None;
-    }
+    let pixels = if icon_info.hbmColor.is_invalid() {
+        // 单色图标：掩码位图上半部分是 AND 掩码，下半部分是 XOR 颜色
+        read_bitmap_bits(dc, icon_info.hbmMask, width, height * 2).map(|bits| {
+            let (and_mask, xor_color) = bits.split_at(bits.len() / 2);
+            let mut pixels = xor_color.to_vec();
+            apply_mask_alpha(&mut pixels, and_mask);
+            pixels
+        })
+    } else {
+        read_bitmap_bits(dc, icon_info.hbmColor, width, height).map(|mut pixels| {
+            // 不带 alpha 通道的 24 位图标依靠 AND 掩码表示透明区域
+            if pixels.chunks_exact(4).all(|px| px[3] == 0) {
+                match read_bitmap_bits(dc, icon_info.hbmMask, width, height) {
+                    Some(mask) => apply_mask_alpha(&mut pixels, &mask),
+                    None => pixels.chunks_exact_mut(4).for_each(|px| px[3] = u8::MAX),
+                }
+            }
+            pixels
+        })
+    };
 
     let _ = DeleteDC(dc);
-
-    // Convert BGRA -> RGBA
-    for chunk in pixels.chunks_exact_mut(4) {
-        chunk.swap(0, 2);
-    }
-
     cleanup_icon(&icon_info);
     let _ = DestroyIcon(icon);
 
+    let mut pixels = pixels?;
+    bgra_to_rgba(&mut pixels);
+
+    encode_png_base64(&pixels, width as u32, height as u32)
+}
+
+fn encode_png_base64(pixels: &[u8], width: u32, height: u32) -> Option<String> {
     let mut png = Vec::new();
     {
         let encoder = PngEncoder::new(&mut png);
         if encoder
-            .write_image(&pixels, width as u32, height as u32, ColorType::Rgba8)
+            .write_image(pixels, width, height, ColorType::Rgba8)
             .is_err()
         {
             return None;
@@ -478,6 +933,61 @@ unsafe fn icon_to_base64(icon: HICON) -> Option<String> {
     Some(BASE64.encode(png))
 }
 
+/// Reads `rows` rows of `bitmap` as top-down 32-bit BGRA pixels.
+unsafe fn read_bitmap_bits(dc: HDC, bitmap: HBITMAP, width: i32, rows: i32) -> Option<Vec<u8>> {
+    if bitmap.is_invalid() {
+        return None;
+    }
+
+    let mut info: BITMAPINFO = std::mem::zeroed();
+    info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+    info.bmiHeader.biWidth = width;
+    info.bmiHeader.biHeight = -rows; // top-down DIB
+    info.bmiHeader.biPlanes = 1;
+    info.bmiHeader.biBitCount = 32;
+    info.bmiHeader.biCompression = BI_RGB.0;
+
+    let mut pixels = vec![0u8; (width * rows * 4) as usize];
+    let copied = GetDIBits(
+        dc,
+        bitmap,
+        0,
+        rows as u32,
+        Some(pixels.as_mut_ptr() as *mut _),
+        &mut info,
+        DIB_RGB_COLORS,
+    );
+    (copied != 0).then_some(pixels)
+}
+
+/// Derives alpha from an AND mask read as BGRA, where set (white) mask pixels are transparent.
+fn apply_mask_alpha(pixels: &mut [u8], mask: &[u8]) {
+    for (pixel, mask) in pixels.chunks_exact_mut(4).zip(mask.chunks_exact(4)) {
+        pixel[3] = if mask[0] != 0 { 0 } else { u8::MAX };
+    }
+}
+
+/// Converts BGRA to RGBA. When no color channel exceeds its alpha the icon is treated as
+/// premultiplied and the alpha is divided back out, avoiding dark fringes on soft edges.
+fn bgra_to_rgba(pixels: &mut [u8]) {
+    let premultiplied = pixels
+        .chunks_exact(4)
+        .any(|px| px[3] > 0 && px[3] < u8::MAX)
+        && pixels
+            .chunks_exact(4)
+            .all(|px| px[0] <= px[3] && px[1] <= px[3] && px[2] <= px[3]);
+
+    for px in pixels.chunks_exact_mut(4) {
+        px.swap(0, 2);
+        let alpha = u32::from(px[3]);
+        if premultiplied && alpha > 0 && alpha < 255 {
+            for channel in &mut px[..3] {
+                *channel = ((u32::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+}
+
 unsafe fn cleanup_icon(info: &ICONINFO) {
     if !info.hbmColor.is_invalid() {
         let _ = DeleteObject(info.hbmColor);
@@ -488,7 +998,7 @@ unsafe fn cleanup_icon(info: &ICONINFO) {
 }
 
 /// Switches the current keyboard layout to English (US) so the search框默认使用英文输入法。
-pub(crate) fn switch_to_english_input_method() {
+pub(crate) fn switch_to_english_input_method() -> std::result::Result<(), String> {
     #[cfg(target_os = "windows")]
     unsafe {
         use windows::Win32::UI::Input::KeyboardAndMouse::{
@@ -510,7 +1020,7 @@ pub(crate) fn switch_to_english_input_method() {
             }
             Err(error) => {
                 warn!("failed to load EN-US keyboard layout: {error:?}");
-                return;
+                return Err(format!("无法加载英文键盘布局: {error}"));
             }
         };
 
@@ -521,11 +1031,15 @@ pub(crate) fn switch_to_english_input_method() {
             // Use KLF_ACTIVATE to switch
             if let Err(error) = ActivateKeyboardLayout(en_us_layout, KLF_ACTIVATE) {
                 warn!("failed to activate EN-US keyboard layout: {error:?}");
-            } else {
-                log::info!("Successfully switched to English");
+                return Err(format!("无法切换到英文键盘布局: {error}"));
             }
+            log::info!("Successfully switched to English");
         }
+        Ok(())
     }
+
+    #[cfg(not(target_os = "windows"))]
+    Ok(())
 }
 
 /// Gets the current input method (keyboard layout) for the current thread.
@@ -564,6 +1078,39 @@ pub(crate) fn restore_input_method(layout_id: isize) {
     }
 }
 
+/// Resolves the executable of the user's default browser from the `http` URL association.
+pub(crate) fn default_browser_executable() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        const USER_CHOICE_KEY: &str =
+            r"Software\Microsoft\Windows\Shell\Associations\UrlAssociations\http\UserChoice";
+
+        let prog_id: String = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(USER_CHOICE_KEY)
+            .ok()?
+            .get_value("ProgId")
+            .ok()?;
+        let command: String = RegKey::predef(HKEY_CLASSES_ROOT)
+            .open_subkey(format!(r"{prog_id}\shell\open\command"))
+            .ok()?
+            .get_value("")
+            .ok()?;
+
+        // 命令形如 "C:\...\chrome.exe" --single-argument %1，只取可执行文件部分
+        let command = command.trim();
+        let executable = match command.strip_prefix('"') {
+            Some(rest) => rest.split('"').next()?,
+            None => command.split_whitespace().next()?,
+        };
+        (!executable.is_empty()).then(|| executable.to_string())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
 /// Enables or disables Windows auto-start via the "Run" registry key.
 pub(crate) fn configure_launch_on_startup(enable: bool) -> std::result::Result<(), String> {
     #[cfg(target_os = "windows")]
@@ -601,3 +1148,265 @@ pub(crate) fn configure_launch_on_startup(enable: bool) -> std::result::Result<(
         Ok(())
     }
 }
+
+/// Interval between checks for a freshly launched process's main window.
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Work area (the monitor minus the taskbar) of the monitor nearest to the given screen point.
+pub(crate) fn monitor_work_area_at(x: i32, y: i32) -> Option<RECT> {
+    unsafe {
+        let monitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        GetMonitorInfoW(monitor, &mut info)
+            .as_bool()
+            .then_some(info.rcWork)
+    }
+}
+
+/// Waits for `process_id` to show a visible top-level window and centers it in `work_area`.
+/// Gives up after `timeout`; a window that already sits on the target monitor is left alone.
+pub(crate) fn move_process_window_to(process_id: u32, work_area: RECT, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(hwnd) = find_process_window(process_id) {
+            center_window_in(hwnd, work_area);
+            return;
+        }
+        if Instant::now() >= deadline {
+            log::debug!("process {process_id} did not show a window in time, not moving it");
+            return;
+        }
+        thread::sleep(WINDOW_POLL_INTERVAL);
+    }
+}
+
+/// Returns the first visible, unowned top-level window belonging to `process_id`.
+fn find_process_window(process_id: u32) -> Option<HWND> {
+    struct Search {
+        process_id: u32,
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let mut owner_process = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut owner_process));
+        let unowned = GetWindow(hwnd, GW_OWNER).map_or(true, |owner| owner.0.is_null());
+        if owner_process == search.process_id && unowned && IsWindowVisible(hwnd).as_bool() {
+            search.found = Some(hwnd);
+            return FALSE;
+        }
+        TRUE
+    }
+
+    let mut search = Search {
+        process_id,
+        found: None,
+    };
+    unsafe {
+        // 回调返回 FALSE 提前结束枚举时 EnumWindows 会报错，这里忽略
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+    }
+    search.found
+}
+
+fn center_window_in(hwnd: HWND, work_area: RECT) {
+    unsafe {
+        let target = MonitorFromPoint(
+            POINT {
+                x: work_area.left,
+                y: work_area.top,
+            },
+            MONITOR_DEFAULTTONEAREST,
+        );
+        if MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) == target {
+            return;
+        }
+
+        // 最大化的窗口需先还原才能移动，移动后再重新最大化
+        let maximized = IsZoomed(hwnd).as_bool();
+        if maximized {
+            let _ = ShowWindow(hwnd, SW_RESTORE);
+        }
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return;
+        }
+        let area_width = work_area.right - work_area.left;
+        let area_height = work_area.bottom - work_area.top;
+        let width = (rect.right - rect.left).min(area_width);
+        let height = (rect.bottom - rect.top).min(area_height);
+        let x = work_area.left + (area_width - width) / 2;
+        let y = work_area.top + (area_height - height) / 2;
+        if let Err(err) = SetWindowPos(
+            hwnd,
+            HWND::default(),
+            x,
+            y,
+            width,
+            height,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        ) {
+            warn!("failed to move launched window: {err}");
+        }
+        if maximized {
+            let _ = ShowWindow(hwnd, SW_MAXIMIZE);
+        }
+    }
+}
+
+/// Reason passed to the callback of [`watch_system_resume`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ResumeReason {
+    /// The system woke up from sleep or hibernation.
+    PowerResume,
+    /// explorer.exe restarted and recreated the taskbar.
+    ExplorerRestart,
+}
+
+type ResumeCallback = Box<dyn Fn(ResumeReason) + Send + Sync>;
+
+static RESUME_CALLBACK: OnceCell<ResumeCallback> = OnceCell::new();
+static TASKBAR_CREATED_MESSAGE: AtomicU32 = AtomicU32::new(0);
+
+/// Calls `on_resume` after the system resumes from sleep or explorer.exe restarts, both of which
+/// can silently drop global hotkey registrations. Uses a hidden top-level window on its own thread,
+/// since message-only windows do not receive these broadcasts. Only the first call installs a
+/// listener.
+pub(crate) fn watch_system_resume(on_resume: impl Fn(ResumeReason) + Send + Sync + 'static) {
+    if RESUME_CALLBACK.set(Box::new(on_resume)).is_err() {
+        return;
+    }
+    thread::spawn(|| unsafe {
+        let class_name = w!("RustLauncherResumeWatcher");
+        let instance = match GetModuleHandleW(None) {
+            Ok(module) => HINSTANCE::from(module),
+            Err(err) => {
+                warn!("failed to start resume watcher: {err}");
+                return;
+            }
+        };
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(resume_window_proc),
+            hInstance: instance,
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&class) == 0 {
+            warn!("failed to register resume watcher window class");
+            return;
+        }
+        TASKBAR_CREATED_MESSAGE.store(
+            RegisterWindowMessageW(w!("TaskbarCreated")),
+            Ordering::SeqCst,
+        );
+        // 不设置 WS_VISIBLE，窗口始终隐藏
+        if let Err(err) = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            PCWSTR::null(),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            HWND::default(),
+            HMENU::default(),
+            instance,
+            None,
+        ) {
+            warn!("failed to create resume watcher window: {err}");
+            return;
+        }
+
+        let mut message = MSG::default();
+        while GetMessageW(&mut message, HWND::default(), 0, 0).as_bool() {
+            let _ = TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+    });
+}
+
+unsafe extern "system" fn resume_window_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let taskbar_created = TASKBAR_CREATED_MESSAGE.load(Ordering::SeqCst);
+    let reason = if message == WM_POWERBROADCAST && wparam.0 as u32 == PBT_APMRESUMEAUTOMATIC {
+        Some(ResumeReason::PowerResume)
+    } else if taskbar_created != 0 && message == taskbar_created {
+        Some(ResumeReason::ExplorerRestart)
+    } else {
+        None
+    };
+    if let (Some(reason), Some(callback)) = (reason, RESUME_CALLBACK.get()) {
+        callback(reason);
+    }
+    DefWindowProcW(hwnd, message, wparam, lparam)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Base64 PNG of a `size`×`size` image filled with noise, so it barely compresses.
+    fn noisy_icon(size: u32) -> String {
+        let mut seed = 0x2545_f491_u32;
+        let pixels: Vec<u8> = (0..size * size * 4)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as u8
+            })
+            .collect();
+        encode_png_base64(&pixels, size, size).unwrap()
+    }
+
+    #[test]
+    fn shrinks_large_icon_under_cap() {
+        let icon = noisy_icon(256);
+        let cap = 16 * 1024;
+        assert!(icon.len() > cap);
+
+        let shrunk = shrink_icon_base64(&icon, cap).unwrap();
+        assert!(shrunk.len() <= cap);
+        let decoded = image::load_from_memory(&BASE64.decode(shrunk).unwrap()).unwrap();
+        assert!(decoded.width() < 256);
+        assert_eq!(decoded.width(), decoded.height());
+    }
+
+    #[test]
+    fn keeps_shrinking_down_to_a_single_pixel() {
+        let shrunk = shrink_icon_base64(&noisy_icon(256), 1).unwrap();
+        let decoded = image::load_from_memory(&BASE64.decode(shrunk).unwrap()).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (1, 1));
+    }
+
+    #[test]
+    fn rejects_undecodable_icon() {
+        assert_eq!(shrink_icon_base64("bm90IGFuIGltYWdl", 1024), None);
+    }
+
+    #[test]
+    fn rejects_oversized_internet_shortcut() {
+        let path = env::temp_dir().join(format!("egg-test-{}.url", std::process::id()));
+        let shortcut = "[InternetShortcut]\r\nURL=https://example.com/\r\n";
+        fs::write(&path, shortcut).unwrap();
+        let parsed = parse_internet_shortcut(&path).map(|info| info.url);
+
+        // 超过上限的文件即使网址在开头也不解析
+        let padding = ";".repeat(MAX_INTERNET_SHORTCUT_BYTES as usize);
+        fs::write(&path, format!("{shortcut}{padding}")).unwrap();
+        let oversized = parse_internet_shortcut(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(parsed.as_deref(), Some("https://example.com/"));
+        assert!(oversized.is_none());
+    }
+}
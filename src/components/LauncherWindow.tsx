@@ -1,4 +1,11 @@
-import { useCallback, useEffect, useMemo, useReducer, useRef } from "react";
+import {
+  useCallback,
+  useEffect,
+  useMemo,
+  useReducer,
+  useRef,
+  useState,
+} from "react";
 import type {
   ChangeEvent,
   CompositionEvent,
@@ -22,6 +29,7 @@ import {
 import {
   FOCUS_INPUT_EVENT,
   HIDE_WINDOW_EVENT,
+  INDEX_READY_EVENT,
   OPEN_SETTINGS_EVENT,
   SETTINGS_UPDATED_EVENT,
 } from "../constants/events";
@@ -29,10 +37,53 @@ import {
   initialLauncherState,
   launcherReducer,
 } from "../state/launcherReducer";
-import type { AppSettings, SearchResult } from "../types";
+import type {
+  AppSettings,
+  ExecuteOutcome,
+  ModeInfo,
+  SearchResult,
+} from "../types";
 
 const SETTINGS_WINDOW_LABEL = "settings";
 
+// execute_action 要求确认时展示给用户的提示
+const confirmationMessage = (selected: SearchResult): string => {
+  if (selected.id.endsWith(":uninstall")) {
+    return `确认卸载 ${selected.title}？`;
+  }
+  switch (selected.action_id) {
+    case "run":
+      return `确认执行命令：${selected.subtitle}？`;
+    case "url":
+    case "search":
+    case "bookmark":
+      return `确认打开 ${selected.title}？`;
+    default:
+      return `以管理员身份运行 ${selected.title}？`;
+  }
+};
+
+// 判断按键是否匹配形如 "Tab" 或 "Ctrl+M" 的组合键，修饰键需完全一致
+const matchesKeyCombo = (
+  event: InputKeyboardEvent<HTMLInputElement>,
+  combo: string,
+): boolean => {
+  const parts = combo
+    .split("+")
+    .map((part) => part.trim().toLowerCase())
+    .filter(Boolean);
+  const key = parts.pop();
+  if (!key || event.key.toLowerCase() !== key) {
+    return false;
+  }
+  return (
+    event.ctrlKey === parts.includes("ctrl") &&
+    event.altKey === parts.includes("alt") &&
+    event.shiftKey === parts.includes("shift") &&
+    event.metaKey === (parts.includes("meta") || parts.includes("super"))
+  );
+};
+
 let trackedSettingsWindow: WebviewWindow | null = null;
 
 const resolveTrackedSettingsWindow =
@@ -58,9 +109,12 @@ const resolveTrackedSettingsWindow =
 
 export const LauncherWindow = () => {
   const [state, dispatch] = useReducer(launcherReducer, initialLauncherState);
+  const [indexReady, setIndexReady] = useState(false);
   const searchInputRef = useRef<HTMLInputElement | null>(null);
   const toastTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const latestQueryRef = useRef("");
+  // 本次会话已补载的图标，null 表示提取失败，不再重复请求
+  const lazyIconsRef = useRef(new Map<string, string | null>());
   const currentWindow = useMemo(() => getCurrentWindow(), []);
   const queryDelayMs = state.settings?.query_delay_ms ?? 120;
   const modeConfigs = useMemo(
@@ -128,6 +182,34 @@ export const LauncherWindow = () => {
     }
   }, [showToast]);
 
+  const loadModes = useCallback(async () => {
+    try {
+      const modes = await invoke<ModeInfo[]>("get_modes");
+      dispatch({ type: "SET_MODES", payload: modes });
+    } catch (error) {
+      console.error("Failed to load modes", error);
+    }
+  }, []);
+
+  // 设置变化可能影响前缀与启用状态，需重新获取模式列表
+  useEffect(() => {
+    void loadModes();
+  }, [loadModes, state.settings]);
+
+  const cycleMode = useCallback(() => {
+    const enabledModes = state.modes.filter((mode) => mode.enabled);
+    if (enabledModes.length === 0) {
+      return;
+    }
+    const currentIndex = enabledModes.findIndex(
+      (mode) => mode.id === state.activeMode.id,
+    );
+    const next = enabledModes[(currentIndex + 1) % enabledModes.length];
+    applyInputValue(
+      next.prefix ? `${next.prefix} ${state.searchQuery}` : state.searchQuery,
+    );
+  }, [applyInputValue, state.activeMode.id, state.modes, state.searchQuery]);
+
   const openSettingsWindow = useCallback(async () => {
     const existing = await resolveTrackedSettingsWindow();
     if (existing) {
@@ -206,7 +288,11 @@ export const LauncherWindow = () => {
 
     const register = async () => {
       try {
-        unlisten = await listen(FOCUS_INPUT_EVENT, () => {
+        unlisten = await listen<boolean | null>(FOCUS_INPUT_EVENT, (event) => {
+          // 负载为 true 时表示执行后保持窗口打开，需要先清空输入
+          if (event.payload === true) {
+            resetSearchState();
+          }
           focusSearchInput();
         });
       } catch (error) {
@@ -221,7 +307,7 @@ export const LauncherWindow = () => {
         unlisten();
       }
     };
-  }, [focusSearchInput]);
+  }, [focusSearchInput, resetSearchState]);
 
   useEffect(() => {
     let unlisten: UnlistenFn | undefined;
@@ -259,6 +345,32 @@ export const LauncherWindow = () => {
     });
   }, [showToast]);
 
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined;
+
+    const register = async () => {
+      try {
+        unlisten = await listen(INDEX_READY_EVENT, () => {
+          setIndexReady(true);
+        });
+        // 监听注册前索引可能已经就绪
+        if (await invoke<boolean>("is_index_ready")) {
+          setIndexReady(true);
+        }
+      } catch (error) {
+        console.error("Failed to listen index ready event", error);
+      }
+    };
+
+    void register();
+
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
   useEffect(() => {
     let unlisten: UnlistenFn | undefined;
 
@@ -344,6 +456,33 @@ export const LauncherWindow = () => {
         if (latestQueryRef.current === state.searchQuery) {
           dispatch({ type: "SET_RESULTS", payload: newResults });
           dispatch({ type: "SET_SELECTED_INDEX", payload: 0 });
+          // 超出内存图标上限的应用没有内联图标，按需补载
+          newResults
+            .filter((item) => !item.icon && item.id.startsWith("app-"))
+            .forEach((item) => {
+              const cached = lazyIconsRef.current.get(item.id);
+              if (cached === null) {
+                return;
+              }
+              if (cached !== undefined) {
+                dispatch({
+                  type: "SET_RESULT_ICON",
+                  payload: { id: item.id, icon: cached },
+                });
+                return;
+              }
+              invoke<string>("refresh_icon", { id: item.id })
+                .then((icon) => {
+                  lazyIconsRef.current.set(item.id, icon);
+                  dispatch({
+                    type: "SET_RESULT_ICON",
+                    payload: { id: item.id, icon },
+                  });
+                })
+                .catch(() => {
+                  lazyIconsRef.current.set(item.id, null);
+                });
+            });
         }
       } catch (error) {
         console.error("Failed to query", error);
@@ -361,28 +500,102 @@ export const LauncherWindow = () => {
     state.isModePrefixOnly,
     showToast,
     queryDelayMs,
+    // 索引就绪后重新执行当前查询，替换“正在建立索引”的占位结果
+    indexReady,
   ]);
 
+  const finishExecution = useCallback(
+    (outcome: ExecuteOutcome) => {
+      if (outcome === "executed_in_background") {
+        // 后台打开网址时保留窗口，便于继续输入
+        showToast("已在后台打开");
+        return;
+      }
+      if (outcome === "executed_keep_open") {
+        return;
+      }
+      // 执行完成后，通过统一的隐藏事件让窗口隐藏并重置搜索
+      const hideEvent = new CustomEvent(HIDE_WINDOW_EVENT);
+      window.dispatchEvent(hideEvent);
+    },
+    [showToast],
+  );
+
   const executeSelected = useCallback(
-    async (selected?: SearchResult, runAsAdmin: boolean = false) => {
-      if (!selected) {
+    async (
+      selected?: SearchResult,
+      runAsAdmin: boolean = false,
+      privateWindow: boolean = false,
+      siteRoot: boolean = false,
+    ) => {
+      // 索引提示与截断提示仅用于展示，不执行任何操作
+      if (
+        !selected ||
+        selected.action_id === "indexing" ||
+        selected.action_id === "truncated"
+      ) {
         return;
       }
 
       try {
-        await invoke("execute_action", {
+        let outcome = await invoke<ExecuteOutcome>("execute_action", {
           id: selected.id,
           runAsAdmin,
+          options: { private: privateWindow, siteRoot },
         });
-        // 执行完成后，通过统一的隐藏事件让窗口隐藏并重置搜索
-        const hideEvent = new CustomEvent(HIDE_WINDOW_EVENT);
-        window.dispatchEvent(hideEvent);
+        if (outcome === "needs_confirmation") {
+          if (!window.confirm(confirmationMessage(selected))) {
+            return;
+          }
+          outcome = await invoke<ExecuteOutcome>("execute_action", {
+            id: selected.id,
+            runAsAdmin,
+            options: { private: privateWindow, siteRoot, confirmed: true },
+          });
+        }
+        finishExecution(outcome);
       } catch (error) {
         console.error("Failed to execute action", error);
         showToast("执行失败，请检查目标是否存在");
       }
     },
-    [showToast],
+    [finishExecution],
+  );
+
+  // 按序号执行后端缓存的第 n 个结果（从 1 开始）
+  const executeNth = useCallback(
+    async (n: number, runAsAdmin: boolean) => {
+      const target = state.results[n - 1];
+      if (
+        !target ||
+        target.action_id === "indexing" ||
+        target.action_id === "truncated"
+      ) {
+        return;
+      }
+
+      try {
+        let outcome = await invoke<ExecuteOutcome>("execute_nth", {
+          n,
+          runAsAdmin,
+        });
+        if (outcome === "needs_confirmation") {
+          if (!window.confirm(confirmationMessage(target))) {
+            return;
+          }
+          outcome = await invoke<ExecuteOutcome>("execute_nth", {
+            n,
+            runAsAdmin,
+            confirmed: true,
+          });
+        }
+        finishExecution(outcome);
+      } catch (error) {
+        console.error("Failed to execute result by number", error);
+        showToast("执行失败，请检查目标是否存在");
+      }
+    },
+    [finishExecution, showToast, state.results],
   );
 
   const stepSelection = useCallback(
@@ -411,6 +624,32 @@ export const LauncherWindow = () => {
         void openSettingsWindow();
         return;
       }
+      const cycleHotkey = state.settings?.mode_cycle_hotkey ?? "Tab";
+      if (cycleHotkey && matchesKeyCombo(event, cycleHotkey)) {
+        event.preventDefault();
+        cycleMode();
+        return;
+      }
+      // Ctrl+Shift+Delete 卸载选中的应用
+      if (
+        (event.ctrlKey || event.metaKey) &&
+        event.shiftKey &&
+        event.key === "Delete"
+      ) {
+        const selected = state.results[state.selectedIndex];
+        if (selected?.action_id === "app" || selected?.action_id === "uwp") {
+          event.preventDefault();
+          void executeSelected({ ...selected, id: `${selected.id}:uninstall` });
+          return;
+        }
+      }
+      // Alt+1..9 直接执行对应序号的结果，同时按 Ctrl 以管理员身份运行
+      const digit = /^Digit([1-9])$/.exec(event.code);
+      if (event.altKey && !event.shiftKey && digit) {
+        event.preventDefault();
+        void executeNth(Number(digit[1]), event.ctrlKey || event.metaKey);
+        return;
+      }
       if (event.key === "ArrowDown") {
         event.preventDefault();
         stepSelection(1);
@@ -425,15 +664,87 @@ export const LauncherWindow = () => {
 
       if (event.key === "Enter") {
         event.preventDefault();
+        const selected = state.results[state.selectedIndex];
+        // Ctrl+Alt+Shift+Enter 将 Markdown 链接粘贴到打开启动器前的窗口
+        if (
+          state.settings?.enable_paste_action &&
+          (event.ctrlKey || event.metaKey) &&
+          event.altKey &&
+          event.shiftKey &&
+          (selected?.action_id === "bookmark" || selected?.action_id === "url")
+        ) {
+          void invoke<ExecuteOutcome>("execute_action", {
+            id: `${selected.id}:markdown`,
+            runAsAdmin: false,
+            options: { paste: true },
+          })
+            .then(finishExecution)
+            .catch((error: unknown) => {
+              console.error("Failed to paste markdown link", error);
+              showToast("粘贴失败");
+            });
+          return;
+        }
+        // Ctrl+Shift+Enter 将网址/收藏复制为 Markdown 链接，窗口保持打开
+        if (
+          (event.ctrlKey || event.metaKey) &&
+          event.shiftKey &&
+          (selected?.action_id === "bookmark" || selected?.action_id === "url")
+        ) {
+          void invoke<ExecuteOutcome>("execute_action", {
+            id: `${selected.id}:markdown`,
+            runAsAdmin: false,
+          })
+            .then(() => showToast("已复制 Markdown 链接"))
+            .catch((error: unknown) => {
+              console.error("Failed to copy markdown link", error);
+              showToast("复制失败");
+            });
+          return;
+        }
+        // Alt+Shift+Enter 打开网址/收藏所在网站的首页
+        if (event.altKey && event.shiftKey) {
+          void executeSelected(selected, false, false, true);
+          return;
+        }
+        // Ctrl+Alt+Enter 在书签管理器中打开收藏所在的文件夹
+        if (
+          event.altKey &&
+          (event.ctrlKey || event.metaKey) &&
+          selected?.action_id === "bookmark"
+        ) {
+          void executeSelected({ ...selected, id: `${selected.id}:folder` });
+          return;
+        }
+        // Alt+Enter 在浏览器书签管理器中定位收藏
+        if (event.altKey && selected?.action_id === "bookmark") {
+          void executeSelected({ ...selected, id: `${selected.id}:edit` });
+          return;
+        }
+        // Alt+Enter 在文件夹中打开终端
+        if (event.altKey && selected?.action_id === "folder") {
+          void executeSelected(
+            { ...selected, id: `${selected.id}:terminal` },
+            event.ctrlKey || event.metaKey,
+          );
+          return;
+        }
         const runAsAdmin = event.ctrlKey || event.metaKey;
-        void executeSelected(state.results[state.selectedIndex], runAsAdmin);
+        // Shift+Enter 在浏览器隐私窗口中打开网址/收藏
+        const privateWindow = event.shiftKey;
+        void executeSelected(selected, runAsAdmin, privateWindow);
       }
     },
     [
+      cycleMode,
+      executeNth,
       executeSelected,
+      finishExecution,
       openSettingsWindow,
+      showToast,
       state.results,
       state.selectedIndex,
+      state.settings,
       stepSelection,
     ],
   );
@@ -443,12 +754,26 @@ export const LauncherWindow = () => {
       case "app":
       case "uwp":
         return "应用";
+      case "folder":
+        return "文件夹";
+      case "script":
+        return "脚本";
+      case "custom":
+        return "自定义";
       case "bookmark":
         return "书签";
       case "url":
         return "网址";
+      case "path":
+        return "文件";
       case "search":
         return "搜索";
+      case "run":
+        return "命令";
+      case "indexing":
+        return "索引";
+      case "truncated":
+        return "提示";
       default:
         return "其他";
     }
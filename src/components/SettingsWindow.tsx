@@ -5,7 +5,7 @@ import type { UnlistenFn } from "@tauri-apps/api/event";
 import {
   SETTINGS_UPDATED_EVENT,
 } from "../constants/events";
-import type { AppSettings } from "../types";
+import type { AppSettings, ProfileOverrides } from "../types";
 
 const TABS = [
   { id: "general", label: "常规", icon: "⚙️", desc: "通用行为设置" },
@@ -80,6 +80,12 @@ export const SettingsWindow = () => {
     [settings, updateSetting],
   );
 
+  // 当前配置方案覆盖的字段在基础配置中修改不会生效，锁定并显示方案中的值
+  const activeOverrides = settings?.profiles?.[settings.active_profile];
+  const isOverridden = (key: keyof ProfileOverrides) =>
+    activeOverrides?.[key] != null;
+  const overriddenHint = `由配置方案“${settings?.active_profile}”覆盖，请在该方案中修改`;
+
   if (loading) {
     return <div className="settings-loading">正在加载设置...</div>;
   }
@@ -161,7 +167,12 @@ export const SettingsWindow = () => {
                   <textarea
                     className="settings-textarea"
                     rows={8}
-                    value={(settings?.system_tool_exclusions || []).join('\n')}
+                    value={(
+                      activeOverrides?.system_tool_exclusions ??
+                      settings?.system_tool_exclusions ??
+                      []
+                    ).join('\n')}
+                    disabled={isOverridden("system_tool_exclusions")}
                     onChange={(e) => {
                       const paths = e.target.value
                         .split('\n')
@@ -172,7 +183,9 @@ export const SettingsWindow = () => {
                     placeholder="c:\windows\system32&#10;c:\windows\syswow64"
                   />
                   <p className="settings-hint">
-                    添加需要过滤的目录路径，每行一个。应用会自动过滤这些目录下的程序。
+                    {isOverridden("system_tool_exclusions")
+                      ? overriddenHint
+                      : "添加需要过滤的目录路径，每行一个。应用会自动过滤这些目录下的程序。"}
                   </p>
                 </div>
               </div>
@@ -258,6 +271,38 @@ export const SettingsWindow = () => {
                 </div>
               </div>
 
+              <div className="settings-card">
+                <div className="settings-card__header">
+                  <div>
+                    <h3 className="settings-card__title">搜索引擎</h3>
+                    <p className="settings-card__subtitle">
+                      网页搜索使用的地址，用 {"{query}"} 表示搜索词
+                    </p>
+                  </div>
+                </div>
+                <div className="settings-input-row">
+                  <input
+                    type="text"
+                    className="settings-input"
+                    value={
+                      activeOverrides?.search_url_template ??
+                      settings?.search_url_template ??
+                      ""
+                    }
+                    disabled={isOverridden("search_url_template")}
+                    onChange={(e) =>
+                      updateSetting("search_url_template", e.target.value)
+                    }
+                    placeholder="https://google.com/search?q={query}"
+                  />
+                  <p className="settings-hint">
+                    {isOverridden("search_url_template")
+                      ? overriddenHint
+                      : "留空则使用 Google"}
+                  </p>
+                </div>
+              </div>
+
               <div className="settings-card">
                 <div className="settings-card__header">
                   <div>
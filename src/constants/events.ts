@@ -2,4 +2,5 @@ export const HIDE_WINDOW_EVENT = "hide_window";
 export const OPEN_SETTINGS_EVENT = "open_settings";
 export const SETTINGS_UPDATED_EVENT = "settings_updated";
 export const FOCUS_INPUT_EVENT = "focus_input";
+export const INDEX_READY_EVENT = "index_ready";
 export const WINDOW_OPACITY_PREVIEW_EVENT = "window_opacity_preview";
@@ -29,6 +29,20 @@ export const DEFAULT_MODE_CONFIGS: Record<ModeId, ModeConfig> = {
     description: "仅使用网络搜索",
     placeholder: "搜索模式 · 输入关键词，在浏览器中搜索",
   },
+  run: {
+    id: "run",
+    label: "运行模式",
+    prefix: ">",
+    description: "执行命令行（需在设置中启用）",
+    placeholder: "运行模式 · 输入要执行的命令",
+  },
+  sys: {
+    id: "sys",
+    label: "系统应用模式",
+    prefix: "sys",
+    description: "搜索全部 UWP 应用，包括系统组件（部分可能无法启动）",
+    placeholder: "系统应用模式 · 输入应用或组件名称",
+  },
 };
 
 // 根据设置构造当前模式配置，允许自定义前缀
@@ -53,6 +67,8 @@ export const buildModeConfigsFromSettings = (
       ...DEFAULT_MODE_CONFIGS.search,
       prefix: settings.prefix_search || DEFAULT_MODE_CONFIGS.search.prefix,
     },
+    run: DEFAULT_MODE_CONFIGS.run,
+    sys: DEFAULT_MODE_CONFIGS.sys,
   };
 };
 
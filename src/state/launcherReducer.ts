@@ -8,6 +8,7 @@ export const initialLauncherState: LauncherState = {
   selectedIndex: 0,
   toastMessage: null,
   settings: null,
+  modes: [],
   activeMode: DEFAULT_MODE_CONFIGS.all,
   isModePrefixOnly: false,
   isComposing: false,
@@ -31,6 +32,15 @@ export const launcherReducer = (
         ...state,
         results: action.payload,
       };
+    case "SET_RESULT_ICON":
+      return {
+        ...state,
+        results: state.results.map((item) =>
+          item.id === action.payload.id
+            ? { ...item, icon: action.payload.icon }
+            : item,
+        ),
+      };
     case "SET_SELECTED_INDEX":
       return {
         ...state,
@@ -46,6 +56,11 @@ export const launcherReducer = (
         ...state,
         settings: action.payload,
       };
+    case "SET_MODES":
+      return {
+        ...state,
+        modes: action.payload,
+      };
     case "SET_COMPOSING":
       return {
         ...state,
@@ -1,3 +1,4 @@
+// id 对同一条目或输入保持稳定，可用于跨查询保留选中项
 export type SearchResult = {
   id: string;
   title: string;
@@ -5,6 +6,22 @@ export type SearchResult = {
   icon: string;
   score: number;
   action_id: string;
+  // 稳定的分组标识：application / bookmark / web / command / other
+  group: string;
+  // 仅在调试模式下返回的得分构成
+  score_breakdown: ScoreBreakdown | null;
+  // 启动次数，没有使用统计时为 null
+  launch_count: number | null;
+};
+
+export type ScoreBreakdown = {
+  base_fuzzy: number;
+  keyword_penalty: number;
+  frecency_bonus: number;
+  prefix_boost: number;
+  session_boost: number;
+  score_override: number;
+  deprioritize_penalty: number;
 };
 
 export type AppSettings = {
@@ -21,9 +38,111 @@ export type AppSettings = {
   force_english_input: boolean;
   debug_mode: boolean;
   system_tool_exclusions: string[];
+  scoring_weights: ScoringWeights;
+  confirm_admin_launch: boolean;
+  skip_dead_shortcuts: boolean;
+  custom_launchers: CustomLauncher[];
+  max_match_query_chars: number;
+  bookmark_browser: string;
+  open_urls_in_background: boolean;
+  max_indexed_apps: number;
+  max_indexed_bookmarks: number;
+  max_inline_icons: number;
+  hidden_ids: string[];
+  smart_case: boolean;
+  enable_run_mode: boolean;
+  run_shell: "cmd" | "powershell";
+  launch_order: "launch_then_hide" | "hide_then_launch";
+  launch_focus_delay_ms: number;
+  strip_tracking_params: boolean;
+  tracking_param_denylist: string[];
+  show_match_source: boolean;
+  folder_separator: string;
+  extra_app_folders: string[];
+  // 可选 "bat" / "cmd" / "ps1"，为空时不索引脚本
+  index_script_extensions: string[];
+  min_match_score: number;
+  terminal: "windows_terminal" | "cmd";
+  enable_opera_bookmarks: boolean;
+  enable_opera_gx_bookmarks: boolean;
+  use_elevation_broker: boolean;
+  // 为 null 时读取全部 Chrome 配置文件
+  chrome_profile_allowlist: string[] | null;
+  max_icon_bytes: number;
+  // 离线模式：不显示网络搜索与网址结果，书签仍保留
+  offline_mode: boolean;
+  custom_bookmark_files: CustomBookmarkFile[];
+  mode_cycle_hotkey: string;
+  launch_on_active_monitor: boolean;
+  score_overrides: Record<string, number>;
+  extra_app_dir_excludes: string[];
+  quick_open_hotkey: string;
+  capture_crashes: boolean;
+  enable_cjk_transliteration: boolean;
+  hide_after_launch: boolean;
+  start_menu_max_depth: number;
+  show_last_used: boolean;
+  index_apps_folder: boolean;
+  confirm_url_open: boolean;
+  locale: string;
+  active_profile: string;
+  profiles: Record<string, ProfileOverrides>;
+  app_dir_allowlist: string[] | null;
+  show_truncation_hint: boolean;
+  enable_paste_action: boolean;
+  search_url_template: string;
+};
+
+export type ExecuteOutcome =
+  | "executed"
+  | "executed_in_background"
+  | "executed_keep_open"
+  | "needs_confirmation";
+
+export type CustomLauncher = {
+  id: string;
+  name: string;
+  keywords: string[];
+  target: string;
+  arguments?: string | null;
+  working_directory?: string | null;
+  icon_path?: string | null;
+};
+
+export type ProfileOverrides = {
+  system_tool_exclusions?: string[] | null;
+  extra_app_folders?: string[] | null;
+  extra_app_dir_excludes?: string[] | null;
+  search_url_template?: string | null;
 };
 
-export type ModeId = "all" | "bookmark" | "app" | "search";
+export type CustomBookmarkFile = {
+  name: string;
+  path: string;
+};
+
+export type ScoringWeights = {
+  keyword_penalty: number;
+  description_penalty: number;
+  bookmark_folder_penalty: number;
+  bookmark_url_penalty: number;
+  bookmark_keyword_penalty: number;
+  prefix_boost: number;
+  substring_boost: number;
+  recent_launch_boost: number;
+  deprioritize_patterns: string[];
+  deprioritize_penalty: number;
+};
+
+export type ModeId = "all" | "bookmark" | "app" | "search" | "run" | "sys";
+
+// 后端 get_modes 返回的模式信息，顺序即循环切换顺序
+export type ModeInfo = {
+  id: ModeId;
+  name: string;
+  prefix: string | null;
+  enabled: boolean;
+};
 
 export type ModeConfig = {
   id: ModeId;
@@ -46,6 +165,7 @@ export type LauncherState = {
   selectedIndex: number;
   toastMessage: string | null;
   settings: AppSettings | null;
+  modes: ModeInfo[];
   activeMode: ModeConfig;
   isModePrefixOnly: boolean;
   isComposing: boolean;
@@ -62,8 +182,75 @@ export type LauncherAction =
     };
   }
   | { type: "SET_RESULTS"; payload: SearchResult[] }
+  | { type: "SET_RESULT_ICON"; payload: { id: string; icon: string } }
   | { type: "SET_SELECTED_INDEX"; payload: number }
   | { type: "SET_TOAST"; payload: string | null }
   | { type: "SET_SETTINGS"; payload: AppSettings }
+  | { type: "SET_MODES"; payload: ModeInfo[] }
   | { type: "SET_COMPOSING"; payload: boolean }
   | { type: "RESET_SEARCH" };
+
+export type BenchmarkResult = {
+  iterations: number;
+  entry_count: number;
+  result_count: number;
+  min_ms: number;
+  median_ms: number;
+  max_ms: number;
+};
+
+export type SourceDiagnostics = {
+  considered: number;
+  matched: number;
+  top_score: number | null;
+};
+
+export type QueryDiagnostics = {
+  mode: string;
+  apps: SourceDiagnostics;
+  custom_launchers: SourceDiagnostics;
+  bookmarks: SourceDiagnostics;
+  url_detected: boolean;
+  web_search_offered: boolean;
+  matching_skipped: boolean;
+  result_count: number;
+  elapsed_ms: number;
+};
+
+export type UsageRecord = {
+  id: string;
+  launch_count: number;
+  last_used: number;
+};
+
+export type ApplicationDetails = {
+  id: string;
+  name: string;
+  path: string;
+  source_path: string | null;
+  app_type: "Win32" | "Uwp" | "Folder" | "Script";
+  icon_b64: string;
+  icon_source: string | null;
+  icon_index: number;
+  description: string | null;
+  keywords: string[];
+  working_directory: string | null;
+  arguments: string | null;
+  installed_at: number | null;
+  uninstall_string: string | null;
+};
+
+// get_result_details 的返回值，按 kind 区分结果类型
+export type ResultDetails =
+  | ({ kind: "application" } & ApplicationDetails)
+  | {
+      kind: "bookmark";
+      title: string;
+      url: string;
+      folder_path: string;
+      keywords: string[];
+    }
+  | ({ kind: "custom_launcher" } & CustomLauncher)
+  | { kind: "url"; url: string }
+  | { kind: "path"; path: string }
+  | { kind: "command"; program: string; arguments: string };